@@ -1,10 +1,18 @@
-use crate::models::Method;
+use crate::models::{Method, RequestOptions};
 use reqwest::{
     blocking::Client,
     header::{HeaderMap, HeaderName, HeaderValue},
+    redirect::Policy,
 };
 use serde_json::Value;
-use std::{str::FromStr, time::Instant};
+use std::{str::FromStr, time::Duration, time::Instant};
+
+mod unix;
+
+pub use unix::parse_url as parse_unix_url;
+
+// reqwest's default, applied to `unix:` sockets as well
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
 
 #[derive(Debug, Clone)]
 pub struct ApiResponse {
@@ -26,18 +34,35 @@ pub fn perform_request(
     url: &str,
     body: &str,
     headers_vec: Vec<(String, String)>,
+    options: RequestOptions,
 ) -> Result<ApiResponse, ApiError> {
-    let client = Client::new();
-
-    let mut headers = HeaderMap::new();
-    for (key, value) in headers_vec {
-        if let Ok(h_name) = HeaderName::from_str(&key) {
-            if let Ok(h_val) = HeaderValue::from_str(&value) {
-                headers.insert(h_name, h_val);
-            }
-        }
+    // `unix:/path/to.sock:/request/path` targets a local daemon instead of a TCP host
+    if let Some((socket_path, path)) = unix::parse_url(url) {
+        return unix::perform_request(
+            method,
+            &socket_path,
+            &path,
+            body,
+            build_headers(headers_vec),
+            options.follow_redirects,
+        );
     }
 
+    let client = Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .danger_accept_invalid_certs(options.insecure)
+        .redirect(if options.follow_redirects {
+            Policy::default()
+        } else {
+            Policy::none()
+        })
+        .gzip(options.compressed)
+        .brotli(options.compressed)
+        .deflate(options.compressed)
+        .build()
+        .map_err(|e| ApiError::RequestFailed(e.to_string()))?;
+    let headers = build_headers(headers_vec);
+
     let request_builder = match method {
         Method::POST => client.post(url).headers(headers).body(body.to_string()),
         Method::PUT => client.put(url).headers(headers).body(body.to_string()),
@@ -52,7 +77,7 @@ pub fn perform_request(
 
     match result {
         Ok(response) => {
-            let status_code = response.status();
+            let status_code = response.status().as_u16();
             let size = response.content_length().unwrap_or(0);
 
             let mut headers_str = String::new();
@@ -60,25 +85,100 @@ pub fn perform_request(
                 headers_str.push_str(&format!("{}: {}\n", key, value.to_str().unwrap_or("")));
             }
 
-            // Pretty Print JSON
-            let body_str = match response.json::<Value>() {
-                Ok(json) => serde_json::to_string_pretty(&json).unwrap_or_default(),
-                Err(_) => "Error: Could not parse JSON".to_string(),
-            };
-
-            Ok(ApiResponse {
-                body: body_str,
-                headers: headers_str,
-                status: format!(
-                    "{} {}",
-                    status_code.as_u16(),
-                    status_code.canonical_reason().unwrap_or("")
-                ),
-                status_code: status_code.as_u16(),
-                time: format!("{:.2?}", duration),
-                size: format!("{} bytes", size),
-            })
+            let body_bytes = response.bytes().unwrap_or_default();
+
+            Ok(build_response(
+                status_code,
+                None,
+                headers_str,
+                &body_bytes,
+                duration,
+                size,
+            ))
         }
         Err(e) => Err(ApiError::RequestFailed(e.to_string())),
     }
 }
+
+/// Options that have no effect on `url`, by their labels in the Options tab.
+/// `unix:` sockets carry no TLS and are read without decompression.
+pub fn ignored_options(url: &str, options: RequestOptions) -> Vec<&'static str> {
+    if unix::parse_url(url).is_none() {
+        return Vec::new();
+    }
+    let mut ignored = Vec::new();
+    if options.insecure {
+        ignored.push("Skip TLS Verification");
+    }
+    if options.compressed {
+        ignored.push("Compressed Response");
+    }
+    ignored
+}
+
+/// Splits the `key: value` lines stored for response headers back into pairs.
+pub fn parse_headers(raw: &str) -> Vec<(String, String)> {
+    raw.lines()
+        .filter_map(|line| line.split_once(':'))
+        .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+        .collect()
+}
+
+/// Reads back a `time` string formatted as `{:.2?}` from a `Duration`, in milliseconds.
+pub fn parse_duration_ms(time: &str) -> Option<f64> {
+    let time = time.trim();
+    let split = time.find(|c: char| !c.is_ascii_digit() && c != '.')?;
+    let (value, unit) = time.split_at(split);
+    let value: f64 = value.parse().ok()?;
+
+    let factor = match unit {
+        "ns" => 0.000_001,
+        "µs" | "us" => 0.001,
+        "ms" => 1.0,
+        "s" => 1000.0,
+        _ => return None,
+    };
+    Some(value * factor)
+}
+
+fn build_headers(headers_vec: Vec<(String, String)>) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    for (key, value) in headers_vec {
+        if let Ok(h_name) = HeaderName::from_str(&key) {
+            if let Ok(h_val) = HeaderValue::from_str(&value) {
+                headers.insert(h_name, h_val);
+            }
+        }
+    }
+    headers
+}
+
+fn build_response(
+    status_code: u16,
+    reason: Option<&str>,
+    headers: String,
+    body: &[u8],
+    duration: Duration,
+    size: u64,
+) -> ApiResponse {
+    let reason = reqwest::StatusCode::from_u16(status_code)
+        .ok()
+        .and_then(|s| s.canonical_reason())
+        .or(reason)
+        .unwrap_or("");
+
+    // Pretty Print JSON; anything else is shown as it came
+    let body_str = match serde_json::from_slice::<Value>(body) {
+        Ok(json) => serde_json::to_string_pretty(&json).unwrap_or_default(),
+        Err(_) => String::from_utf8_lossy(body).into_owned(),
+    };
+
+    ApiResponse {
+        body: body_str,
+        headers,
+        status: format!("{} {}", status_code, reason),
+        status_code,
+        time: format!("{:.2?}", duration),
+        size: format!("{} bytes", size),
+    }
+}
@@ -0,0 +1,313 @@
+use super::{ApiError, ApiResponse, REQUEST_TIMEOUT, build_response};
+use crate::models::Method;
+use reqwest::header::{CONNECTION, CONTENT_LENGTH, HOST, HeaderMap, HeaderValue};
+use std::{
+    io::{BufRead, BufReader, ErrorKind, Read, Write},
+    os::unix::net::UnixStream,
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+const URL_PREFIX: &str = "unix:";
+// As many hops as reqwest's default redirect policy allows
+const MAX_REDIRECTS: usize = 10;
+// Guards against a bogus Content-Length or a stream that never ends
+const MAX_BODY_SIZE: u64 = 100 * 1024 * 1024;
+
+/// Splits `unix:/var/run/docker.sock:/v1.43/containers/json` into the socket
+/// path and the request path. The request path defaults to `/`.
+pub fn parse_url(url: &str) -> Option<(PathBuf, String)> {
+    let rest = url.trim().strip_prefix(URL_PREFIX)?;
+
+    let (socket, path) = match rest.find(':') {
+        Some(idx) => (&rest[..idx], &rest[idx + 1..]),
+        None => (rest, "/"),
+    };
+
+    if socket.is_empty() {
+        return None;
+    }
+
+    let path = if path.starts_with('/') {
+        path.to_string()
+    } else {
+        format!("/{}", path)
+    };
+
+    Some((PathBuf::from(socket), path))
+}
+
+/// Redirects are followed when `follow_redirects` is set and the `Location`
+/// is a path, which stays on the same socket; others come back as they are.
+pub fn perform_request(
+    method: Method,
+    socket_path: &Path,
+    path: &str,
+    body: &str,
+    mut headers: HeaderMap,
+    follow_redirects: bool,
+) -> Result<ApiResponse, ApiError> {
+    let failed =
+        |e: std::io::Error| ApiError::RequestFailed(format!("{}: {}", socket_path.display(), e));
+
+    if !headers.contains_key(HOST) {
+        headers.insert(HOST, HeaderValue::from_static("localhost"));
+    }
+    // Reading until EOF is the simplest way to find the end of an unframed body
+    headers.insert(CONNECTION, HeaderValue::from_static("close"));
+
+    let start_time = Instant::now();
+    // Like reqwest's timeout, this covers the whole request, redirects included
+    let deadline = start_time + REQUEST_TIMEOUT;
+    let (mut method, mut path, mut body) = (method, path.to_string(), body);
+    let mut hops = 0;
+    let (status_code, reason, response_headers, body_bytes) = loop {
+        let response =
+            exchange(method, socket_path, &path, body, &headers, deadline).map_err(failed)?;
+        let (status_code, _, response_headers, _) = &response;
+        let location = response_headers
+            .iter()
+            .find(|(key, _)| key == "location")
+            .map(|(_, value)| value.clone());
+
+        match location {
+            Some(location)
+                if follow_redirects
+                    && matches!(status_code, 301 | 302 | 303 | 307 | 308)
+                    && location.starts_with('/')
+                    && hops < MAX_REDIRECTS =>
+            {
+                // Like browsers and reqwest, only 307 and 308 repeat the method and body
+                if !matches!(status_code, 307 | 308) && method != Method::GET {
+                    method = Method::GET;
+                    body = "";
+                }
+                path = location;
+                hops += 1;
+            }
+            _ => break response,
+        }
+    };
+    let duration = start_time.elapsed();
+
+    let mut headers_str = String::new();
+    for (key, value) in &response_headers {
+        headers_str.push_str(&format!("{}: {}\n", key, value));
+    }
+
+    Ok(build_response(
+        status_code,
+        Some(&reason),
+        headers_str,
+        &body_bytes,
+        duration,
+        body_bytes.len() as u64,
+    ))
+}
+
+type Head = (u16, String, Vec<(String, String)>);
+type Exchange = (u16, String, Vec<(String, String)>, Vec<u8>);
+
+/// Sends one request on a new connection and reads the whole response.
+fn exchange(
+    method: Method,
+    socket_path: &Path,
+    path: &str,
+    body: &str,
+    headers: &HeaderMap,
+    deadline: Instant,
+) -> std::io::Result<Exchange> {
+    let mut headers = headers.clone();
+    // Same rules as reqwest: only POST, PUT and PATCH carry a body
+    let body = match method {
+        Method::POST | Method::PUT | Method::PATCH => {
+            headers.insert(CONTENT_LENGTH, HeaderValue::from(body.len()));
+            body
+        }
+        Method::GET | Method::DELETE => "",
+    };
+
+    let mut head = format!("{} {} HTTP/1.1\r\n", method.as_str(), path);
+    for (key, value) in headers.iter() {
+        head.push_str(&format!("{}: {}\r\n", key, value.to_str().unwrap_or("")));
+    }
+    head.push_str("\r\n");
+
+    // A daemon that accepts but never answers, or answers a byte at a time,
+    // would otherwise hang the send
+    let mut stream = Deadline {
+        stream: UnixStream::connect(socket_path)?,
+        deadline,
+    };
+    stream.write_all(head.as_bytes())?;
+    stream.write_all(body.as_bytes())?;
+
+    let mut reader = BufReader::new(stream);
+    let (status_code, reason, response_headers) = read_head(&mut reader)?;
+    let body_bytes = read_body(&mut reader, status_code, &response_headers)?;
+    Ok((status_code, reason, response_headers, body_bytes))
+}
+
+/// A socket whose reads and writes fail once `deadline` has passed.
+struct Deadline {
+    stream: UnixStream,
+    deadline: Instant,
+}
+
+impl Deadline {
+    fn remaining(&self) -> std::io::Result<std::time::Duration> {
+        self.deadline
+            .checked_duration_since(Instant::now())
+            .filter(|left| !left.is_zero())
+            .ok_or_else(|| std::io::Error::new(ErrorKind::TimedOut, "request timed out"))
+    }
+}
+
+impl Read for Deadline {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.stream.set_read_timeout(Some(self.remaining()?))?;
+        self.stream.read(buf)
+    }
+}
+
+impl Write for Deadline {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.stream.set_write_timeout(Some(self.remaining()?))?;
+        self.stream.write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.stream.flush()
+    }
+}
+
+fn invalid(msg: &str) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, msg.to_string())
+}
+
+fn truncated() -> std::io::Error {
+    invalid("connection closed before the response was complete")
+}
+
+fn read_line(reader: &mut impl BufRead) -> std::io::Result<String> {
+    let mut line = String::new();
+    if reader.read_line(&mut line)? == 0 {
+        return Err(truncated());
+    }
+    Ok(line.trim_end_matches(['\r', '\n']).to_string())
+}
+
+fn read_head(reader: &mut impl BufRead) -> std::io::Result<Head> {
+    loop {
+        let status_line = read_line(reader)?;
+        let mut parts = status_line.splitn(3, ' ');
+        let version = parts.next().unwrap_or("");
+        if !version.starts_with("HTTP/") {
+            return Err(invalid("malformed HTTP status line"));
+        }
+        let status_code = parts
+            .next()
+            .and_then(|code| code.parse::<u16>().ok())
+            .ok_or_else(|| invalid("malformed HTTP status code"))?;
+        let reason = parts.next().unwrap_or("").to_string();
+
+        let mut headers = Vec::new();
+        loop {
+            let line = read_line(reader)?;
+            if line.is_empty() {
+                break;
+            }
+            if let Some((key, value)) = line.split_once(':') {
+                headers.push((key.trim().to_lowercase(), value.trim().to_string()));
+            }
+        }
+
+        // Interim responses (100 Continue, 103 Early Hints) precede the real one
+        if (100..200).contains(&status_code) && status_code != 101 {
+            continue;
+        }
+
+        return Ok((status_code, reason, headers));
+    }
+}
+
+fn read_body(
+    reader: &mut impl BufRead,
+    status_code: u16,
+    headers: &[(String, String)],
+) -> std::io::Result<Vec<u8>> {
+    if status_code == 204 || status_code == 304 {
+        return Ok(Vec::new());
+    }
+
+    let header = |name: &str| {
+        headers
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    };
+
+    if header("transfer-encoding").is_some_and(|te| te.to_lowercase().contains("chunked")) {
+        return read_chunked(reader);
+    }
+
+    match header("content-length").and_then(|len| len.parse::<u64>().ok()) {
+        Some(len) => {
+            if len > MAX_BODY_SIZE {
+                return Err(too_large());
+            }
+            let body = read_at_most(reader, len)?;
+            if (body.len() as u64) < len {
+                return Err(truncated());
+            }
+            Ok(body)
+        }
+        None => {
+            let body = read_at_most(reader, MAX_BODY_SIZE + 1)?;
+            if body.len() as u64 > MAX_BODY_SIZE {
+                return Err(too_large());
+            }
+            Ok(body)
+        }
+    }
+}
+
+/// Reads up to `limit` bytes, growing the buffer only as data arrives.
+fn read_at_most(reader: &mut impl BufRead, limit: u64) -> std::io::Result<Vec<u8>> {
+    let mut body = Vec::new();
+    reader.take(limit).read_to_end(&mut body)?;
+    Ok(body)
+}
+
+fn too_large() -> std::io::Error {
+    invalid(&format!(
+        "response body is larger than {} MB",
+        MAX_BODY_SIZE / (1024 * 1024)
+    ))
+}
+
+fn read_chunked(reader: &mut impl BufRead) -> std::io::Result<Vec<u8>> {
+    let mut body = Vec::new();
+    loop {
+        let size_line = read_line(reader)?;
+        let size_hex = size_line.split(';').next().unwrap_or("").trim();
+        let size =
+            usize::from_str_radix(size_hex, 16).map_err(|_| invalid("malformed chunk size"))?;
+
+        if size == 0 {
+            // Skip optional trailers
+            while !read_line(reader)?.is_empty() {}
+            return Ok(body);
+        }
+
+        if size as u64 > MAX_BODY_SIZE - body.len() as u64 {
+            return Err(too_large());
+        }
+        let chunk = read_at_most(reader, size as u64)?;
+        if chunk.len() < size {
+            return Err(truncated());
+        }
+        body.extend_from_slice(&chunk);
+        read_line(reader)?;
+    }
+}
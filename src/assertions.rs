@@ -0,0 +1,273 @@
+use crate::api::{self, ApiResponse};
+use crate::contract;
+use crate::jsonpath;
+use crate::models::{Assertion, AssertionKind, AssertionResult};
+use crate::schema;
+use regex::Regex;
+use serde_json::Value;
+use std::path::Path;
+
+/// Runs a request's assertions against its response, in order.
+pub fn evaluate(assertions: &[Assertion], response: &ApiResponse) -> Vec<AssertionResult> {
+    let body = serde_json::from_str::<Value>(&response.body).ok();
+    assertions
+        .iter()
+        .map(|assertion| {
+            let outcome = check(assertion, response, body.as_ref());
+            AssertionResult {
+                description: describe(assertion),
+                passed: outcome.is_ok(),
+                message: outcome.unwrap_or_else(|e| e),
+            }
+        })
+        .collect()
+}
+
+pub fn describe(assertion: &Assertion) -> String {
+    let target = assertion.target.trim();
+    let expected = assertion.expected.trim();
+    match assertion.kind {
+        AssertionKind::StatusEquals => format!("status is {}", expected),
+        AssertionKind::StatusInRange => format!("status is in {}", expected),
+        AssertionKind::HeaderExists => format!("header `{}` is present", target),
+        AssertionKind::HeaderMatches => format!("header `{}` matches `{}`", target, expected),
+        AssertionKind::JsonPathEquals => format!("`{}` equals {}", target, expected),
+        AssertionKind::JsonPathContains => format!("`{}` contains {}", target, expected),
+        AssertionKind::MatchesSchema => format!("body matches {}", target),
+        AssertionKind::TimeBelow => format!("response time is below {} ms", expected),
+    }
+}
+
+/// `Ok` carries the actual value, `Err` the reason for failing.
+fn check(
+    assertion: &Assertion,
+    response: &ApiResponse,
+    body: Option<&Value>,
+) -> Result<String, String> {
+    let target = assertion.target.trim();
+    let expected = assertion.expected.trim();
+    let status = response.status_code;
+
+    match assertion.kind {
+        AssertionKind::StatusEquals => {
+            let expected: u16 = expected
+                .parse()
+                .map_err(|_| format!("`{}` is not a status code", expected))?;
+            pass_if(status == expected, format!("status {}", status))
+        }
+        AssertionKind::StatusInRange => {
+            let (low, high) = status_range(expected)?;
+            pass_if((low..=high).contains(&status), format!("status {}", status))
+        }
+        AssertionKind::HeaderExists => match header(response, target) {
+            Some(value) => Ok(value),
+            None => Err("header is missing".to_string()),
+        },
+        AssertionKind::HeaderMatches => {
+            let regex = Regex::new(expected).map_err(|e| format!("invalid pattern: {}", e))?;
+            let value = header(response, target).ok_or("header is missing")?;
+            pass_if(regex.is_match(&value), value)
+        }
+        AssertionKind::JsonPathEquals | AssertionKind::JsonPathContains => {
+            let body = body.ok_or("response body is not JSON")?;
+            let matches = jsonpath::select(body, target)?;
+            if matches.is_empty() {
+                return Err("path matched nothing".to_string());
+            }
+            // Unquoted text that is not valid JSON is compared as a string
+            let expected = serde_json::from_str::<Value>(expected)
+                .unwrap_or_else(|_| Value::String(expected.to_string()));
+
+            let actual = match matches.as_slice() {
+                [single] => single.to_string(),
+                _ => Value::Array(matches.iter().map(|v| (*v).clone()).collect()).to_string(),
+            };
+            let passed = if assertion.kind == AssertionKind::JsonPathEquals {
+                matches.iter().all(|v| **v == expected)
+            } else {
+                matches.iter().any(|v| contains(v, &expected))
+            };
+            pass_if(passed, actual)
+        }
+        AssertionKind::MatchesSchema => {
+            let body = body.ok_or("response body is not JSON")?;
+            let schema = contract::load(Path::new(target))?;
+            let violations = schema::validate(&schema, &schema, body);
+            match violations.first() {
+                None => Ok("valid".to_string()),
+                Some(first) => {
+                    let pointer = if first.pointer.is_empty() {
+                        "/"
+                    } else {
+                        &first.pointer
+                    };
+                    let more = match violations.len() {
+                        1 => String::new(),
+                        n => format!(" (and {} more)", n - 1),
+                    };
+                    Err(format!("{}: {}{}", pointer, first.message, more))
+                }
+            }
+        }
+        AssertionKind::TimeBelow => {
+            let limit: f64 = expected
+                .parse()
+                .map_err(|_| format!("`{}` is not a number of milliseconds", expected))?;
+            let time = api::parse_duration_ms(&response.time).ok_or("response time unknown")?;
+            pass_if(time < limit, format!("{:.0} ms", time))
+        }
+    }
+}
+
+fn pass_if(passed: bool, actual: String) -> Result<String, String> {
+    if passed {
+        Ok(actual)
+    } else {
+        Err(format!("got {}", actual))
+    }
+}
+
+/// `200-299`, or `2xx` for a whole class.
+fn status_range(text: &str) -> Result<(u16, u16), String> {
+    let invalid = || format!("`{}` is not a range like 200-299 or 2xx", text);
+    if let Some((low, high)) = text.split_once('-') {
+        let low = low.trim().parse().map_err(|_| invalid())?;
+        let high = high.trim().parse().map_err(|_| invalid())?;
+        return Ok((low, high));
+    }
+    match text.to_ascii_lowercase().strip_suffix("xx") {
+        Some(class) => {
+            let class: u16 = class
+                .parse()
+                .ok()
+                .filter(|class| (1..=9).contains(class))
+                .ok_or_else(invalid)?;
+            Ok((class * 100, class * 100 + 99))
+        }
+        None => Err(invalid()),
+    }
+}
+
+fn header(response: &ApiResponse, name: &str) -> Option<String> {
+    api::parse_headers(&response.headers)
+        .into_iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value)
+}
+
+/// Substring for strings, element for arrays, key for objects.
+fn contains(value: &Value, expected: &Value) -> bool {
+    match (value, expected) {
+        (Value::String(text), Value::String(part)) => text.contains(part.as_str()),
+        (Value::Array(items), _) => items.contains(expected),
+        (Value::Object(map), Value::String(key)) => map.contains_key(key),
+        _ => value == expected,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response() -> ApiResponse {
+        ApiResponse {
+            body: r#"{"data": {"id": 7, "tags": ["a", "b"], "items": [{"name": "x"}, {"name": "x"}]}, "msg": "hello world"}"#
+                .to_string(),
+            headers: "content-type: application/json\nx-request-id: abc-123\n".to_string(),
+            status: "200 OK".to_string(),
+            status_code: 200,
+            time: "35.20ms".to_string(),
+            size: "100 bytes".to_string(),
+        }
+    }
+
+    fn run(kind: AssertionKind, target: &str, expected: &str) -> AssertionResult {
+        let assertion = Assertion {
+            kind,
+            target: target.to_string(),
+            expected: expected.to_string(),
+        };
+        evaluate(&[assertion], &response()).remove(0)
+    }
+
+    fn passes(kind: AssertionKind, target: &str, expected: &str) -> bool {
+        run(kind, target, expected).passed
+    }
+
+    #[test]
+    fn checks_the_status() {
+        assert!(passes(AssertionKind::StatusEquals, "", "200"));
+        assert!(!passes(AssertionKind::StatusEquals, "", "201"));
+        assert!(passes(AssertionKind::StatusInRange, "", "2xx"));
+        assert!(passes(AssertionKind::StatusInRange, "", "200-204"));
+        assert!(!passes(AssertionKind::StatusInRange, "", "3XX"));
+    }
+
+    #[test]
+    fn checks_headers_by_any_case() {
+        assert!(passes(AssertionKind::HeaderExists, "X-Request-Id", ""));
+        assert!(!passes(AssertionKind::HeaderExists, "etag", ""));
+        assert!(passes(
+            AssertionKind::HeaderMatches,
+            "x-request-id",
+            r"^abc-\d+$"
+        ));
+        assert_eq!(
+            run(AssertionKind::HeaderMatches, "etag", ".*").message,
+            "header is missing"
+        );
+    }
+
+    #[test]
+    fn compares_json_paths() {
+        assert!(passes(AssertionKind::JsonPathEquals, "$.data.id", "7"));
+        assert!(!passes(AssertionKind::JsonPathEquals, "$.data.id", "\"7\""));
+        assert!(passes(
+            AssertionKind::JsonPathEquals,
+            "$.data.items[*].name",
+            "x"
+        ));
+        assert!(passes(AssertionKind::JsonPathContains, "$.msg", "world"));
+        assert!(passes(AssertionKind::JsonPathContains, "$.data.tags", "b"));
+        assert!(passes(AssertionKind::JsonPathContains, "$.data", "id"));
+        assert_eq!(
+            run(AssertionKind::JsonPathEquals, "$.nope", "1").message,
+            "path matched nothing"
+        );
+    }
+
+    #[test]
+    fn validates_the_body_against_a_schema() {
+        let path =
+            std::env::temp_dir().join(format!("dispatch-schema-{}.json", std::process::id()));
+        std::fs::write(
+            &path,
+            r#"{"type": "object", "required": ["data", "missing"]}"#,
+        )
+        .unwrap();
+        let result = run(AssertionKind::MatchesSchema, path.to_str().unwrap(), "");
+        let _ = std::fs::remove_file(&path);
+
+        assert!(!result.passed);
+        assert!(result.message.starts_with("/: "));
+    }
+
+    #[test]
+    fn compares_the_response_time() {
+        assert!(passes(AssertionKind::TimeBelow, "", "100"));
+        assert_eq!(run(AssertionKind::TimeBelow, "", "10").message, "got 35 ms");
+    }
+
+    #[test]
+    fn fails_on_expectations_it_cannot_read() {
+        for (kind, expected) in [
+            (AssertionKind::StatusEquals, "abc"),
+            (AssertionKind::StatusInRange, "700xx"),
+            (AssertionKind::StatusInRange, "0xx"),
+            (AssertionKind::HeaderMatches, "("),
+            (AssertionKind::TimeBelow, "soon"),
+        ] {
+            assert!(!passes(kind, "x-request-id", expected), "{:?}", kind);
+        }
+    }
+}
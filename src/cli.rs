@@ -0,0 +1,631 @@
+use clap::{CommandFactory, Parser, Subcommand};
+use serde_json::{Value, json};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::atomic::AtomicBool,
+};
+
+use crate::{
+    api::{self, ApiError, ApiResponse},
+    database::{self, Database, HistoryItem, RetentionPolicy},
+    formats::{data::DataSet, har, report},
+    models::{Method, Request, RequestOptions, Variable},
+    runner::{self, RunOptions, RunStep},
+    secrets, variables,
+};
+
+// Every request succeeded
+const EXIT_OK: i32 = 0;
+// A request failed to send, failed an assertion or got a 4xx/5xx status
+const EXIT_FAILED: i32 = 1;
+// Bad input: unknown collection, unreadable file, database errors
+const EXIT_ERROR: i32 = 2;
+
+// Unlocks secret variables, since there is no window to ask in
+const PASSPHRASE_ENV: &str = "DISPATCH_PASSPHRASE";
+
+/// Runs requests without the GUI. With no subcommand the window opens.
+#[derive(Parser)]
+#[command(name = "dispatch", version)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+/// Whether `args` (program name first) are meant for the command line
+/// interface: a subcommand, or its help or version. Anything else, such as
+/// GApplication options or files to open, is left to GTK.
+pub fn handles(args: impl IntoIterator<Item = String>) -> bool {
+    let Some(first) = args.into_iter().nth(1) else {
+        return false;
+    };
+    matches!(
+        first.as_str(),
+        "-h" | "--help" | "-V" | "--version" | "help"
+    ) || Cli::command().find_subcommand(&first).is_some()
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Send a single request
+    Send {
+        url: String,
+        /// GET, POST, PUT, PATCH or DELETE; POST when --data is given
+        #[arg(short = 'X', long = "request")]
+        method: Option<String>,
+        /// `Name: value`, repeatable
+        #[arg(short = 'H', long = "header")]
+        headers: Vec<String>,
+        /// Request body, or `@file` to read it from a file
+        #[arg(short = 'd', long = "data")]
+        data: Option<String>,
+        #[arg(short = 'k', long)]
+        insecure: bool,
+        /// Do not follow redirects
+        #[arg(long)]
+        no_follow: bool,
+        #[arg(long)]
+        compressed: bool,
+        /// Print the status line and headers before the body
+        #[arg(short = 'i', long)]
+        include: bool,
+        #[command(flatten)]
+        common: CommonArgs,
+    },
+    /// Send every request in a collection or .http file, in order
+    Run {
+        /// Collection name or id, or a path to a .http / .rest file
+        target: String,
+        /// Only run the requests in this folder of the collection
+        #[arg(long)]
+        folder: Option<String>,
+        /// Stop at the first failing request
+        #[arg(long)]
+        bail: bool,
+        /// Milliseconds to wait between requests
+        #[arg(long, default_value_t = 0)]
+        delay: u64,
+        /// Run the whole sequence this many times
+        #[arg(short = 'n', long, default_value_t = 1)]
+        iterations: u32,
+        /// CSV or JSON array whose rows fill `{{placeholders}}`, one pass per row
+        #[arg(long, value_name = "FILE")]
+        data: Option<PathBuf>,
+        /// Write a JUnit XML report to this file
+        #[arg(long, value_name = "FILE")]
+        junit: Option<PathBuf>,
+        /// Write a JSON report to this file
+        #[arg(long, value_name = "FILE")]
+        report: Option<PathBuf>,
+        /// Record the responses as the requests' snapshots instead of checking them
+        #[arg(long)]
+        update_snapshots: bool,
+        #[command(flatten)]
+        common: CommonArgs,
+    },
+    /// Inspect or export request history
+    History {
+        #[command(subcommand)]
+        command: HistoryCommand,
+    },
+}
+
+#[derive(clap::Args)]
+pub struct CommonArgs {
+    /// Environment whose variables fill `{{placeholders}}`
+    #[arg(short, long = "env")]
+    environment: Option<String>,
+    /// Do not record the exchanges in history
+    #[arg(long)]
+    no_history: bool,
+    /// Print machine-readable JSON
+    #[arg(long)]
+    json: bool,
+}
+
+#[derive(Subcommand)]
+pub enum HistoryCommand {
+    /// Most recent entries first
+    List {
+        #[arg(short = 'n', long, default_value_t = 50)]
+        limit: usize,
+        #[arg(long)]
+        json: bool,
+    },
+    /// A single entry with request and response
+    Show {
+        id: i64,
+        #[arg(long)]
+        json: bool,
+    },
+    /// Write all history as a HAR file
+    Export {
+        /// Defaults to standard output
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Apply the redaction policy to entries saved before it
+    Scrub,
+    /// Remove entries the retention policy no longer keeps and compact the
+    /// database; the options override the saved policy for this run
+    Prune {
+        /// Keep at most this many entries besides pinned ones
+        #[arg(long)]
+        max_entries: Option<usize>,
+        /// Remove entries older than this many days
+        #[arg(long)]
+        max_age_days: Option<u32>,
+        /// Remove the oldest entries while bodies take up more bytes
+        #[arg(long)]
+        max_body_bytes: Option<u64>,
+    },
+}
+
+/// Runs a subcommand and returns the process exit code.
+pub fn run(command: Command) -> i32 {
+    let db = match Database::new() {
+        Ok(db) => db,
+        Err(e) => return fail(&format!("could not open the database: {}", e)),
+    };
+    if let Ok(passphrase) = std::env::var(PASSPHRASE_ENV)
+        && let Err(e) = db.unlock_secrets(&passphrase)
+    {
+        return fail(&format!("could not unlock secrets: {}", e));
+    }
+
+    let result = match command {
+        Command::Send {
+            url,
+            method,
+            headers,
+            data,
+            insecure,
+            no_follow,
+            compressed,
+            include,
+            common,
+        } => build_request(url, method, headers, data, insecure, no_follow, compressed)
+            .and_then(|request| send(&db, request, include, &common)),
+        Command::Run {
+            target,
+            folder,
+            bail,
+            delay,
+            iterations,
+            data,
+            junit,
+            report,
+            update_snapshots,
+            common,
+        } => {
+            let options = RunOptions {
+                stop_on_failure: bail,
+                delay_ms: delay,
+                iterations,
+                update_snapshots,
+            };
+            let files = ReportFiles { junit, report };
+            run_target(
+                &db,
+                &target,
+                folder.as_deref(),
+                data.as_deref(),
+                options,
+                files,
+                &common,
+            )
+        }
+        Command::History { command } => history(&db, command),
+    };
+
+    result.unwrap_or_else(|e| fail(&e))
+}
+
+fn fail(message: &str) -> i32 {
+    eprintln!("dispatch: {}", message);
+    EXIT_ERROR
+}
+
+fn build_request(
+    url: String,
+    method: Option<String>,
+    headers: Vec<String>,
+    data: Option<String>,
+    insecure: bool,
+    no_follow: bool,
+    compressed: bool,
+) -> Result<Request, String> {
+    let method = match method {
+        Some(method) => method
+            .to_uppercase()
+            .parse::<Method>()
+            .map_err(|_| format!("unsupported method `{}`", method))?,
+        None if data.is_some() => Method::POST,
+        None => Method::GET,
+    };
+
+    let headers = headers
+        .iter()
+        .map(|header| {
+            header
+                .split_once(':')
+                .map(|(k, v)| (k.trim().to_string(), v.trim().to_string()))
+                .ok_or_else(|| format!("malformed header `{}`", header))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let body = match data.as_deref().map(|d| d.strip_prefix('@')) {
+        Some(Some(file)) => fs::read_to_string(file).map_err(|e| format!("{}: {}", file, e))?,
+        _ => data.unwrap_or_default(),
+    };
+
+    Ok(Request {
+        method,
+        url,
+        headers,
+        body,
+        options: RequestOptions {
+            insecure,
+            follow_redirects: !no_follow,
+            compressed,
+        },
+        ..Default::default()
+    })
+}
+
+fn environment_variables(db: &Database, name: Option<&str>) -> Result<Vec<Variable>, String> {
+    let Some(name) = name else {
+        return Ok(Vec::new());
+    };
+    db.get_environments()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .find(|e| e.name == name)
+        .map(|e| e.variables)
+        .inspect(|variables| warn_locked(variables))
+        .ok_or_else(|| format!("no environment named `{}`", name))
+}
+
+fn warn_locked(variables: &[Variable]) {
+    let locked: Vec<&str> = variables
+        .iter()
+        .filter(|v| secrets::is_locked(v))
+        .map(|v| v.key.as_str())
+        .collect();
+    if !locked.is_empty() {
+        eprintln!(
+            "warning: secret variables are locked, set {} to use {}",
+            PASSPHRASE_ENV,
+            locked.join(", ")
+        );
+    }
+}
+
+fn send(
+    db: &Database,
+    request: Request,
+    include: bool,
+    common: &CommonArgs,
+) -> Result<i32, String> {
+    let environment = environment_variables(db, common.environment.as_deref())?;
+    let scope = variables::scope(&[&environment]);
+    let request = variables::resolve_request(&request, &scope);
+    for option in api::ignored_options(&request.url, request.options) {
+        eprintln!("warning: {} does not apply to unix: sockets", option);
+    }
+
+    let result = runner::send(&request);
+    if let Ok(response) = &result
+        && !common.no_history
+    {
+        runner::record(db, &request, response, &[]).map_err(|e| e.to_string())?;
+    }
+
+    if common.json {
+        // The resolved request holds the values of secret variables
+        let secret_values = db.secret_values().map_err(|e| e.to_string())?;
+        let request = secrets::redact_request(&request, &secret_values);
+        print_json(&exchange_json(&request, &result));
+    } else {
+        match &result {
+            Ok(response) => {
+                if include {
+                    println!("{}", response.status);
+                    print!("{}", response.headers);
+                    println!();
+                }
+                println!("{}", response.body);
+            }
+            Err(ApiError::RequestFailed(msg)) => eprintln!("dispatch: {}", msg),
+        }
+    }
+
+    Ok(if passed(&result) {
+        EXIT_OK
+    } else {
+        EXIT_FAILED
+    })
+}
+
+/// Where `run` writes its reports, besides standard output.
+struct ReportFiles {
+    junit: Option<PathBuf>,
+    report: Option<PathBuf>,
+}
+
+fn run_target(
+    db: &Database,
+    target: &str,
+    folder: Option<&str>,
+    data: Option<&Path>,
+    options: RunOptions,
+    files: ReportFiles,
+    common: &CommonArgs,
+) -> Result<i32, String> {
+    let target = runner::load_target(db, target, folder)?;
+    let data = data
+        .map(|path| DataSet::load(path).map_err(|e| format!("{}: {}", path.display(), e)))
+        .transpose()?;
+    for warning in &target.warnings {
+        eprintln!("warning: {}", warning);
+    }
+    warn_locked(&target.variables);
+    let environment = environment_variables(db, common.environment.as_deref())?;
+
+    let mut history_error = None;
+    // With data, every pass gets a header line instead
+    let show_iteration = options.iterations > 1 && data.is_none();
+    let mut current_iteration = 0;
+    let run = runner::run(
+        &target,
+        &environment,
+        data.as_ref(),
+        options,
+        &AtomicBool::new(false),
+        |step| {
+            if let Ok(response) = &step.response
+                && !common.no_history
+                && let Err(e) = runner::record(db, &step.request, response, &step.assertions)
+            {
+                history_error.get_or_insert(e.to_string());
+            }
+            if common.json {
+                return;
+            }
+            // Each data row starts a new pass; name its values once
+            if let (Some(data), Some(row)) = (&data, step.row)
+                && step.iteration != current_iteration
+            {
+                println!("[{}] {}", step.iteration, data.describe(row));
+            }
+            current_iteration = step.iteration;
+            print_step(step, show_iteration);
+        },
+    );
+    if let Some(e) = history_error {
+        return Err(e);
+    }
+    let updated = if options.update_snapshots {
+        runner::update_snapshots(db, &target, &run)?
+    } else {
+        0
+    };
+
+    // Reports end up in CI artifacts, so they get the same masking as history
+    let redacted = runner::redact_report(db, &run).map_err(|e| e.to_string())?;
+    if let Some(path) = &files.junit {
+        fs::write(path, report::to_junit(&redacted))
+            .map_err(|e| format!("{}: {}", path.display(), e))?;
+    }
+    if let Some(path) = &files.report {
+        fs::write(path, report::to_json(&redacted))
+            .map_err(|e| format!("{}: {}", path.display(), e))?;
+    }
+
+    if common.json {
+        print_json(&report::to_value(&redacted));
+    } else {
+        println!(
+            "\n{}: {} passed, {} failed in {:.0} ms",
+            run.name,
+            run.passed(),
+            run.failed(),
+            run.duration_ms
+        );
+        let failed_rows = run.failed_rows();
+        if !failed_rows.is_empty() {
+            let rows: Vec<String> = failed_rows.iter().map(|r| (r + 1).to_string()).collect();
+            println!("Failing rows: {}", rows.join(", "));
+        }
+        if options.update_snapshots {
+            println!("Snapshots updated: {}", updated);
+        }
+    }
+
+    Ok(if run.failed() == 0 {
+        EXIT_OK
+    } else {
+        EXIT_FAILED
+    })
+}
+
+fn print_step(step: &RunStep, show_iteration: bool) {
+    let name = if show_iteration {
+        format!("[{}] {}", step.iteration, step.name)
+    } else {
+        step.name.clone()
+    };
+    let mark = if step.passed() { "✓" } else { "✗" };
+    match &step.response {
+        Ok(response) => println!(
+            "{} {:>3} {:>9}  {}",
+            mark, response.status_code, response.time, name
+        ),
+        Err(ApiError::RequestFailed(msg)) => println!("{} ERR  {}: {}", mark, name, msg),
+    }
+    for check in step.assertions.iter().filter(|c| !c.passed) {
+        println!("      ✗ {}: {}", check.description, check.message);
+    }
+    for extraction in &step.extracted {
+        if let Err(reason) = &extraction.value {
+            println!("      ✗ capture {}: {}", extraction.variable, reason);
+        }
+    }
+    for line in &step.logs {
+        println!("      │ {}", line);
+    }
+}
+
+fn history(db: &Database, command: HistoryCommand) -> Result<i32, String> {
+    match command {
+        HistoryCommand::List { limit, json } => {
+            let items = db.get_recent_history(limit).map_err(|e| e.to_string())?;
+            if json {
+                print_json(&Value::Array(
+                    items.iter().map(history_summary_json).collect(),
+                ));
+            } else {
+                for item in items {
+                    println!(
+                        "{:>6}  {}  {:<6} {:<16} {}",
+                        item.id, item.timestamp, item.method, item.status, item.url
+                    );
+                }
+            }
+        }
+        HistoryCommand::Show { id, json } => {
+            let item = db
+                .get_request_by_id(id)
+                .map_err(|_| format!("no history entry {}", id))?;
+            if json {
+                let mut value = history_summary_json(&item);
+                value["request"] = json!({
+                    "headers": headers_json(&item.to_request().headers),
+                    "body": item.request_body,
+                });
+                value["response"] = json!({
+                    "headers": headers_json(&api::parse_headers(&item.response_headers)),
+                    "body": item.response_body,
+                });
+                print_json(&value);
+            } else {
+                println!("{} {}", item.method, item.url);
+                for (key, value) in item.to_request().headers {
+                    println!("{}: {}", key, value);
+                }
+                if !item.request_body.is_empty() {
+                    println!("\n{}", item.request_body);
+                }
+                println!("\n{}  {}  {}", item.status, item.time, item.size);
+                print!("{}", item.response_headers);
+                println!("\n{}", item.response_body);
+            }
+        }
+        HistoryCommand::Export { output } => {
+            let redact = runner::history_redaction(db).map_err(|e| e.to_string())?;
+            let items: Vec<HistoryItem> = db
+                .get_all_history()
+                .map_err(|e| e.to_string())?
+                .iter()
+                .map(redact)
+                .collect();
+            let document = har::export(&items);
+            match output {
+                Some(path) => {
+                    fs::write(&path, document).map_err(|e| format!("{}: {}", path.display(), e))?
+                }
+                None => println!("{}", document),
+            }
+        }
+        HistoryCommand::Scrub => {
+            let redact = runner::history_redaction(db).map_err(|e| e.to_string())?;
+            let scrubbed = db.scrub_history(redact).map_err(|e| e.to_string())?;
+            println!("Scrubbed {} entries", scrubbed);
+        }
+        HistoryCommand::Prune {
+            max_entries,
+            max_age_days,
+            max_body_bytes,
+        } => {
+            let saved = db.retention_policy().map_err(|e| e.to_string())?;
+            let policy = RetentionPolicy {
+                max_entries: max_entries.or(saved.max_entries),
+                max_age_days: max_age_days.or(saved.max_age_days),
+                max_body_bytes: max_body_bytes.or(saved.max_body_bytes),
+            };
+            let report = database::maintain(db.path(), &policy).map_err(|e| e.to_string())?;
+            let freed = db.compact().map_err(|e| e.to_string())?;
+            let usage = db.storage_usage().map_err(|e| e.to_string())?;
+            println!(
+                "Removed {} entries, freed {} bytes; {} entries remain in {} bytes",
+                report.removed, freed, usage.entries, usage.file_bytes
+            );
+        }
+    }
+    Ok(EXIT_OK)
+}
+
+/// A 4xx/5xx status fails, as does not getting a response at all.
+fn passed(result: &Result<ApiResponse, ApiError>) -> bool {
+    result
+        .as_ref()
+        .is_ok_and(|response| response.status_code < 400)
+}
+
+fn headers_json(headers: &[(String, String)]) -> Value {
+    Value::Array(
+        headers
+            .iter()
+            .map(|(name, value)| json!({ "name": name, "value": value }))
+            .collect(),
+    )
+}
+
+fn exchange_json(request: &Request, result: &Result<ApiResponse, ApiError>) -> Value {
+    let mut value = json!({
+        "request": {
+            "method": request.method.as_str(),
+            "url": request.url,
+            "headers": headers_json(&request.headers),
+        },
+        "ok": passed(result),
+    });
+    match result {
+        Ok(response) => {
+            value["response"] = json!({
+                "status": response.status_code,
+                "status_text": response.status,
+                "time_ms": api::parse_duration_ms(&response.time),
+                "size": response.size,
+                "headers": headers_json(&api::parse_headers(&response.headers)),
+                "body": response.body,
+            });
+        }
+        Err(ApiError::RequestFailed(msg)) => value["error"] = json!(msg),
+    }
+    value
+}
+
+fn history_summary_json(item: &HistoryItem) -> Value {
+    json!({
+        "id": item.id,
+        "timestamp": item.timestamp,
+        "method": item.method,
+        "url": item.url,
+        "status": item.status,
+        "time_ms": api::parse_duration_ms(&item.time),
+        "size": item.size,
+        "pinned": item.pinned,
+        "label": item.label,
+        "note": item.note,
+        "tags": item.tags,
+    })
+}
+
+fn print_json(value: &Value) {
+    println!(
+        "{}",
+        serde_json::to_string_pretty(value).unwrap_or_default()
+    );
+}
@@ -0,0 +1,388 @@
+use crate::api;
+use crate::models::{Method, Request};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Language {
+    Curl,
+    Httpie,
+    Python,
+    JavaScript,
+    Go,
+    Rust,
+}
+
+impl Language {
+    pub const ALL: [Language; 6] = [
+        Language::Curl,
+        Language::Httpie,
+        Language::Python,
+        Language::JavaScript,
+        Language::Go,
+        Language::Rust,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Language::Curl => "curl",
+            Language::Httpie => "HTTPie",
+            Language::Python => "Python (requests)",
+            Language::JavaScript => "JavaScript (fetch)",
+            Language::Go => "Go (net/http)",
+            Language::Rust => "Rust (reqwest)",
+        }
+    }
+
+    /// Sourceview language id used to highlight the snippet
+    pub fn syntax_id(&self) -> &'static str {
+        match self {
+            Language::Curl | Language::Httpie => "sh",
+            Language::Python => "python3",
+            Language::JavaScript => "js",
+            Language::Go => "go",
+            Language::Rust => "rust",
+        }
+    }
+}
+
+pub fn generate(language: Language, request: &Request) -> String {
+    match language {
+        Language::Curl => curl(request),
+        Language::Httpie => httpie(request),
+        Language::Python => python(request),
+        Language::JavaScript => javascript(request),
+        Language::Go => go(request),
+        Language::Rust => rust(request),
+    }
+}
+
+// Same rule as `api::perform_request`: only POST, PUT and PATCH send a body
+fn body(request: &Request) -> Option<&str> {
+    match request.method {
+        Method::POST | Method::PUT | Method::PATCH if !request.body.is_empty() => {
+            Some(&request.body)
+        }
+        _ => None,
+    }
+}
+
+/// Url to put in the snippet, plus a note when the request goes over a Unix socket,
+/// which only curl can express directly.
+fn target(request: &Request) -> (String, Option<String>) {
+    match api::parse_unix_url(&request.url) {
+        Some((socket, path)) => (
+            format!("http://localhost{}", path),
+            Some(format!(
+                "Sent over the Unix socket {}; point the client's transport at it.",
+                socket.display()
+            )),
+        ),
+        None => (request.url.clone(), None),
+    }
+}
+
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+// JSON string literals are also valid Python, JavaScript and Go string literals
+fn string_literal(value: &str) -> String {
+    serde_json::to_string(value).unwrap_or_default()
+}
+
+fn curl(request: &Request) -> String {
+    let mut parts = vec!["curl".to_string()];
+
+    if request.method != Method::GET {
+        parts.push(format!("-X {}", request.method.as_str()));
+    }
+
+    match api::parse_unix_url(&request.url) {
+        Some((socket, path)) => {
+            parts.push(format!(
+                "--unix-socket {}",
+                shell_quote(&socket.to_string_lossy())
+            ));
+            parts.push(shell_quote(&format!("http://localhost{}", path)));
+        }
+        None => parts.push(shell_quote(&request.url)),
+    }
+
+    for (key, value) in &request.headers {
+        parts.push(format!(
+            "-H {}",
+            shell_quote(&format!("{}: {}", key, value))
+        ));
+    }
+
+    if let Some(body) = body(request) {
+        parts.push(format!("--data-raw {}", shell_quote(body)));
+    }
+
+    if request.options.insecure {
+        parts.push("-k".to_string());
+    }
+    if request.options.follow_redirects {
+        parts.push("-L".to_string());
+    }
+    if request.options.compressed {
+        parts.push("--compressed".to_string());
+    }
+
+    parts.join(" \\\n  ")
+}
+
+fn httpie(request: &Request) -> String {
+    let (url, note) = target(request);
+    let mut parts = vec!["http".to_string()];
+
+    if request.options.insecure {
+        parts.push("--verify=no".to_string());
+    }
+    if request.options.follow_redirects {
+        parts.push("--follow".to_string());
+    }
+    if let Some(body) = body(request) {
+        parts.push(format!("--raw {}", shell_quote(body)));
+    }
+
+    parts.push(request.method.as_str().to_string());
+    parts.push(shell_quote(&url));
+
+    for (key, value) in &request.headers {
+        parts.push(shell_quote(&format!("{}:{}", key, value)));
+    }
+
+    let command = parts.join(" \\\n  ");
+    match note {
+        Some(note) => format!("# {}\n{}", note, command),
+        None => command,
+    }
+}
+
+fn python(request: &Request) -> String {
+    let (url, note) = target(request);
+    let mut code = String::new();
+
+    if let Some(note) = note {
+        code.push_str(&format!("# {}\n", note));
+    }
+    code.push_str("import requests\n\n");
+    code.push_str(&format!("url = {}\n", string_literal(&url)));
+
+    let mut args = vec!["url".to_string()];
+
+    if !request.headers.is_empty() {
+        code.push_str("headers = {\n");
+        for (key, value) in &request.headers {
+            code.push_str(&format!(
+                "    {}: {},\n",
+                string_literal(key),
+                string_literal(value)
+            ));
+        }
+        code.push_str("}\n");
+        args.push("headers=headers".to_string());
+    }
+
+    if let Some(body) = body(request) {
+        code.push_str(&format!("payload = {}\n", string_literal(body)));
+        args.push("data=payload".to_string());
+    }
+
+    if request.options.insecure {
+        args.push("verify=False".to_string());
+    }
+    if !request.options.follow_redirects {
+        args.push("allow_redirects=False".to_string());
+    }
+
+    code.push_str(&format!(
+        "\nresponse = requests.request({}, {})\n",
+        string_literal(request.method.as_str()),
+        args.join(", ")
+    ));
+    code.push_str("print(response.status_code)\n");
+    code.push_str("print(response.text)\n");
+    code
+}
+
+fn javascript(request: &Request) -> String {
+    let (url, note) = target(request);
+    let mut code = String::new();
+
+    if let Some(note) = note {
+        code.push_str(&format!("// {}\n", note));
+    }
+    if request.options.insecure {
+        code.push_str("// fetch cannot skip TLS verification; trust the certificate instead.\n");
+    }
+
+    code.push_str(&format!(
+        "const response = await fetch({}, {{\n",
+        string_literal(&url)
+    ));
+    code.push_str(&format!(
+        "  method: {},\n",
+        string_literal(request.method.as_str())
+    ));
+
+    if !request.headers.is_empty() {
+        code.push_str("  headers: {\n");
+        for (key, value) in &request.headers {
+            code.push_str(&format!(
+                "    {}: {},\n",
+                string_literal(key),
+                string_literal(value)
+            ));
+        }
+        code.push_str("  },\n");
+    }
+
+    if let Some(body) = body(request) {
+        code.push_str(&format!("  body: {},\n", string_literal(body)));
+    }
+
+    if !request.options.follow_redirects {
+        code.push_str("  redirect: \"manual\",\n");
+    }
+
+    code.push_str("});\n\n");
+    code.push_str("console.log(response.status);\n");
+    code.push_str("console.log(await response.text());\n");
+    code
+}
+
+fn go(request: &Request) -> String {
+    let (url, note) = target(request);
+    let body = body(request);
+
+    let mut imports = vec!["\"fmt\"", "\"io\"", "\"net/http\""];
+    if body.is_some() {
+        imports.push("\"strings\"");
+    }
+    if request.options.insecure {
+        imports.insert(0, "\"crypto/tls\"");
+    }
+
+    let mut code = String::new();
+    if let Some(note) = note {
+        code.push_str(&format!("// {}\n", note));
+    }
+    code.push_str("package main\n\nimport (\n");
+    for import in imports {
+        code.push_str(&format!("\t{}\n", import));
+    }
+    code.push_str(")\n\nfunc main() {\n");
+
+    let body_arg = match body {
+        Some(body) => {
+            let literal = if body.contains('`') {
+                string_literal(body)
+            } else {
+                format!("`{}`", body)
+            };
+            code.push_str(&format!("\tbody := strings.NewReader({})\n", literal));
+            "body"
+        }
+        None => "nil",
+    };
+
+    code.push_str(&format!(
+        "\treq, err := http.NewRequest({}, {}, {})\n",
+        string_literal(request.method.as_str()),
+        string_literal(&url),
+        body_arg
+    ));
+    code.push_str("\tif err != nil {\n\t\tpanic(err)\n\t}\n");
+
+    for (key, value) in &request.headers {
+        code.push_str(&format!(
+            "\treq.Header.Add({}, {})\n",
+            string_literal(key),
+            string_literal(value)
+        ));
+    }
+
+    code.push_str("\n\tclient := &http.Client{");
+    if request.options.insecure || !request.options.follow_redirects {
+        code.push('\n');
+        if request.options.insecure {
+            code.push_str("\t\tTransport: &http.Transport{\n");
+            code.push_str("\t\t\tTLSClientConfig: &tls.Config{InsecureSkipVerify: true},\n");
+            code.push_str("\t\t},\n");
+        }
+        if !request.options.follow_redirects {
+            code.push_str(
+                "\t\tCheckRedirect: func(req *http.Request, via []*http.Request) error {\n",
+            );
+            code.push_str("\t\t\treturn http.ErrUseLastResponse\n");
+            code.push_str("\t\t},\n");
+        }
+        code.push('\t');
+    }
+    code.push_str("}\n");
+
+    code.push_str("\tresp, err := client.Do(req)\n");
+    code.push_str("\tif err != nil {\n\t\tpanic(err)\n\t}\n");
+    code.push_str("\tdefer resp.Body.Close()\n\n");
+    code.push_str("\tdata, err := io.ReadAll(resp.Body)\n");
+    code.push_str("\tif err != nil {\n\t\tpanic(err)\n\t}\n");
+    code.push_str("\tfmt.Println(resp.Status)\n");
+    code.push_str("\tfmt.Println(string(data))\n");
+    code.push_str("}\n");
+    code
+}
+
+fn rust(request: &Request) -> String {
+    let (url, note) = target(request);
+    let mut code = String::new();
+
+    if let Some(note) = note {
+        code.push_str(&format!("// {}\n", note));
+    }
+    code.push_str("use reqwest::blocking::Client;\n\n");
+    code.push_str("fn main() -> Result<(), Box<dyn std::error::Error>> {\n");
+    code.push_str("    let client = Client::builder()\n");
+    if request.options.insecure {
+        code.push_str("        .danger_accept_invalid_certs(true)\n");
+    }
+    if !request.options.follow_redirects {
+        code.push_str("        .redirect(reqwest::redirect::Policy::none())\n");
+    }
+    if request.options.compressed {
+        code.push_str("        .gzip(true)\n");
+    }
+    code.push_str("        .build()?;\n\n");
+
+    code.push_str("    let response = client\n");
+    code.push_str(&format!(
+        "        .request(reqwest::Method::{}, {})\n",
+        request.method.as_str(),
+        string_literal(&url)
+    ));
+    for (key, value) in &request.headers {
+        code.push_str(&format!(
+            "        .header({}, {})\n",
+            string_literal(key),
+            string_literal(value)
+        ));
+    }
+    if let Some(body) = body(request) {
+        code.push_str(&format!("        .body({})\n", raw_string(body)));
+    }
+    code.push_str("        .send()?;\n\n");
+    code.push_str("    println!(\"{}\", response.status());\n");
+    code.push_str("    println!(\"{}\", response.text()?);\n");
+    code.push_str("    Ok(())\n");
+    code.push_str("}\n");
+    code
+}
+
+fn raw_string(value: &str) -> String {
+    // Enough hashes that no `"###` sequence inside the value closes the literal
+    let mut hashes = String::from("#");
+    while value.contains(&format!("\"{}", hashes)) {
+        hashes.push('#');
+    }
+    format!("r{}\"{}\"{}", hashes, value, hashes)
+}
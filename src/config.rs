@@ -5,6 +5,39 @@ pub const SIDEBAR_WIDTH_FRACTION: f64 = 0.25;
 pub const MIN_SIDEBAR_WIDTH: f64 = 220.0;
 pub const BREAKPOINT_WIDTH: f64 = 650.0;
 pub const REQUEST_PANE_POSITION: i32 = 250;
+pub const DIALOG_DEFAULT_WIDTH: i32 = 640;
+pub const DIALOG_DEFAULT_HEIGHT: i32 = 480;
+pub const REPORT_MIN_HEIGHT: i32 = 120;
+pub const NOTE_MIN_HEIGHT: i32 = 120;
+
+// Response Diff
+pub const DIFF_VALUE_MAX_CHARS: usize = 120;
+pub const DIFF_ADDED_COLOR: &str = "rgba(46, 194, 126, 0.25)";
+pub const DIFF_REMOVED_COLOR: &str = "rgba(224, 27, 36, 0.25)";
+
+// Snapshots
+// Differences named in a failed check before "and N more"
+pub const SNAPSHOT_MAX_LISTED: usize = 5;
+
+// History List
+/// Rows fetched from the database at a time, and how many such pages stay cached
+pub const HISTORY_PAGE_SIZE: usize = 100;
+pub const HISTORY_CACHED_PAGES: usize = 8;
+pub const HISTORY_SNIPPET_TOKENS: usize = 12;
+
+// History Retention
+pub const RETENTION_MAX_ENTRIES: f64 = 1_000_000.0;
+pub const RETENTION_MAX_DAYS: f64 = 3_650.0;
+pub const RETENTION_MAX_MEGABYTES: f64 = 100_000.0;
+
+// Collection Runner
+pub const RUN_MAX_DELAY_MS: f64 = 60_000.0;
+pub const RUN_MAX_ITERATIONS: f64 = 1_000.0;
+
+// Scripting
+pub const SCRIPT_MAX_OPERATIONS: u64 = 1_000_000;
+pub const SCRIPT_MAX_CALL_LEVELS: usize = 64;
+pub const SCRIPT_MAX_STRING_SIZE: usize = 10_000_000;
 
 // Editor Styles
 pub const EDITOR_SCHEME_PREF_1: &str = "Adwaita-Dark";
@@ -20,6 +53,7 @@ pub const CLASS_BADGE_PATCH: &str = "badge-patch";
 pub const CLASS_BADGE_DEFAULT: &str = "badge-default";
 pub const CLASS_SUCCESS: &str = "success";
 pub const CLASS_ERROR: &str = "error";
+pub const CLASS_WARNING: &str = "warning";
 
 // Sizing
 pub const SPACING_NONE: i32 = 0;
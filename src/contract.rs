@@ -0,0 +1,200 @@
+use crate::api::{self, ApiResponse};
+use crate::models::ContractLink;
+use crate::schema::{self, Violation};
+use serde_json::Value;
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::SystemTime,
+};
+
+// Parsed documents by path, with the modification time they were read at
+static LOADED: Mutex<BTreeMap<PathBuf, (SystemTime, Arc<Value>)>> = Mutex::new(BTreeMap::new());
+
+/// Checks a response against the contract a request is linked to. Violation
+/// pointers address the response as `/status`, `/headers/<name>` and `/body/...`.
+pub fn validate(link: &ContractLink, response: &ApiResponse) -> Result<Vec<Violation>, String> {
+    match link {
+        ContractLink::OpenApi { spec, operation } => {
+            let root = load(spec)?;
+            validate_operation(&root, operation, response)
+        }
+        ContractLink::JsonSchema { path } => {
+            let schema = load(path)?;
+            Ok(validate_body(&schema, &schema, &response.body))
+        }
+    }
+}
+
+/// Keys of every operation in a spec, in the form `GET /pets/{id}`.
+pub fn operations(spec: &Path) -> Result<Vec<String>, String> {
+    let root = load(spec)?;
+    let mut keys = Vec::new();
+
+    for (path, item) in root
+        .get("paths")
+        .and_then(Value::as_object)
+        .into_iter()
+        .flatten()
+    {
+        let item = resolve(&root, item);
+        for method in ["get", "put", "post", "delete", "patch"] {
+            if item.get(method).is_some() {
+                keys.push(format!("{} {}", method.to_uppercase(), path));
+            }
+        }
+    }
+
+    if keys.is_empty() {
+        return Err(format!("{} defines no operations", spec.display()));
+    }
+    Ok(keys)
+}
+
+/// Reads a JSON or YAML document. Every response is checked against it, so
+/// it is only parsed again once the file changes.
+pub fn load(path: &Path) -> Result<Arc<Value>, String> {
+    let failed = |e: &dyn std::fmt::Display| format!("{}: {}", path.display(), e);
+    let modified = fs::metadata(path)
+        .and_then(|m| m.modified())
+        .map_err(|e| failed(&e))?;
+
+    if let Some((loaded_at, value)) = LOADED.lock().unwrap().get(path)
+        && *loaded_at == modified
+    {
+        return Ok(value.clone());
+    }
+
+    let text = fs::read_to_string(path).map_err(|e| failed(&e))?;
+    // JSON is valid YAML, so one parser covers both
+    let value: Arc<Value> = Arc::new(serde_yaml::from_str(&text).map_err(|e| failed(&e))?);
+    LOADED
+        .lock()
+        .unwrap()
+        .insert(path.to_path_buf(), (modified, value.clone()));
+    Ok(value)
+}
+
+fn resolve<'a>(root: &'a Value, value: &'a Value) -> &'a Value {
+    let mut value = value;
+    // A few hops at most; cycles would otherwise never end
+    for _ in 0..16 {
+        match value
+            .get("$ref")
+            .and_then(Value::as_str)
+            .and_then(|r| r.strip_prefix('#'))
+            .and_then(|pointer| root.pointer(pointer))
+        {
+            Some(target) => value = target,
+            None => break,
+        }
+    }
+    value
+}
+
+fn validate_operation(
+    root: &Value,
+    key: &str,
+    response: &ApiResponse,
+) -> Result<Vec<Violation>, String> {
+    let (method, path) = key
+        .split_once(' ')
+        .ok_or_else(|| format!("invalid operation `{}`", key))?;
+    let operation = root
+        .get("paths")
+        .and_then(|paths| paths.get(path))
+        .map(|item| resolve(root, item))
+        .and_then(|item| item.get(method.to_lowercase()))
+        .ok_or_else(|| format!("operation {} is no longer in the spec", key))?;
+
+    let responses = operation.get("responses").and_then(Value::as_object);
+    let status = response.status_code.to_string();
+    let range = format!("{}XX", status.chars().next().unwrap_or('0'));
+
+    let documented = responses.and_then(|responses| {
+        responses
+            .get(&status)
+            .or_else(|| {
+                responses
+                    .iter()
+                    .find(|(code, _)| code.eq_ignore_ascii_case(&range))
+                    .map(|(_, r)| r)
+            })
+            .or_else(|| responses.get("default"))
+    });
+
+    let Some(documented) = documented.map(|r| resolve(root, r)) else {
+        return Ok(vec![Violation {
+            pointer: "/status".to_string(),
+            message: format!("status {} is not documented for {}", status, key),
+        }]);
+    };
+
+    let mut violations = Vec::new();
+    let headers = api::parse_headers(&response.headers);
+
+    for (name, header) in documented
+        .get("headers")
+        .and_then(Value::as_object)
+        .into_iter()
+        .flatten()
+    {
+        let header = resolve(root, header);
+        let required = header
+            .get("required")
+            .and_then(Value::as_bool)
+            .unwrap_or(false);
+        if required && !headers.iter().any(|(k, _)| k.eq_ignore_ascii_case(name)) {
+            violations.push(Violation {
+                pointer: format!("/headers/{}", schema::escape(name)),
+                message: "required header is missing".to_string(),
+            });
+        }
+    }
+
+    // OpenAPI 3 keys schemas by media type, Swagger 2 has a single one
+    let body_schema = match documented.get("content").and_then(Value::as_object) {
+        Some(content) => {
+            let content_type = headers
+                .iter()
+                .find(|(k, _)| k.eq_ignore_ascii_case("content-type"))
+                .map(|(_, v)| v.split(';').next().unwrap_or("").trim().to_lowercase())
+                .unwrap_or_default();
+            content
+                .get(&content_type)
+                .or_else(|| {
+                    content
+                        .iter()
+                        .find(|(media, _)| media.contains("json"))
+                        .map(|(_, m)| m)
+                })
+                .and_then(|media| media.get("schema"))
+        }
+        None => documented.get("schema"),
+    };
+
+    if let Some(body_schema) = body_schema {
+        violations.extend(validate_body(body_schema, root, &response.body));
+    }
+
+    Ok(violations)
+}
+
+fn validate_body(body_schema: &Value, root: &Value, body: &str) -> Vec<Violation> {
+    let Ok(instance) = serde_json::from_str::<Value>(body) else {
+        return vec![Violation {
+            pointer: "/body".to_string(),
+            message: "response body is not JSON".to_string(),
+        }];
+    };
+
+    schema::validate(body_schema, root, &instance)
+        .into_iter()
+        .map(|v| Violation {
+            pointer: format!("/body{}", v.pointer),
+            message: v.message,
+        })
+        .collect()
+}
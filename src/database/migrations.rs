@@ -0,0 +1,278 @@
+use super::DatabaseError;
+use rusqlite::Connection;
+use std::fs;
+use std::path::Path;
+
+/// Schema upgrades, oldest first. `PRAGMA user_version` holds how many have
+/// been applied, so a step must never change once released: add a new one.
+const MIGRATIONS: &[&str] = &[
+    // 1: the layout from before versioning. `IF NOT EXISTS` since databases
+    // created then already have these tables at version 0.
+    "CREATE TABLE IF NOT EXISTS history (
+        id INTEGER PRIMARY KEY,
+        method TEXT NOT NULL,
+        url TEXT NOT NULL,
+        request_body TEXT,
+        request_headers TEXT,
+        response_body TEXT,
+        response_headers TEXT,
+        status TEXT,
+        time TEXT,
+        size TEXT,
+        timestamp DATETIME DEFAULT CURRENT_TIMESTAMP
+    );
+    CREATE TABLE IF NOT EXISTS collections (
+        id INTEGER PRIMARY KEY,
+        name TEXT NOT NULL,
+        variables TEXT
+    );
+    CREATE TABLE IF NOT EXISTS collection_items (
+        id INTEGER PRIMARY KEY,
+        collection_id INTEGER NOT NULL,
+        parent_id INTEGER,
+        kind TEXT NOT NULL,
+        name TEXT NOT NULL,
+        position INTEGER NOT NULL DEFAULT 0,
+        request TEXT
+    );
+    CREATE TABLE IF NOT EXISTS environments (
+        id INTEGER PRIMARY KEY,
+        name TEXT NOT NULL,
+        variables TEXT
+    );
+    CREATE TABLE IF NOT EXISTS settings (
+        key TEXT PRIMARY KEY,
+        value TEXT
+    );
+    CREATE TABLE IF NOT EXISTS collection_sources (
+        collection_id INTEGER PRIMARY KEY,
+        source TEXT NOT NULL UNIQUE
+    );
+    CREATE TABLE IF NOT EXISTS item_sources (
+        item_id INTEGER PRIMARY KEY,
+        source_key TEXT NOT NULL,
+        generated TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS assertion_results (
+        history_id INTEGER PRIMARY KEY,
+        results TEXT NOT NULL
+    );",
+    // 2: full-text search over history, kept in step by triggers
+    "CREATE VIRTUAL TABLE history_fts USING fts5(
+        url, request_headers, request_body, response_headers, response_body,
+        content = 'history', content_rowid = 'id'
+    );
+    CREATE TRIGGER history_fts_insert AFTER INSERT ON history BEGIN
+        INSERT INTO history_fts (rowid, url, request_headers, request_body, response_headers, response_body)
+        VALUES (new.id, new.url, new.request_headers, new.request_body, new.response_headers, new.response_body);
+    END;
+    CREATE TRIGGER history_fts_delete AFTER DELETE ON history BEGIN
+        INSERT INTO history_fts (history_fts, rowid, url, request_headers, request_body, response_headers, response_body)
+        VALUES ('delete', old.id, old.url, old.request_headers, old.request_body, old.response_headers, old.response_body);
+    END;
+    CREATE TRIGGER history_fts_update AFTER UPDATE ON history BEGIN
+        INSERT INTO history_fts (history_fts, rowid, url, request_headers, request_body, response_headers, response_body)
+        VALUES ('delete', old.id, old.url, old.request_headers, old.request_body, old.response_headers, old.response_body);
+        INSERT INTO history_fts (rowid, url, request_headers, request_body, response_headers, response_body)
+        VALUES (new.id, new.url, new.request_headers, new.request_body, new.response_headers, new.response_body);
+    END;
+    INSERT INTO history_fts (history_fts) VALUES ('rebuild');",
+    // 3: pinned entries are kept however old
+    "ALTER TABLE history ADD COLUMN pinned INTEGER NOT NULL DEFAULT 0;",
+    // 4: a name, note and tags per entry, and deletes that can be undone
+    "ALTER TABLE history ADD COLUMN label TEXT;
+    ALTER TABLE history ADD COLUMN note TEXT;
+    ALTER TABLE history ADD COLUMN tags TEXT;
+    ALTER TABLE history ADD COLUMN trashed INTEGER;",
+    // 5: the expected response of a saved request
+    "CREATE TABLE snapshots (
+        item_id INTEGER PRIMARY KEY,
+        snapshot TEXT NOT NULL
+    );",
+];
+
+/// The schema version this build reads and writes.
+pub const SCHEMA_VERSION: i64 = MIGRATIONS.len() as i64;
+
+/// Brings the database at `path` up to `SCHEMA_VERSION`, one transaction per
+/// step. A database that already has tables is copied next to itself first.
+pub fn migrate(conn: &mut Connection, path: &Path) -> Result<(), DatabaseError> {
+    let version: i64 = conn.pragma_query_value(None, "user_version", |row| row.get(0))?;
+    if version > SCHEMA_VERSION {
+        return Err(DatabaseError::TooNew {
+            version,
+            supported: SCHEMA_VERSION,
+        });
+    }
+    if version == SCHEMA_VERSION {
+        return Ok(());
+    }
+
+    let tables: i64 = conn.query_row("SELECT COUNT(*) FROM sqlite_master", [], |row| row.get(0))?;
+    if tables > 0 {
+        backup(path, version)?;
+    }
+
+    for (index, step) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+        let tx = conn.transaction()?;
+        tx.execute_batch(step)?;
+        tx.pragma_update(None, "user_version", index as i64 + 1)?;
+        tx.commit()?;
+    }
+    Ok(())
+}
+
+/// Copies the file to `<name>.v<version>.bak`, replacing an older backup of
+/// the same version.
+fn backup(path: &Path, version: i64) -> Result<(), DatabaseError> {
+    let mut backup = path.as_os_str().to_owned();
+    backup.push(format!(".v{}.bak", version));
+    fs::copy(path, &backup).map_err(DatabaseError::Backup)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    /// A database file of its own per test, removed with its backups.
+    struct TestFile(PathBuf);
+
+    impl TestFile {
+        fn new(name: &str) -> TestFile {
+            let path = std::env::temp_dir().join(format!(
+                "dispatch-migrations-{}-{}.db",
+                std::process::id(),
+                name
+            ));
+            let file = TestFile(path);
+            file.remove();
+            file
+        }
+
+        fn backup(&self, version: i64) -> PathBuf {
+            let mut backup = self.0.as_os_str().to_owned();
+            backup.push(format!(".v{}.bak", version));
+            PathBuf::from(backup)
+        }
+
+        fn remove(&self) {
+            let _ = fs::remove_file(&self.0);
+            for version in 0..=SCHEMA_VERSION {
+                let _ = fs::remove_file(self.backup(version));
+            }
+        }
+    }
+
+    impl Drop for TestFile {
+        fn drop(&mut self) {
+            self.remove();
+        }
+    }
+
+    /// A database as a build at `version` would have left it, with one entry.
+    fn database_at(file: &TestFile, version: usize) -> Connection {
+        let conn = Connection::open(&file.0).unwrap();
+        for step in &MIGRATIONS[..version] {
+            conn.execute_batch(step).unwrap();
+        }
+        conn.pragma_update(None, "user_version", version as i64)
+            .unwrap();
+        if version > 0 {
+            conn.execute(
+                "INSERT INTO history (method, url) VALUES ('GET', 'https://a.test/')",
+                [],
+            )
+            .unwrap();
+        }
+        conn
+    }
+
+    fn user_version(conn: &Connection) -> i64 {
+        conn.pragma_query_value(None, "user_version", |row| row.get(0))
+            .unwrap()
+    }
+
+    #[test]
+    fn upgrades_from_every_version() {
+        for version in 0..=MIGRATIONS.len() {
+            let file = TestFile::new(&format!("v{}", version));
+            let mut conn = database_at(&file, version);
+
+            migrate(&mut conn, &file.0).unwrap();
+
+            assert_eq!(user_version(&conn), SCHEMA_VERSION, "from {}", version);
+            let urls: i64 = conn
+                .query_row(
+                    "SELECT COUNT(*) FROM history
+                     WHERE pinned = 0 AND trashed IS NULL AND url = 'https://a.test/'",
+                    [],
+                    |row| row.get(0),
+                )
+                .unwrap();
+            assert_eq!(urls, i64::from(version > 0), "from {}", version);
+            // The last step's table is there too
+            let snapshots: i64 = conn
+                .query_row("SELECT COUNT(*) FROM snapshots", [], |row| row.get(0))
+                .unwrap();
+            assert_eq!(snapshots, 0);
+        }
+    }
+
+    #[test]
+    fn indexes_history_saved_before_search_existed() {
+        let file = TestFile::new("fts");
+        let mut conn = database_at(&file, 1);
+
+        migrate(&mut conn, &file.0).unwrap();
+
+        let found: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM history_fts WHERE history_fts MATCH 'test'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(found, 1);
+    }
+
+    #[test]
+    fn adopts_tables_from_before_versioning() {
+        let file = TestFile::new("legacy");
+        let mut conn = database_at(&file, 0);
+        conn.execute_batch(MIGRATIONS[0]).unwrap();
+        conn.execute("INSERT INTO history (method, url) VALUES ('GET', 'x')", [])
+            .unwrap();
+
+        migrate(&mut conn, &file.0).unwrap();
+
+        assert_eq!(user_version(&conn), SCHEMA_VERSION);
+        assert!(file.backup(0).exists());
+    }
+
+    #[test]
+    fn backs_up_only_databases_with_tables() {
+        let empty = TestFile::new("empty");
+        migrate(&mut database_at(&empty, 0), &empty.0).unwrap();
+        assert!(!empty.backup(0).exists());
+
+        let older = TestFile::new("older");
+        migrate(&mut database_at(&older, 2), &older.0).unwrap();
+        assert!(older.backup(2).exists());
+    }
+
+    #[test]
+    fn refuses_databases_from_newer_versions() {
+        let file = TestFile::new("newer");
+        let mut conn = database_at(&file, MIGRATIONS.len());
+        conn.pragma_update(None, "user_version", SCHEMA_VERSION + 1)
+            .unwrap();
+
+        assert!(matches!(
+            migrate(&mut conn, &file.0),
+            Err(DatabaseError::TooNew { version, supported })
+                if version == SCHEMA_VERSION + 1 && supported == SCHEMA_VERSION
+        ));
+    }
+}
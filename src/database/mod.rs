@@ -0,0 +1,1333 @@
+use crate::config;
+use crate::models::{
+    AssertionResult, Collection, CollectionItem, CollectionNode, CollectionTree, Environment,
+    ItemKind, Request, SourcedRequest, SyncSummary, Variable,
+};
+use crate::redaction::RedactionPolicy;
+use crate::secrets::{self, SecretsError, Vault};
+use crate::snapshot::Snapshot;
+use directories::ProjectDirs;
+use rusqlite::{
+    Connection, OptionalExtension, Result, Row, params, params_from_iter, types::Value,
+};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+mod migrations;
+mod retention;
+
+pub use retention::{MaintenanceReport, RetentionPolicy, StorageUsage, maintain};
+
+const SECRETS_SALT_SETTING: &str = "secrets_salt";
+const SECRETS_CHECK_SETTING: &str = "secrets_check";
+const REDACTION_POLICY_SETTING: &str = "redaction_policy";
+const RETENTION_POLICY_SETTING: &str = "retention_policy";
+
+#[derive(Debug, Clone, Default)]
+pub struct HistoryItem {
+    pub id: i64,
+    pub method: String,
+    pub url: String,
+    pub request_body: String,
+    pub request_headers: String,
+    pub response_body: String,
+    pub response_headers: String,
+    pub status: String,
+    pub time: String,
+    pub size: String,
+    pub timestamp: String,
+    /// Listed above the rest and never removed by retention
+    pub pinned: bool,
+    /// Shown instead of the URL when set
+    pub label: String,
+    pub note: String,
+    pub tags: Vec<String>,
+}
+
+impl HistoryItem {
+    pub fn to_request(&self) -> Request {
+        Request {
+            method: self.method.parse().unwrap_or_default(),
+            url: self.url.clone(),
+            headers: serde_json::from_str(&self.request_headers).unwrap_or_default(),
+            body: self.request_body.clone(),
+            ..Default::default()
+        }
+    }
+}
+
+/// What to look for in history; empty fields match every entry.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HistoryFilter {
+    /// Words to find in the URL, headers or bodies
+    pub text: String,
+    pub method: Option<String>,
+    /// First digit of the status code, e.g. 4 for 4xx
+    pub status_class: Option<u8>,
+    pub host: String,
+    /// Inclusive `YYYY-MM-DD` bounds, in UTC like the timestamps
+    pub since: String,
+    pub until: String,
+    pub pinned_only: bool,
+    /// Only 4xx and 5xx responses
+    pub errors_only: bool,
+}
+
+impl HistoryFilter {
+    pub fn is_empty(&self) -> bool {
+        *self == HistoryFilter::default()
+    }
+}
+
+/// How the history list is split into sections. Pinned entries always come
+/// first, in a section of their own.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum HistoryGrouping {
+    /// Today, Yesterday, This Week and Older, in local time
+    #[default]
+    Date,
+    Host,
+}
+
+impl HistoryGrouping {
+    /// Key entries are sorted and grouped by; NULL for pinned entries.
+    fn key(self) -> String {
+        let key = match self {
+            HistoryGrouping::Date => {
+                let day = "date(history.timestamp, 'localtime')";
+                format!(
+                    "CASE WHEN {day} = date('now', 'localtime') THEN 1
+                        WHEN {day} = date('now', 'localtime', '-1 day') THEN 2
+                        WHEN {day} >= date('now', 'localtime', '-6 days', 'weekday 1') THEN 3
+                        ELSE 4 END"
+                )
+            }
+            HistoryGrouping::Host => url_host(),
+        };
+        format!("(CASE WHEN history.pinned = 1 THEN NULL ELSE {} END)", key)
+    }
+
+    fn title(self, key: Value) -> String {
+        match (self, key) {
+            (_, Value::Null) => "Pinned".to_string(),
+            (HistoryGrouping::Date, Value::Integer(1)) => "Today".to_string(),
+            (HistoryGrouping::Date, Value::Integer(2)) => "Yesterday".to_string(),
+            (HistoryGrouping::Date, Value::Integer(3)) => "This Week".to_string(),
+            (HistoryGrouping::Date, _) => "Older".to_string(),
+            (HistoryGrouping::Host, Value::Text(host)) if !host.is_empty() => host,
+            (HistoryGrouping::Host, _) => "Other".to_string(),
+        }
+    }
+
+    fn order(self) -> String {
+        format!(
+            "ORDER BY history.pinned DESC, {}, history.timestamp DESC, history.id DESC",
+            self.key()
+        )
+    }
+}
+
+/// A run of consecutive entries under one header.
+#[derive(Debug, Clone)]
+pub struct HistorySection {
+    pub title: String,
+    pub count: usize,
+    pub pinned: bool,
+}
+
+/// Marks the start and end of a matched term in `HistoryMatch::snippet`.
+pub const HIGHLIGHT_START: char = '\u{E000}';
+pub const HIGHLIGHT_END: char = '\u{E001}';
+
+pub struct HistoryMatch {
+    pub item: HistoryItem,
+    /// Text around the best match when searching for words
+    pub snippet: Option<String>,
+}
+
+const HISTORY_COLUMNS: &str = "id, method, url, request_body, request_headers, response_body, response_headers, status, time, size, timestamp, pinned, label, note, tags";
+
+/// SQL for the host of an entry's URL, between the scheme and the path.
+fn url_host() -> String {
+    let rest = "substr(history.url, instr(history.url, '://') + 3)";
+    let ends = format!("replace(replace({rest} || '/', '?', '/'), '#', '/')");
+    format!("substr({rest}, 1, instr({ends}, '/') - 1)")
+}
+
+/// The `FROM` and `WHERE` clauses selecting what `filter` matches, and
+/// their parameters. The index is only joined when searching for words.
+fn history_selection(filter: &HistoryFilter) -> (String, Vec<String>) {
+    let mut sql = "FROM history".to_string();
+    // The same host the Host grouping files an entry under
+    let host_condition = format!("{} = ? COLLATE NOCASE", url_host());
+    let mut conditions = vec!["history.trashed IS NULL"];
+    let mut values = Vec::new();
+
+    let query = fts_query(&filter.text);
+    if !query.is_empty() {
+        sql.push_str(" JOIN history_fts ON history_fts.rowid = history.id");
+        conditions.push("history_fts MATCH ?");
+        values.push(query);
+    }
+    if let Some(method) = &filter.method {
+        conditions.push("history.method = ?");
+        values.push(method.clone());
+    }
+    if let Some(class) = filter.status_class {
+        conditions.push("history.status LIKE ?");
+        values.push(format!("{}%", class));
+    }
+    if !filter.host.is_empty() {
+        conditions.push(&host_condition);
+        values.push(filter.host.trim().to_string());
+    }
+    if !filter.since.is_empty() {
+        conditions.push("date(history.timestamp) >= ?");
+        values.push(filter.since.trim().to_string());
+    }
+    if !filter.until.is_empty() {
+        conditions.push("date(history.timestamp) <= ?");
+        values.push(filter.until.trim().to_string());
+    }
+    if filter.pinned_only {
+        conditions.push("history.pinned = 1");
+    }
+    if filter.errors_only {
+        conditions.push("(history.status LIKE '4%' OR history.status LIKE '5%')");
+    }
+
+    sql.push_str(&format!(" WHERE {}", conditions.join(" AND ")));
+    (sql, values)
+}
+
+fn history_item_from_row(row: &Row) -> Result<HistoryItem> {
+    Ok(HistoryItem {
+        id: row.get(0)?,
+        method: row.get(1)?,
+        url: row.get(2)?,
+        request_body: row.get(3).unwrap_or_default(),
+        request_headers: row.get(4).unwrap_or_default(),
+        response_body: row.get(5).unwrap_or_default(),
+        response_headers: row.get(6).unwrap_or_default(),
+        status: row.get(7).unwrap_or_default(),
+        time: row.get(8).unwrap_or_default(),
+        size: row.get(9).unwrap_or_default(),
+        timestamp: row.get(10).unwrap_or_default(),
+        pinned: row.get(11)?,
+        label: row.get(12).unwrap_or_default(),
+        note: row.get(13).unwrap_or_default(),
+        tags: row
+            .get::<_, Option<String>>(14)?
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default(),
+    })
+}
+
+#[derive(Debug)]
+pub enum DatabaseError {
+    Sqlite(rusqlite::Error),
+    /// The copy made before migrating could not be written
+    Backup(std::io::Error),
+    /// Written by a newer version of Dispatch, whose changes this one
+    /// would not understand
+    TooNew {
+        version: i64,
+        supported: i64,
+    },
+}
+
+impl std::fmt::Display for DatabaseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DatabaseError::Sqlite(e) => write!(f, "{}", e),
+            DatabaseError::Backup(e) => write!(f, "could not back up before upgrading: {}", e),
+            DatabaseError::TooNew { version, supported } => write!(
+                f,
+                "the database is at version {}, but this version of Dispatch supports up to {}; please update Dispatch",
+                version, supported
+            ),
+        }
+    }
+}
+
+impl std::error::Error for DatabaseError {}
+
+impl From<rusqlite::Error> for DatabaseError {
+    fn from(e: rusqlite::Error) -> Self {
+        DatabaseError::Sqlite(e)
+    }
+}
+
+pub struct Database {
+    conn: Connection,
+    path: PathBuf,
+    /// Decrypts secret variables once unlocked, for the rest of the session
+    vault: RefCell<Option<Vault>>,
+    /// Unredacted history entries from this session, by id
+    originals: RefCell<HashMap<i64, HistoryItem>>,
+}
+
+impl Database {
+    pub fn new() -> std::result::Result<Self, DatabaseError> {
+        let db_path = if let Some(proj_dirs) = ProjectDirs::from("com", "example", "dispatch") {
+            let config_dir = proj_dirs.config_dir();
+            if !config_dir.exists() {
+                fs::create_dir_all(config_dir).unwrap_or_default();
+            }
+            config_dir.join("history.db")
+        } else {
+            PathBuf::from("history.db")
+        };
+
+        let mut conn = Connection::open(&db_path)?;
+        migrations::migrate(&mut conn, &db_path)?;
+
+        Ok(Database {
+            conn,
+            path: db_path,
+            vault: RefCell::new(None),
+            originals: RefCell::new(HashMap::new()),
+        })
+    }
+
+    pub fn save_exchange(
+        &self,
+        method: &str,
+        url: &str,
+        req_body: &str,
+        req_headers: &str,
+        res_body: &str,
+        res_headers: &str,
+        status: &str,
+        time: &str,
+        size: &str,
+    ) -> Result<i64> {
+        self.conn.execute(
+            "INSERT INTO history (
+                method, url, request_body, request_headers, 
+                response_body, response_headers, status, time, size
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                method,
+                url,
+                req_body,
+                req_headers,
+                res_body,
+                res_headers,
+                status,
+                time,
+                size
+            ],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    /// Inserts exchanges recorded elsewhere, keeping their original
+    /// timestamps. On an error none of them are kept.
+    pub fn import_exchanges(&self, items: &[HistoryItem]) -> Result<usize> {
+        let tx = self.conn.unchecked_transaction()?;
+        for item in items {
+            self.conn.execute(
+                "INSERT INTO history (
+                    method, url, request_body, request_headers,
+                    response_body, response_headers, status, time, size, timestamp
+                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+                params![
+                    item.method,
+                    item.url,
+                    item.request_body,
+                    item.request_headers,
+                    item.response_body,
+                    item.response_headers,
+                    item.status,
+                    item.time,
+                    item.size,
+                    item.timestamp
+                ],
+            )?;
+        }
+        tx.commit()?;
+        Ok(items.len())
+    }
+
+    pub fn get_all_history(&self) -> Result<Vec<HistoryItem>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM history WHERE trashed IS NULL ORDER BY id DESC",
+            HISTORY_COLUMNS
+        ))?;
+
+        let rows = stmt.query_map([], history_item_from_row)?;
+
+        let mut items = Vec::new();
+        for row in rows {
+            items.push(row?);
+        }
+        Ok(items)
+    }
+
+    /// The `limit` most recent entries, newest first.
+    pub fn get_recent_history(&self, limit: usize) -> Result<Vec<HistoryItem>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM history WHERE trashed IS NULL ORDER BY id DESC LIMIT ?1",
+            HISTORY_COLUMNS
+        ))?;
+
+        let rows = stmt.query_map([limit as i64], history_item_from_row)?;
+        rows.collect()
+    }
+
+    /// The sections `search_history` splits the entries `filter` matches
+    /// into, in order.
+    pub fn history_sections(
+        &self,
+        filter: &HistoryFilter,
+        grouping: HistoryGrouping,
+    ) -> Result<Vec<HistorySection>> {
+        let (selection, values) = history_selection(filter);
+        let key = grouping.key();
+        let sql = format!(
+            "SELECT {key} AS section, COUNT(*) {selection}
+             GROUP BY section ORDER BY MAX(history.pinned) DESC, section"
+        );
+        let mut stmt = self.conn.prepare(&sql)?;
+        let rows = stmt.query_map(params_from_iter(values.iter()), |row| {
+            let key: Value = row.get(0)?;
+            Ok(HistorySection {
+                pinned: key == Value::Null,
+                title: grouping.title(key),
+                count: row.get(1)?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    /// Up to `limit` entries matching `filter`, pinned ones first and the
+    /// rest by section, most recent first, skipping the first `offset`.
+    pub fn search_history(
+        &self,
+        filter: &HistoryFilter,
+        grouping: HistoryGrouping,
+        offset: usize,
+        limit: usize,
+    ) -> Result<Vec<HistoryMatch>> {
+        let columns: Vec<String> = HISTORY_COLUMNS
+            .split(", ")
+            .map(|c| format!("history.{}", c))
+            .collect();
+        let snippet = if fts_query(&filter.text).is_empty() {
+            "NULL".to_string()
+        } else {
+            format!(
+                "snippet(history_fts, -1, '{}', '{}', '…', {})",
+                HIGHLIGHT_START,
+                HIGHLIGHT_END,
+                config::HISTORY_SNIPPET_TOKENS
+            )
+        };
+        let (selection, values) = history_selection(filter);
+        let sql = format!(
+            "SELECT {}, {} {} {} LIMIT {} OFFSET {}",
+            columns.join(", "),
+            snippet,
+            selection,
+            grouping.order(),
+            limit,
+            offset
+        );
+
+        let mut stmt = self.conn.prepare(&sql)?;
+        let rows = stmt.query_map(params_from_iter(values.iter()), |row| {
+            Ok(HistoryMatch {
+                item: history_item_from_row(row)?,
+                snippet: row.get(15)?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    /// The entry as it was sent when this session kept it, otherwise as stored.
+    pub fn get_request_by_id(&self, id: i64) -> Result<HistoryItem> {
+        if let Some(item) = self.originals.borrow().get(&id) {
+            return Ok(item.clone());
+        }
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM history WHERE id = ?1",
+            HISTORY_COLUMNS
+        ))?;
+
+        let mut rows = stmt.query_map(params![id], history_item_from_row)?;
+
+        if let Some(row) = rows.next() {
+            row
+        } else {
+            Err(rusqlite::Error::QueryReturnedNoRows)
+        }
+    }
+
+    pub fn set_history_pinned(&self, id: i64, pinned: bool) -> Result<()> {
+        self.conn.execute(
+            "UPDATE history SET pinned = ?1 WHERE id = ?2",
+            params![pinned, id],
+        )?;
+        self.update_original(id, |item| item.pinned = pinned);
+        Ok(())
+    }
+
+    /// An empty label shows the URL again.
+    pub fn set_history_label(&self, id: i64, label: &str) -> Result<()> {
+        let label = Some(label.trim()).filter(|l| !l.is_empty());
+        self.conn.execute(
+            "UPDATE history SET label = ?1 WHERE id = ?2",
+            params![label, id],
+        )?;
+        self.update_original(id, |item| {
+            item.label = label.unwrap_or_default().to_string()
+        });
+        Ok(())
+    }
+
+    pub fn set_history_notes(&self, id: i64, note: &str, tags: &[String]) -> Result<()> {
+        let json = serde_json::to_string(tags).unwrap_or_default();
+        self.conn.execute(
+            "UPDATE history SET note = ?1, tags = ?2 WHERE id = ?3",
+            params![note, json, id],
+        )?;
+        self.update_original(id, |item| {
+            item.note = note.to_string();
+            item.tags = tags.to_vec();
+        });
+        Ok(())
+    }
+
+    /// Hides the entries, or all of them with `None`, until they are restored
+    /// or purged. Returns the batch to pass to either.
+    pub fn trash_history(&self, ids: Option<&[i64]>) -> Result<i64> {
+        let batch: i64 = self.conn.query_row(
+            "SELECT COALESCE(MAX(trashed), 0) + 1 FROM history",
+            [],
+            |row| row.get(0),
+        )?;
+        match ids {
+            Some(ids) => {
+                let tx = self.conn.unchecked_transaction()?;
+                for id in ids {
+                    self.conn.execute(
+                        "UPDATE history SET trashed = ?1 WHERE id = ?2 AND trashed IS NULL",
+                        params![batch, id],
+                    )?;
+                }
+                tx.commit()?;
+            }
+            None => {
+                self.conn.execute(
+                    "UPDATE history SET trashed = ?1 WHERE trashed IS NULL",
+                    params![batch],
+                )?;
+            }
+        }
+        Ok(batch)
+    }
+
+    pub fn restore_history(&self, batch: i64) -> Result<usize> {
+        self.conn.execute(
+            "UPDATE history SET trashed = NULL WHERE trashed = ?1",
+            params![batch],
+        )
+    }
+
+    /// Deletes a trashed batch for good, or every one with `None`.
+    pub fn purge_history(&self, batch: Option<i64>) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        self.conn.execute(
+            "DELETE FROM assertion_results WHERE history_id IN (
+                SELECT id FROM history WHERE trashed = ?1 OR (?1 IS NULL AND trashed IS NOT NULL)
+            )",
+            params![batch],
+        )?;
+        self.conn.execute(
+            "DELETE FROM history WHERE trashed = ?1 OR (?1 IS NULL AND trashed IS NOT NULL)",
+            params![batch],
+        )?;
+        tx.commit()
+    }
+
+    /// Keeps the unredacted entry in memory until the app closes.
+    pub fn keep_original(&self, item: HistoryItem) {
+        self.originals.borrow_mut().insert(item.id, item);
+    }
+
+    /// Applies an edit to the kept entry too, if there is one.
+    fn update_original(&self, id: i64, edit: impl FnOnce(&mut HistoryItem)) {
+        if let Some(item) = self.originals.borrow_mut().get_mut(&id) {
+            edit(item);
+        }
+    }
+
+    /// Rewrites every history entry through `redact` and returns how many changed.
+    pub fn scrub_history(&self, redact: impl Fn(&HistoryItem) -> HistoryItem) -> Result<usize> {
+        let tx = self.conn.unchecked_transaction()?;
+        let mut scrubbed = 0;
+        for item in self.get_all_history()? {
+            let redacted = redact(&item);
+            if redacted.url == item.url
+                && redacted.request_body == item.request_body
+                && redacted.request_headers == item.request_headers
+                && redacted.response_body == item.response_body
+                && redacted.response_headers == item.response_headers
+            {
+                continue;
+            }
+            self.conn.execute(
+                "UPDATE history SET url = ?1, request_body = ?2, request_headers = ?3,
+                 response_body = ?4, response_headers = ?5 WHERE id = ?6",
+                params![
+                    redacted.url,
+                    redacted.request_body,
+                    redacted.request_headers,
+                    redacted.response_body,
+                    redacted.response_headers,
+                    item.id
+                ],
+            )?;
+            scrubbed += 1;
+        }
+        tx.commit()?;
+        self.originals.borrow_mut().clear();
+        Ok(scrubbed)
+    }
+
+    pub fn save_assertion_results(
+        &self,
+        history_id: i64,
+        results: &[AssertionResult],
+    ) -> Result<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO assertion_results (history_id, results) VALUES (?1, ?2)",
+            params![
+                history_id,
+                serde_json::to_string(results).unwrap_or_default()
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Empty when the request had no assertions.
+    pub fn get_assertion_results(&self, history_id: i64) -> Result<Vec<AssertionResult>> {
+        let results: Option<String> = self
+            .conn
+            .query_row(
+                "SELECT results FROM assertion_results WHERE history_id = ?1",
+                params![history_id],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(results
+            .and_then(|r| serde_json::from_str(&r).ok())
+            .unwrap_or_default())
+    }
+
+    /// Stored results for each of `history_ids` that has any, in one query.
+    pub fn get_assertion_results_for(
+        &self,
+        history_ids: &[i64],
+    ) -> Result<HashMap<i64, Vec<AssertionResult>>> {
+        if history_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+        let placeholders = vec!["?"; history_ids.len()].join(", ");
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT history_id, results FROM assertion_results WHERE history_id IN ({})",
+            placeholders
+        ))?;
+        let rows = stmt.query_map(params_from_iter(history_ids), |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+        })?;
+
+        let mut results = HashMap::new();
+        for row in rows {
+            let (id, json) = row?;
+            if let Ok(parsed) = serde_json::from_str(&json) {
+                results.insert(id, parsed);
+            }
+        }
+        Ok(results)
+    }
+
+    // --- Collections ---
+
+    pub fn get_collections(&self) -> Result<Vec<Collection>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT id, name, variables FROM collections ORDER BY name COLLATE NOCASE")?;
+
+        let rows = stmt.query_map([], |row| {
+            let variables: String = row.get(2).unwrap_or_default();
+            Ok(Collection {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                variables: self.decode_variables(&variables),
+            })
+        })?;
+
+        let mut collections = Vec::new();
+        for row in rows {
+            collections.push(row?);
+        }
+        Ok(collections)
+    }
+
+    pub fn get_collection(&self, id: i64) -> Result<Collection> {
+        self.conn.query_row(
+            "SELECT id, name, variables FROM collections WHERE id = ?1",
+            params![id],
+            |row| {
+                let variables: String = row.get(2).unwrap_or_default();
+                Ok(Collection {
+                    id: row.get(0)?,
+                    name: row.get(1)?,
+                    variables: self.decode_variables(&variables),
+                })
+            },
+        )
+    }
+
+    pub fn create_collection(&self, name: &str, variables: &[Variable]) -> Result<i64> {
+        self.conn.execute(
+            "INSERT INTO collections (name, variables) VALUES (?1, ?2)",
+            params![name, self.encode_variables(variables)?],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    pub fn delete_collection(&self, id: i64) -> Result<()> {
+        self.conn.execute(
+            "DELETE FROM item_sources WHERE item_id IN
+             (SELECT id FROM collection_items WHERE collection_id = ?1)",
+            params![id],
+        )?;
+        self.conn.execute(
+            "DELETE FROM snapshots WHERE item_id IN
+             (SELECT id FROM collection_items WHERE collection_id = ?1)",
+            params![id],
+        )?;
+        self.conn.execute(
+            "DELETE FROM collection_sources WHERE collection_id = ?1",
+            params![id],
+        )?;
+        self.conn.execute(
+            "DELETE FROM collection_items WHERE collection_id = ?1",
+            params![id],
+        )?;
+        self.conn
+            .execute("DELETE FROM collections WHERE id = ?1", params![id])?;
+        Ok(())
+    }
+
+    /// Items of a collection in display order: depth first, by position.
+    pub fn get_collection_items(&self, collection_id: i64) -> Result<Vec<CollectionItem>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, collection_id, parent_id, kind, name, position, request
+             FROM collection_items WHERE collection_id = ?1 ORDER BY position, id",
+        )?;
+
+        let rows = stmt.query_map(params![collection_id], collection_item_from_row)?;
+
+        let mut items = Vec::new();
+        for row in rows {
+            items.push(row?);
+        }
+
+        let mut ordered = Vec::with_capacity(items.len());
+        append_children(&items, None, &mut ordered);
+        Ok(ordered)
+    }
+
+    pub fn get_collection_item(&self, id: i64) -> Result<CollectionItem> {
+        self.conn.query_row(
+            "SELECT id, collection_id, parent_id, kind, name, position, request
+             FROM collection_items WHERE id = ?1",
+            params![id],
+            collection_item_from_row,
+        )
+    }
+
+    pub fn insert_collection_item(
+        &self,
+        collection_id: i64,
+        parent_id: Option<i64>,
+        kind: ItemKind,
+        name: &str,
+        request: &Request,
+    ) -> Result<i64> {
+        let position: i64 = self.conn.query_row(
+            "SELECT COALESCE(MAX(position) + 1, 0) FROM collection_items
+             WHERE collection_id = ?1 AND parent_id IS ?2",
+            params![collection_id, parent_id],
+            |row| row.get(0),
+        )?;
+
+        self.conn.execute(
+            "INSERT INTO collection_items (collection_id, parent_id, kind, name, position, request)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                collection_id,
+                parent_id,
+                kind.as_str(),
+                name,
+                position,
+                serde_json::to_string(request).unwrap_or_default()
+            ],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    pub fn update_collection_variables(&self, id: i64, variables: &[Variable]) -> Result<()> {
+        self.conn.execute(
+            "UPDATE collections SET variables = ?1 WHERE id = ?2",
+            params![self.encode_variables(variables)?, id],
+        )?;
+        Ok(())
+    }
+
+    /// Deletes an item, and its children if it is a folder.
+    pub fn delete_collection_item(&self, id: i64) -> Result<()> {
+        let children: Vec<i64> = {
+            let mut stmt = self
+                .conn
+                .prepare("SELECT id FROM collection_items WHERE parent_id = ?1")?;
+            let rows = stmt.query_map(params![id], |row| row.get(0))?;
+            rows.collect::<Result<_>>()?
+        };
+        for child in children {
+            self.delete_collection_item(child)?;
+        }
+
+        self.conn
+            .execute("DELETE FROM item_sources WHERE item_id = ?1", params![id])?;
+        self.conn
+            .execute("DELETE FROM snapshots WHERE item_id = ?1", params![id])?;
+        self.conn
+            .execute("DELETE FROM collection_items WHERE id = ?1", params![id])?;
+        Ok(())
+    }
+
+    pub fn update_collection_item_request(&self, id: i64, request: &Request) -> Result<()> {
+        self.conn.execute(
+            "UPDATE collection_items SET request = ?1 WHERE id = ?2",
+            params![serde_json::to_string(request).unwrap_or_default(), id],
+        )?;
+        Ok(())
+    }
+
+    /// Stores an imported collection and all of its folders and requests at once.
+    pub fn import_collection(&self, tree: &CollectionTree) -> Result<i64> {
+        let tx = self.conn.unchecked_transaction()?;
+        let collection_id = self.create_collection(&tree.name, &tree.variables)?;
+        self.insert_nodes(collection_id, None, &tree.items)?;
+        tx.commit()?;
+        Ok(collection_id)
+    }
+
+    fn insert_nodes(
+        &self,
+        collection_id: i64,
+        parent_id: Option<i64>,
+        nodes: &[CollectionNode],
+    ) -> Result<()> {
+        for node in nodes {
+            match node {
+                CollectionNode::Folder { name, children } => {
+                    let folder_id = self.insert_collection_item(
+                        collection_id,
+                        parent_id,
+                        ItemKind::Folder,
+                        name,
+                        &Request::default(),
+                    )?;
+                    self.insert_nodes(collection_id, Some(folder_id), children)?;
+                }
+                CollectionNode::Request { name, request } => {
+                    self.insert_collection_item(
+                        collection_id,
+                        parent_id,
+                        ItemKind::Request,
+                        name,
+                        request,
+                    )?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Creates or updates the collection generated from `source`. Requests the
+    /// user has not touched follow the source; edited ones are left alone.
+    pub fn sync_collection(
+        &self,
+        source: &str,
+        name: &str,
+        variables: &[Variable],
+        requests: &[SourcedRequest],
+    ) -> Result<SyncSummary> {
+        let tx = self.conn.unchecked_transaction()?;
+
+        let existing: Option<i64> = self
+            .conn
+            .query_row(
+                "SELECT collection_id FROM collection_sources WHERE source = ?1",
+                params![source],
+                |row| row.get(0),
+            )
+            .optional()?;
+
+        let collection_id = match existing {
+            Some(id) => {
+                // Keep values the user changed, add variables new to the source
+                let mut merged = self.get_collection(id)?.variables;
+                for variable in variables {
+                    if !merged.iter().any(|v| v.key == variable.key) {
+                        merged.push(variable.clone());
+                    }
+                }
+                self.update_collection_variables(id, &merged)?;
+                id
+            }
+            None => {
+                let id = self.create_collection(name, variables)?;
+                self.conn.execute(
+                    "INSERT INTO collection_sources (collection_id, source) VALUES (?1, ?2)",
+                    params![id, source],
+                )?;
+                id
+            }
+        };
+
+        let mut summary = SyncSummary {
+            collection_id,
+            ..Default::default()
+        };
+
+        let items = self.get_collection_items(collection_id)?;
+        let mut sourced: HashMap<String, (CollectionItem, Request)> = HashMap::new();
+        {
+            let mut stmt = self.conn.prepare(
+                "SELECT s.item_id, s.source_key, s.generated FROM item_sources s
+                 JOIN collection_items i ON i.id = s.item_id WHERE i.collection_id = ?1",
+            )?;
+            let rows = stmt.query_map(params![collection_id], |row| {
+                let generated: String = row.get(2)?;
+                Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?, generated))
+            })?;
+            for row in rows {
+                let (item_id, key, generated) = row?;
+                if let Some(item) = items.iter().find(|item| item.id == item_id) {
+                    let generated = serde_json::from_str(&generated).unwrap_or_default();
+                    sourced.insert(key, (item.clone(), generated));
+                }
+            }
+        }
+
+        let mut folders: HashMap<String, i64> = items
+            .iter()
+            .filter(|item| item.kind == ItemKind::Folder && item.parent_id.is_none())
+            .map(|item| (item.name.clone(), item.id))
+            .collect();
+
+        for sourced_request in requests {
+            match sourced.remove(&sourced_request.key) {
+                Some((item, generated)) => {
+                    if item.request != sourced_request.request {
+                        if item.request != generated {
+                            summary.kept.push(item.name.clone());
+                            continue;
+                        }
+                        self.update_collection_item_request(item.id, &sourced_request.request)?;
+                        summary.updated += 1;
+                    }
+                    self.set_item_source(item.id, &sourced_request.key, &sourced_request.request)?;
+                }
+                None => {
+                    let parent_id = match &sourced_request.folder {
+                        Some(folder) => Some(match folders.get(folder) {
+                            Some(id) => *id,
+                            None => {
+                                let id = self.insert_collection_item(
+                                    collection_id,
+                                    None,
+                                    ItemKind::Folder,
+                                    folder,
+                                    &Request::default(),
+                                )?;
+                                folders.insert(folder.clone(), id);
+                                id
+                            }
+                        }),
+                        None => None,
+                    };
+                    let id = self.insert_collection_item(
+                        collection_id,
+                        parent_id,
+                        ItemKind::Request,
+                        &sourced_request.name,
+                        &sourced_request.request,
+                    )?;
+                    self.set_item_source(id, &sourced_request.key, &sourced_request.request)?;
+                    summary.added += 1;
+                }
+            }
+        }
+
+        // Whatever is left disappeared from the source
+        for (item, generated) in sourced.into_values() {
+            if item.request == generated {
+                self.delete_collection_item(item.id)?;
+                summary.removed += 1;
+            } else {
+                summary.kept.push(item.name);
+            }
+        }
+
+        tx.commit()?;
+        Ok(summary)
+    }
+
+    /// Where a collection was generated from, as passed to `sync_collection`.
+    pub fn get_collection_source(&self, collection_id: i64) -> Result<Option<String>> {
+        self.conn
+            .query_row(
+                "SELECT source FROM collection_sources WHERE collection_id = ?1",
+                params![collection_id],
+                |row| row.get(0),
+            )
+            .optional()
+    }
+
+    pub fn get_item_source_key(&self, item_id: i64) -> Result<Option<String>> {
+        self.conn
+            .query_row(
+                "SELECT source_key FROM item_sources WHERE item_id = ?1",
+                params![item_id],
+                |row| row.get(0),
+            )
+            .optional()
+    }
+
+    /// Records `generated` as what the source now says for an item, so the
+    /// next sync does not treat it as a local edit.
+    pub fn set_item_source(&self, item_id: i64, key: &str, generated: &Request) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO item_sources (item_id, source_key, generated) VALUES (?1, ?2, ?3)
+             ON CONFLICT(item_id) DO UPDATE
+             SET source_key = excluded.source_key, generated = excluded.generated",
+            params![
+                item_id,
+                key,
+                serde_json::to_string(generated).unwrap_or_default()
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn get_collection_tree(&self, collection_id: i64) -> Result<CollectionTree> {
+        let collection = self.get_collection(collection_id)?;
+        let items = self.get_collection_items(collection_id)?;
+
+        Ok(CollectionTree {
+            name: collection.name,
+            variables: collection.variables,
+            items: build_nodes(&items, None),
+        })
+    }
+
+    // --- Environments ---
+
+    pub fn get_environments(&self) -> Result<Vec<Environment>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT id, name, variables FROM environments ORDER BY name COLLATE NOCASE")?;
+
+        let rows = stmt.query_map([], |row| self.environment_from_row(row))?;
+
+        let mut environments = Vec::new();
+        for row in rows {
+            environments.push(row?);
+        }
+        Ok(environments)
+    }
+
+    pub fn get_environment(&self, id: i64) -> Result<Environment> {
+        self.conn.query_row(
+            "SELECT id, name, variables FROM environments WHERE id = ?1",
+            params![id],
+            |row| self.environment_from_row(row),
+        )
+    }
+
+    pub fn create_environment(&self, name: &str, variables: &[Variable]) -> Result<i64> {
+        self.conn.execute(
+            "INSERT INTO environments (name, variables) VALUES (?1, ?2)",
+            params![name, self.encode_variables(variables)?],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    pub fn update_environment(&self, environment: &Environment) -> Result<()> {
+        self.conn.execute(
+            "UPDATE environments SET name = ?1, variables = ?2 WHERE id = ?3",
+            params![
+                environment.name,
+                self.encode_variables(&environment.variables)?,
+                environment.id
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn delete_environment(&self, id: i64) -> Result<()> {
+        self.conn
+            .execute("DELETE FROM environments WHERE id = ?1", params![id])?;
+        Ok(())
+    }
+
+    fn environment_from_row(&self, row: &Row) -> Result<Environment> {
+        let variables: String = row.get(2).unwrap_or_default();
+        Ok(Environment {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            variables: self.decode_variables(&variables),
+        })
+    }
+
+    // --- Secrets ---
+
+    pub fn has_secrets_passphrase(&self) -> Result<bool> {
+        Ok(self.get_setting(SECRETS_CHECK_SETTING)?.is_some())
+    }
+
+    pub fn secrets_unlocked(&self) -> bool {
+        self.vault.borrow().is_some()
+    }
+
+    /// Unlocks secret variables for the rest of the session. The first
+    /// passphrase ever entered becomes the one to unlock with.
+    pub fn unlock_secrets(&self, passphrase: &str) -> std::result::Result<(), SecretsError> {
+        let storage = |e: rusqlite::Error| SecretsError::Storage(e.to_string());
+        let salt = self.get_setting(SECRETS_SALT_SETTING).map_err(storage)?;
+        let check = self.get_setting(SECRETS_CHECK_SETTING).map_err(storage)?;
+
+        let vault = match salt.zip(check) {
+            Some((salt, check)) => Vault::open(passphrase, &salt, &check)?,
+            None => {
+                let (vault, salt, check) = Vault::create(passphrase)?;
+                self.set_setting(SECRETS_SALT_SETTING, Some(&salt))
+                    .map_err(storage)?;
+                self.set_setting(SECRETS_CHECK_SETTING, Some(&check))
+                    .map_err(storage)?;
+                vault
+            }
+        };
+        *self.vault.borrow_mut() = Some(vault);
+        Ok(())
+    }
+
+    /// Every unlocked secret as `(name, value)`, for redacting what leaves the app.
+    pub fn secret_values(&self) -> Result<Vec<(String, String)>> {
+        let environments = self.get_environments()?.into_iter().map(|e| e.variables);
+        let collections = self.get_collections()?.into_iter().map(|c| c.variables);
+        Ok(environments
+            .chain(collections)
+            .flatten()
+            .filter(|v| v.secret && !secrets::is_encrypted(&v.value))
+            .map(|v| (v.key, v.value))
+            .collect())
+    }
+
+    /// Variables as stored: JSON with secret values encrypted. Fails while
+    /// secrets are locked if a secret has a new value.
+    fn encode_variables(&self, variables: &[Variable]) -> Result<String> {
+        let vault = self.vault.borrow();
+        let mut stored = variables.to_vec();
+        for variable in stored
+            .iter_mut()
+            .filter(|v| v.secret && !secrets::is_encrypted(&v.value))
+        {
+            secrets::check_length(variable).map_err(secrets_error)?;
+            let vault = vault
+                .as_ref()
+                .ok_or(SecretsError::Locked)
+                .map_err(secrets_error)?;
+            variable.value = vault.encrypt(&variable.value).map_err(secrets_error)?;
+        }
+        Ok(serde_json::to_string(&stored).unwrap_or_default())
+    }
+
+    /// Reads stored variables; secrets stay encrypted until unlocked.
+    fn decode_variables(&self, json: &str) -> Vec<Variable> {
+        let mut variables: Vec<Variable> = serde_json::from_str(json).unwrap_or_default();
+        if let Some(vault) = self.vault.borrow().as_ref() {
+            for variable in variables.iter_mut().filter(|v| secrets::is_locked(v)) {
+                if let Ok(value) = vault.decrypt(&variable.value) {
+                    variable.value = value;
+                }
+            }
+        }
+        variables
+    }
+
+    pub fn redaction_policy(&self) -> Result<RedactionPolicy> {
+        Ok(self
+            .get_setting(REDACTION_POLICY_SETTING)?
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default())
+    }
+
+    pub fn set_redaction_policy(&self, policy: &RedactionPolicy) -> Result<()> {
+        let json = serde_json::to_string(policy).unwrap_or_default();
+        self.set_setting(REDACTION_POLICY_SETTING, Some(&json))
+    }
+
+    pub fn retention_policy(&self) -> Result<RetentionPolicy> {
+        Ok(self
+            .get_setting(RETENTION_POLICY_SETTING)?
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default())
+    }
+
+    pub fn set_retention_policy(&self, policy: &RetentionPolicy) -> Result<()> {
+        let json = serde_json::to_string(policy).unwrap_or_default();
+        self.set_setting(RETENTION_POLICY_SETTING, Some(&json))
+    }
+
+    pub fn storage_usage(&self) -> Result<StorageUsage> {
+        retention::storage_usage(&self.conn)
+    }
+
+    /// Gives back the space `maintain` freed; see `retention::compact`.
+    pub fn compact(&self) -> Result<u64> {
+        retention::compact(&self.conn)
+    }
+
+    /// Where the database file is, for `maintain` to open it again.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    // --- Snapshots ---
+
+    pub fn get_snapshot(&self, item_id: i64) -> Result<Option<Snapshot>> {
+        let json: Option<String> = self
+            .conn
+            .query_row(
+                "SELECT snapshot FROM snapshots WHERE item_id = ?1",
+                params![item_id],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(json.and_then(|json| serde_json::from_str(&json).ok()))
+    }
+
+    /// Replaces an item's snapshot, or deletes it with `None`.
+    pub fn set_snapshot(&self, item_id: i64, snapshot: Option<&Snapshot>) -> Result<()> {
+        match snapshot {
+            Some(snapshot) => self.conn.execute(
+                "INSERT INTO snapshots (item_id, snapshot) VALUES (?1, ?2)
+                 ON CONFLICT(item_id) DO UPDATE SET snapshot = excluded.snapshot",
+                params![item_id, serde_json::to_string(snapshot).unwrap_or_default()],
+            )?,
+            None => self
+                .conn
+                .execute("DELETE FROM snapshots WHERE item_id = ?1", params![item_id])?,
+        };
+        Ok(())
+    }
+
+    // --- Settings ---
+
+    pub fn get_setting(&self, key: &str) -> Result<Option<String>> {
+        self.conn
+            .query_row(
+                "SELECT value FROM settings WHERE key = ?1",
+                params![key],
+                |row| row.get(0),
+            )
+            .optional()
+    }
+
+    pub fn set_setting(&self, key: &str, value: Option<&str>) -> Result<()> {
+        match value {
+            Some(value) => self.conn.execute(
+                "INSERT INTO settings (key, value) VALUES (?1, ?2)
+                 ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+                params![key, value],
+            )?,
+            None => self
+                .conn
+                .execute("DELETE FROM settings WHERE key = ?1", params![key])?,
+        };
+        Ok(())
+    }
+}
+
+/// Each word as a quoted FTS5 phrase, so punctuation in URLs and JSON is
+/// taken literally; the last one also matches as a prefix while typing.
+fn fts_query(text: &str) -> String {
+    let terms: Vec<String> = text
+        .split_whitespace()
+        .map(|word| format!("\"{}\"", word.replace('"', "\"\"")))
+        .collect();
+    match terms.split_last() {
+        Some((last, rest)) if !rest.is_empty() => format!("{} {}*", rest.join(" "), last),
+        Some((last, _)) => format!("{}*", last),
+        None => String::new(),
+    }
+}
+
+fn secrets_error(error: SecretsError) -> rusqlite::Error {
+    rusqlite::Error::ToSqlConversionFailure(Box::new(error))
+}
+
+fn collection_item_from_row(row: &Row) -> Result<CollectionItem> {
+    let kind: String = row.get(3)?;
+    let request: String = row.get(6).unwrap_or_default();
+    Ok(CollectionItem {
+        id: row.get(0)?,
+        collection_id: row.get(1)?,
+        parent_id: row.get(2)?,
+        kind: kind.parse().unwrap_or(ItemKind::Request),
+        name: row.get(4)?,
+        position: row.get(5)?,
+        request: serde_json::from_str(&request).unwrap_or_default(),
+    })
+}
+
+fn append_children(
+    items: &[CollectionItem],
+    parent_id: Option<i64>,
+    out: &mut Vec<CollectionItem>,
+) {
+    for item in items.iter().filter(|item| item.parent_id == parent_id) {
+        out.push(item.clone());
+        if item.kind == ItemKind::Folder {
+            append_children(items, Some(item.id), out);
+        }
+    }
+}
+
+fn build_nodes(items: &[CollectionItem], parent_id: Option<i64>) -> Vec<CollectionNode> {
+    items
+        .iter()
+        .filter(|item| item.parent_id == parent_id)
+        .map(|item| match item.kind {
+            ItemKind::Folder => CollectionNode::Folder {
+                name: item.name.clone(),
+                children: build_nodes(items, Some(item.id)),
+            },
+            ItemKind::Request => CollectionNode::Request {
+                name: item.name.clone(),
+                request: item.request.clone(),
+            },
+        })
+        .collect()
+}
@@ -0,0 +1,139 @@
+use rusqlite::{Connection, Result, params};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// How much history to keep; `None` is no limit. Pinned entries are never
+/// removed and do not count towards the entry limit.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetentionPolicy {
+    pub max_entries: Option<usize>,
+    pub max_age_days: Option<u32>,
+    /// Request and response bodies together, pinned ones included
+    pub max_body_bytes: Option<u64>,
+}
+
+impl RetentionPolicy {
+    pub fn is_unlimited(&self) -> bool {
+        *self == RetentionPolicy::default()
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct StorageUsage {
+    pub entries: usize,
+    pub pinned: usize,
+    pub body_bytes: u64,
+    /// Size of the database file, free pages included
+    pub file_bytes: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct MaintenanceReport {
+    pub removed: usize,
+}
+
+// Share of the file in free pages that makes `compact` vacuum
+const VACUUM_FREE_PERCENT: i64 = 10;
+
+const BODY_BYTES: &str = "COALESCE(length(CAST(request_body AS BLOB)), 0)
+    + COALESCE(length(CAST(response_body AS BLOB)), 0)";
+
+pub fn storage_usage(conn: &Connection) -> Result<StorageUsage> {
+    let (entries, pinned, body_bytes) = conn.query_row(
+        &format!(
+            "SELECT COUNT(*), COALESCE(SUM(pinned), 0), COALESCE(SUM({}), 0) FROM history",
+            BODY_BYTES
+        ),
+        [],
+        |row| Ok((row.get(0)?, row.get(1)?, row.get::<_, i64>(2)? as u64)),
+    )?;
+    let page_count: i64 = conn.pragma_query_value(None, "page_count", |row| row.get(0))?;
+    let page_size: i64 = conn.pragma_query_value(None, "page_size", |row| row.get(0))?;
+    Ok(StorageUsage {
+        entries,
+        pinned,
+        body_bytes,
+        file_bytes: (page_count * page_size) as u64,
+    })
+}
+
+/// Removes the entries `policy` no longer keeps, oldest first. The space
+/// they took is reused by new entries, or given back by `compact`.
+///
+/// Opens its own connection to the database at `path`, so it can run on a
+/// background thread while the app keeps using the database.
+pub fn maintain(path: &Path, policy: &RetentionPolicy) -> Result<MaintenanceReport> {
+    let mut conn = Connection::open(path)?;
+
+    let tx = conn.transaction()?;
+    let mut removed = 0;
+    if let Some(days) = policy.max_age_days {
+        removed += tx.execute(
+            "DELETE FROM history WHERE pinned = 0 AND timestamp < datetime('now', ?1)",
+            params![format!("-{} days", days)],
+        )?;
+    }
+    if let Some(max) = policy.max_entries {
+        removed += tx.execute(
+            "DELETE FROM history WHERE pinned = 0 AND id NOT IN (
+                SELECT id FROM history WHERE pinned = 0 ORDER BY id DESC LIMIT ?1
+            )",
+            params![max as i64],
+        )?;
+    }
+    if let Some(max) = policy.max_body_bytes {
+        // Pinned bodies use up part of the allowance
+        let pinned: i64 = tx.query_row(
+            &format!(
+                "SELECT COALESCE(SUM({}), 0) FROM history WHERE pinned = 1",
+                BODY_BYTES
+            ),
+            [],
+            |row| row.get(0),
+        )?;
+        removed += tx.execute(
+            &format!(
+                "DELETE FROM history WHERE id IN (
+                    SELECT id FROM (
+                        SELECT id, SUM({}) OVER (ORDER BY id DESC) AS total
+                        FROM history WHERE pinned = 0
+                    ) WHERE total > ?1
+                )",
+                BODY_BYTES
+            ),
+            params![max as i64 - pinned],
+        )?;
+    }
+    tx.execute(
+        "DELETE FROM assertion_results WHERE history_id NOT IN (SELECT id FROM history)",
+        [],
+    )?;
+    tx.commit()?;
+
+    if removed > 0 {
+        // Merges the search index segments left behind by the deletes
+        conn.execute(
+            "INSERT INTO history_fts (history_fts) VALUES ('optimize')",
+            [],
+        )?;
+    }
+
+    Ok(MaintenanceReport { removed })
+}
+
+/// Vacuums if much of the file is unused, and returns how much smaller it
+/// got. Every other writer waits until it is done, so this runs where
+/// nothing else is using the database.
+pub fn compact(conn: &Connection) -> Result<u64> {
+    // Rewriting the file is slow, so only when enough of it is unused
+    let page_count: i64 = conn.pragma_query_value(None, "page_count", |row| row.get(0))?;
+    let free_pages: i64 = conn.pragma_query_value(None, "freelist_count", |row| row.get(0))?;
+    if free_pages == 0 || free_pages * 100 < page_count * VACUUM_FREE_PERCENT {
+        return Ok(0);
+    }
+
+    let before = storage_usage(conn)?.file_bytes;
+    conn.execute_batch("VACUUM")?;
+    Ok(before.saturating_sub(storage_usage(conn)?.file_bytes))
+}
@@ -0,0 +1,255 @@
+use serde_json::Value;
+use similar::{ChangeTag, TextDiff};
+use std::collections::{BTreeSet, HashSet};
+
+use crate::{api, api::ApiResponse, database::HistoryItem, jsonpath};
+
+/// A response as kept in history or shown in the response view.
+#[derive(Debug, Clone, Default)]
+pub struct DiffSide {
+    /// Where it came from, e.g. "#12 GET /users" or "Current Response"
+    pub title: String,
+    pub status: String,
+    pub time: String,
+    pub size: String,
+    /// `key: value` lines, as stored
+    pub headers: String,
+    pub body: String,
+}
+
+impl DiffSide {
+    pub fn from_history(item: &HistoryItem) -> Self {
+        let name = if item.label.is_empty() {
+            &item.url
+        } else {
+            &item.label
+        };
+        DiffSide {
+            title: format!("#{} {} {}", item.id, item.method, name),
+            status: item.status.clone(),
+            time: item.time.clone(),
+            size: item.size.clone(),
+            headers: item.response_headers.clone(),
+            body: item.response_body.clone(),
+        }
+    }
+
+    pub fn from_response(title: &str, response: &ApiResponse) -> Self {
+        DiffSide {
+            title: title.to_string(),
+            status: response.status.clone(),
+            time: response.time.clone(),
+            size: response.size.clone(),
+            headers: response.headers.clone(),
+            body: response.body.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsonChange {
+    Added(String, Value),
+    Removed(String, Value),
+    Changed(String, Value, Value),
+}
+
+impl JsonChange {
+    pub fn path(&self) -> &str {
+        match self {
+            JsonChange::Added(path, _)
+            | JsonChange::Removed(path, _)
+            | JsonChange::Changed(path, _, _) => path,
+        }
+    }
+}
+
+/// A header missing on one side has `None` there.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HeaderChange {
+    pub name: String,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LineTag {
+    Same,
+    Removed,
+    Added,
+}
+
+#[derive(Debug, Clone)]
+pub struct ResponseDiff {
+    /// `None` when the statuses are the same
+    pub status: Option<(String, String)>,
+    /// After minus before, when both could be read
+    pub time_ms: Option<f64>,
+    pub size_bytes: Option<i64>,
+    pub headers: Vec<HeaderChange>,
+    /// Only when both bodies are JSON
+    pub json: Option<Vec<JsonChange>>,
+    pub lines: Vec<(LineTag, String)>,
+}
+
+/// Compares two responses. `ignored` holds JSONPath expressions (starting
+/// with `$`) for body values and header names for headers, such as
+/// `$..updated_at` or `date`, which are left out of the comparison.
+pub fn compare(before: &DiffSide, after: &DiffSide, ignored: &[String]) -> ResponseDiff {
+    let (paths, header_names): (Vec<&String>, Vec<&String>) =
+        ignored.iter().partition(|entry| entry.starts_with('$'));
+    let header_names: HashSet<String> = header_names
+        .iter()
+        .map(|name| name.to_lowercase())
+        .collect();
+
+    let json = match (
+        serde_json::from_str::<Value>(&before.body),
+        serde_json::from_str::<Value>(&after.body),
+    ) {
+        (Ok(a), Ok(b)) => {
+            let mut changes = Vec::new();
+            let skip = (ignored_values(&a, &paths), ignored_values(&b, &paths));
+            compare_values("$".to_string(), &a, &b, &skip, &mut changes);
+            Some(changes)
+        }
+        _ => None,
+    };
+
+    let lines = TextDiff::from_lines(&before.body, &after.body)
+        .iter_all_changes()
+        .map(|change| {
+            let tag = match change.tag() {
+                ChangeTag::Equal => LineTag::Same,
+                ChangeTag::Delete => LineTag::Removed,
+                ChangeTag::Insert => LineTag::Added,
+            };
+            (tag, change.value().trim_end_matches('\n').to_string())
+        })
+        .collect();
+
+    let time_ms = api::parse_duration_ms(&after.time)
+        .zip(api::parse_duration_ms(&before.time))
+        .map(|(after, before)| after - before);
+    let size_bytes = byte_count(&after.size)
+        .zip(byte_count(&before.size))
+        .map(|(after, before)| after - before);
+
+    ResponseDiff {
+        status: (before.status != after.status)
+            .then(|| (before.status.clone(), after.status.clone())),
+        time_ms,
+        size_bytes,
+        headers: compare_headers(&before.headers, &after.headers, &header_names),
+        json,
+        lines,
+    }
+}
+
+fn byte_count(size: &str) -> Option<i64> {
+    size.trim().trim_end_matches(" bytes").parse().ok()
+}
+
+/// The values `paths` select, by address, so the walk can skip them.
+fn ignored_values(root: &Value, paths: &[&String]) -> HashSet<*const Value> {
+    paths
+        .iter()
+        .filter_map(|path| jsonpath::select(root, path).ok())
+        .flatten()
+        .map(|value| value as *const Value)
+        .collect()
+}
+
+type Skipped = (HashSet<*const Value>, HashSet<*const Value>);
+
+fn compare_values(path: String, a: &Value, b: &Value, skip: &Skipped, out: &mut Vec<JsonChange>) {
+    if skip.0.contains(&(a as *const Value)) || skip.1.contains(&(b as *const Value)) {
+        return;
+    }
+    match (a, b) {
+        // Key order does not matter, so walk the keys sorted
+        (Value::Object(a_map), Value::Object(b_map)) => {
+            let keys: BTreeSet<&String> = a_map.keys().chain(b_map.keys()).collect();
+            for key in keys {
+                let child = child_path(&path, key);
+                match (a_map.get(key), b_map.get(key)) {
+                    (Some(a), Some(b)) => compare_values(child, a, b, skip, out),
+                    (Some(a), None) if !skip.0.contains(&(a as *const Value)) => {
+                        out.push(JsonChange::Removed(child, a.clone()))
+                    }
+                    (None, Some(b)) if !skip.1.contains(&(b as *const Value)) => {
+                        out.push(JsonChange::Added(child, b.clone()))
+                    }
+                    _ => {}
+                }
+            }
+        }
+        (Value::Array(a_items), Value::Array(b_items)) => {
+            for index in 0..a_items.len().max(b_items.len()) {
+                let child = format!("{}[{}]", path, index);
+                match (a_items.get(index), b_items.get(index)) {
+                    (Some(a), Some(b)) => compare_values(child, a, b, skip, out),
+                    (Some(a), None) if !skip.0.contains(&(a as *const Value)) => {
+                        out.push(JsonChange::Removed(child, a.clone()))
+                    }
+                    (None, Some(b)) if !skip.1.contains(&(b as *const Value)) => {
+                        out.push(JsonChange::Added(child, b.clone()))
+                    }
+                    _ => {}
+                }
+            }
+        }
+        _ if a != b => out.push(JsonChange::Changed(path, a.clone(), b.clone())),
+        _ => {}
+    }
+}
+
+/// `$.key` for plain keys, `$['odd key']` otherwise, as `jsonpath` reads them.
+fn child_path(path: &str, key: &str) -> String {
+    let plain = !key.is_empty() && key != "*" && !key.contains(['.', '[', ']', '\'', ' ']);
+    if plain {
+        format!("{}.{}", path, key)
+    } else {
+        format!("{}['{}']", path, key)
+    }
+}
+
+/// Header names compare without case; repeated headers are joined.
+fn compare_headers(before: &str, after: &str, ignored: &HashSet<String>) -> Vec<HeaderChange> {
+    let collect = |raw: &str| {
+        let mut headers: Vec<(String, String)> = Vec::new();
+        for (name, value) in api::parse_headers(raw) {
+            let name = name.to_lowercase();
+            if ignored.contains(&name) {
+                continue;
+            }
+            match headers.iter_mut().find(|(n, _)| *n == name) {
+                Some((_, existing)) => {
+                    existing.push_str(", ");
+                    existing.push_str(&value);
+                }
+                None => headers.push((name, value)),
+            }
+        }
+        headers
+    };
+    let (before, after) = (collect(before), collect(after));
+    let find = |headers: &[(String, String)], name: &str| {
+        headers
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, value)| value.clone())
+    };
+
+    let names: BTreeSet<&String> = before.iter().chain(after.iter()).map(|(n, _)| n).collect();
+    names
+        .into_iter()
+        .filter_map(|name| {
+            let (old, new) = (find(&before, name), find(&after, name));
+            (old != new).then(|| HeaderChange {
+                name: name.clone(),
+                before: old,
+                after: new,
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,87 @@
+use crate::api::{self, ApiResponse};
+use crate::jsonpath;
+use crate::models::{Extraction, Extractor, ExtractorSource, Variable};
+use regex::Regex;
+use serde_json::Value;
+
+/// Runs a request's extractors against its response, in order. Extractors
+/// without a variable name are skipped.
+pub fn extract(extractors: &[Extractor], response: &ApiResponse) -> Vec<Extraction> {
+    extractors
+        .iter()
+        .filter(|e| !e.variable.trim().is_empty())
+        .map(|extractor| Extraction {
+            variable: extractor.variable.trim().to_string(),
+            value: capture(extractor, response),
+        })
+        .collect()
+}
+
+/// Sets every captured value in `variables`, adding the ones not there yet.
+/// Returns whether anything changed.
+pub fn apply(extractions: &[Extraction], variables: &mut Vec<Variable>) -> bool {
+    let mut changed = false;
+    for extraction in extractions {
+        let Ok(value) = &extraction.value else {
+            continue;
+        };
+        match variables.iter_mut().find(|v| v.key == extraction.variable) {
+            Some(variable) if variable.value == *value && variable.enabled => {}
+            Some(variable) => {
+                variable.value = value.clone();
+                variable.enabled = true;
+                changed = true;
+            }
+            None => {
+                variables.push(Variable {
+                    key: extraction.variable.clone(),
+                    value: value.clone(),
+                    enabled: true,
+                    secret: false,
+                });
+                changed = true;
+            }
+        }
+    }
+    changed
+}
+
+fn capture(extractor: &Extractor, response: &ApiResponse) -> Result<String, String> {
+    let expression = extractor.expression.trim();
+    match extractor.source {
+        ExtractorSource::JsonPath => {
+            let body = serde_json::from_str::<Value>(&response.body)
+                .map_err(|_| "response body is not JSON".to_string())?;
+            let matches = jsonpath::select(&body, expression)?;
+            match matches.first() {
+                // Strings without their quotes, anything else as JSON text
+                Some(Value::String(text)) => Ok(text.clone()),
+                Some(value) => Ok(value.to_string()),
+                None => Err("path matched nothing".to_string()),
+            }
+        }
+        ExtractorSource::Regex => {
+            let regex = Regex::new(expression).map_err(|e| format!("invalid pattern: {}", e))?;
+            let captures = regex
+                .captures(&response.body)
+                .ok_or("pattern matched nothing")?;
+            // The first group when there is one, the whole match otherwise
+            let found = captures.get(1).or_else(|| captures.get(0));
+            Ok(found.map_or("", |m| m.as_str()).to_string())
+        }
+        ExtractorSource::Header => api::parse_headers(&response.headers)
+            .into_iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(expression))
+            .map(|(_, value)| value)
+            .ok_or_else(|| "header is missing".to_string()),
+        ExtractorSource::Cookie => api::parse_headers(&response.headers)
+            .into_iter()
+            .filter(|(key, _)| key.eq_ignore_ascii_case("set-cookie"))
+            .find_map(|(_, cookie)| {
+                let pair = cookie.split(';').next()?;
+                let (name, value) = pair.split_once('=')?;
+                (name.trim() == expression).then(|| value.trim().to_string())
+            })
+            .ok_or_else(|| "cookie was not set".to_string()),
+    }
+}
@@ -0,0 +1,571 @@
+use super::{
+    FORM_BOUNDARY, FormPart, ImportError, build_multipart, percent_encode, set_default_header,
+};
+use crate::models::{Method, Request, RequestOptions};
+use base64::{Engine, engine::general_purpose::STANDARD};
+use std::fs;
+
+// Short flags that take a value, so `-XPOST` and `-H'Accept: */*'` can be split
+const SHORT_FLAGS_WITH_VALUE: &str = "XHdFuAebomwxcEDTrYyzK";
+
+// Long flags we do not map but must skip together with their value
+const IGNORED_FLAGS_WITH_VALUE: &[&str] = &[
+    "--output",
+    "--max-time",
+    "--connect-timeout",
+    "--write-out",
+    "--retry",
+    "--retry-delay",
+    "--proxy",
+    "--cacert",
+    "--capath",
+    "--cert",
+    "--key",
+    "--cookie-jar",
+    "--resolve",
+    "--max-redirs",
+    "--range",
+    "--dump-header",
+    "--limit-rate",
+    "--config",
+    "--interface",
+    "--trace",
+    "--trace-ascii",
+];
+
+pub fn looks_like_curl(text: &str) -> bool {
+    let text = text.trim_start();
+    text.strip_prefix("curl")
+        .is_some_and(|rest| rest.starts_with(char::is_whitespace))
+}
+
+/// Parses a `curl ...` command line into a request.
+/// Supports the flags browsers and API docs emit when copying a request as curl.
+pub fn parse(command: &str) -> Result<Request, ImportError> {
+    let tokens = tokenize(command)?;
+    let mut tokens = tokens.into_iter();
+
+    if tokens.next().as_deref() != Some("curl") {
+        return Err(ImportError::Parse("not a curl command".to_string()));
+    }
+
+    // Popped from the end, so flag clusters can be pushed back expanded
+    let mut args: Vec<String> = tokens.rev().collect();
+
+    let mut method: Option<Method> = None;
+    let mut url: Option<String> = None;
+    let mut unix_socket: Option<String> = None;
+    let mut headers: Vec<(String, String)> = Vec::new();
+    let mut data: Vec<String> = Vec::new();
+    let mut form: Vec<FormPart> = Vec::new();
+    let mut json = false;
+    let mut use_get = false;
+    let mut options = RequestOptions {
+        insecure: false,
+        follow_redirects: false,
+        compressed: false,
+    };
+
+    while let Some(token) = args.pop() {
+        // Only where a flag is expected, so values like `-d -abc` stay whole
+        let mut expanded = expand_short_flags(token);
+        let arg = expanded.remove(0);
+        args.extend(expanded.into_iter().rev());
+
+        let mut value = || {
+            args.pop()
+                .ok_or_else(|| ImportError::Parse(format!("missing value for {}", arg)))
+        };
+
+        match arg.as_str() {
+            "-X" | "--request" => {
+                let name = value()?;
+                let parsed = name
+                    .to_uppercase()
+                    .parse::<Method>()
+                    .map_err(|_| ImportError::Unsupported(format!("HTTP method {}", name)))?;
+                method = Some(parsed);
+            }
+            "-I" | "--head" => {
+                return Err(ImportError::Unsupported("HTTP method HEAD".to_string()));
+            }
+            "-H" | "--header" => {
+                if let Some(header) = parse_header(&value()?) {
+                    headers.push(header);
+                }
+            }
+            "-d" | "--data" | "--data-ascii" => {
+                let v = value()?;
+                match v.strip_prefix('@') {
+                    Some(path) => data.push(read_file(path)?.replace(['\r', '\n'], "")),
+                    None => data.push(v),
+                }
+            }
+            "--data-raw" => data.push(value()?),
+            "--data-binary" => {
+                let v = value()?;
+                match v.strip_prefix('@') {
+                    Some(path) => data.push(read_file(path)?),
+                    None => data.push(v),
+                }
+            }
+            "--data-urlencode" => data.push(url_encode_data(&value()?)?),
+            "--json" => {
+                let v = value()?;
+                match v.strip_prefix('@') {
+                    Some(path) => data.push(read_file(path)?),
+                    None => data.push(v),
+                }
+                json = true;
+            }
+            "-F" | "--form" => form.push(parse_form_part(&value()?, false)?),
+            "--form-string" => form.push(parse_form_part(&value()?, true)?),
+            "-u" | "--user" => {
+                let credentials = value()?;
+                let credentials = if credentials.contains(':') {
+                    credentials
+                } else {
+                    format!("{}:", credentials)
+                };
+                headers.push((
+                    "Authorization".to_string(),
+                    format!("Basic {}", STANDARD.encode(credentials)),
+                ));
+            }
+            "-A" | "--user-agent" => headers.push(("User-Agent".to_string(), value()?)),
+            "-e" | "--referer" => headers.push(("Referer".to_string(), value()?)),
+            "-b" | "--cookie" => {
+                // Without `=` the value names a cookie file, which we cannot follow
+                let cookie = value()?;
+                if cookie.contains('=') {
+                    headers.push(("Cookie".to_string(), cookie));
+                }
+            }
+            "--url" => url = Some(value()?),
+            "--unix-socket" => unix_socket = Some(value()?),
+            "-k" | "--insecure" => options.insecure = true,
+            "-L" | "--location" => options.follow_redirects = true,
+            "--compressed" => options.compressed = true,
+            "-G" | "--get" => use_get = true,
+            "-T" | "--upload-file" => {
+                return Err(ImportError::Unsupported(
+                    "uploading a file with -T".to_string(),
+                ));
+            }
+            flag if IGNORED_FLAGS_WITH_VALUE.contains(&flag) => {
+                value()?;
+            }
+            flag if flag.len() == 2
+                && flag.starts_with('-')
+                && SHORT_FLAGS_WITH_VALUE.contains(&flag[1..]) =>
+            {
+                value()?;
+            }
+            flag if flag.starts_with('-') && flag.len() > 1 => {}
+            _ => {
+                if url.is_none() {
+                    url = Some(arg);
+                }
+            }
+        }
+    }
+
+    let mut url = url.ok_or_else(|| ImportError::Parse("no URL found".to_string()))?;
+
+    if !data.is_empty() && !form.is_empty() {
+        return Err(ImportError::Parse(
+            "cannot combine --data and --form".to_string(),
+        ));
+    }
+
+    let mut body = String::new();
+    if !form.is_empty() {
+        body = build_multipart(&form);
+        set_default_header(
+            &mut headers,
+            "Content-Type",
+            &format!("multipart/form-data; boundary={}", FORM_BOUNDARY),
+        );
+    } else if !data.is_empty() {
+        let joined = data.join("&");
+        if use_get {
+            let separator = if url.contains('?') { '&' } else { '?' };
+            url = format!("{}{}{}", url, separator, joined);
+        } else if json {
+            body = joined;
+            set_default_header(&mut headers, "Content-Type", "application/json");
+            set_default_header(&mut headers, "Accept", "application/json");
+        } else {
+            body = joined;
+            set_default_header(
+                &mut headers,
+                "Content-Type",
+                "application/x-www-form-urlencoded",
+            );
+        }
+    }
+
+    let method = match method {
+        Some(m) => m,
+        None if use_get => Method::GET,
+        None if !body.is_empty() => Method::POST,
+        None => Method::GET,
+    };
+
+    let url = match unix_socket {
+        Some(socket) => {
+            let without_scheme = url.split_once("://").map(|(_, rest)| rest).unwrap_or(&url);
+            let path = without_scheme
+                .find('/')
+                .map(|idx| &without_scheme[idx..])
+                .unwrap_or("/");
+            format!("unix:{}:{}", socket, path)
+        }
+        None if url.contains("://") => url,
+        None => format!("http://{}", url),
+    };
+
+    Ok(Request {
+        method,
+        url,
+        headers,
+        body,
+        options,
+        ..Default::default()
+    })
+}
+
+/// Splits a command line the way a POSIX shell would, including `$'...'`
+/// strings and backslash line continuations.
+fn tokenize(command: &str) -> Result<Vec<String>, ImportError> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut in_token = false;
+    let mut chars = command.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => {
+                in_token = true;
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(ch) => current.push(ch),
+                        None => return Err(unterminated()),
+                    }
+                }
+            }
+            '"' => {
+                in_token = true;
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(ch @ ('$' | '`' | '"' | '\\')) => current.push(ch),
+                            Some('\n') => {}
+                            Some(ch) => {
+                                current.push('\\');
+                                current.push(ch);
+                            }
+                            None => return Err(unterminated()),
+                        },
+                        Some(ch) => current.push(ch),
+                        None => return Err(unterminated()),
+                    }
+                }
+            }
+            '$' if chars.peek() == Some(&'\'') => {
+                chars.next();
+                in_token = true;
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some('\\') => match chars.next() {
+                            Some('n') => current.push('\n'),
+                            Some('t') => current.push('\t'),
+                            Some('r') => current.push('\r'),
+                            Some(ch) => current.push(ch),
+                            None => return Err(unterminated()),
+                        },
+                        Some(ch) => current.push(ch),
+                        None => return Err(unterminated()),
+                    }
+                }
+            }
+            '\\' => match chars.peek() {
+                // Line continuation; pasting into a single-line entry may have
+                // turned the newline into a space
+                Some(ch) if ch.is_whitespace() => {
+                    chars.next();
+                    if in_token {
+                        tokens.push(std::mem::take(&mut current));
+                        in_token = false;
+                    }
+                }
+                Some(_) => {
+                    in_token = true;
+                    current.push(chars.next().unwrap_or_default());
+                }
+                None => {}
+            },
+            ch if ch.is_whitespace() => {
+                if in_token {
+                    tokens.push(std::mem::take(&mut current));
+                    in_token = false;
+                }
+            }
+            ch => {
+                in_token = true;
+                current.push(ch);
+            }
+        }
+    }
+
+    if in_token {
+        tokens.push(current);
+    }
+
+    Ok(tokens)
+}
+
+fn unterminated() -> ImportError {
+    ImportError::Parse("unterminated quote".to_string())
+}
+
+/// Turns `-sSL` into `-s -S -L` and `-XPOST` into `-X POST`; other tokens
+/// come back unchanged.
+fn expand_short_flags(token: String) -> Vec<String> {
+    if token.len() <= 2 || !token.starts_with('-') || token.starts_with("--") {
+        return vec![token];
+    }
+
+    let mut args = Vec::new();
+    for (idx, flag) in token[1..].char_indices() {
+        args.push(format!("-{}", flag));
+        if SHORT_FLAGS_WITH_VALUE.contains(flag) {
+            let rest = &token[idx + 1 + flag.len_utf8()..];
+            if !rest.is_empty() {
+                args.push(rest.to_string());
+            }
+            break;
+        }
+    }
+    args
+}
+
+fn parse_header(raw: &str) -> Option<(String, String)> {
+    // `-H 'X-Empty;'` sends an empty header, `-H 'Accept:'` removes one
+    if let Some(name) = raw.strip_suffix(';')
+        && !name.contains(':')
+    {
+        return Some((name.trim().to_string(), String::new()));
+    }
+
+    let (name, value) = raw.split_once(':')?;
+    let value = value.trim();
+    if value.is_empty() {
+        return None;
+    }
+    Some((name.trim().to_string(), value.to_string()))
+}
+
+fn read_file(path: &str) -> Result<String, ImportError> {
+    let path = expand_home(path);
+    let bytes = fs::read(&path).map_err(|e| ImportError::Io(format!("{}: {}", path, e)))?;
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+fn expand_home(path: &str) -> String {
+    match (path.strip_prefix("~/"), std::env::var("HOME")) {
+        (Some(rest), Ok(home)) => format!("{}/{}", home, rest),
+        _ => path.to_string(),
+    }
+}
+
+fn url_encode_data(value: &str) -> Result<String, ImportError> {
+    // Mirrors curl: `content`, `=content`, `name=content`, `@file`, `name@file`
+    if let Some((name, content)) = value.split_once('=') {
+        return Ok(if name.is_empty() {
+            percent_encode(content)
+        } else {
+            format!("{}={}", name, percent_encode(content))
+        });
+    }
+
+    if let Some((name, path)) = value.split_once('@') {
+        let content = read_file(path)?;
+        return Ok(if name.is_empty() {
+            percent_encode(&content)
+        } else {
+            format!("{}={}", name, percent_encode(&content))
+        });
+    }
+
+    Ok(percent_encode(value))
+}
+
+fn parse_form_part(raw: &str, literal: bool) -> Result<FormPart, ImportError> {
+    let (name, value) = raw
+        .split_once('=')
+        .ok_or_else(|| ImportError::Parse(format!("invalid form field: {}", raw)))?;
+
+    if literal {
+        return Ok(FormPart {
+            name: name.to_string(),
+            content: value.to_string(),
+            filename: None,
+            content_type: None,
+        });
+    }
+
+    let mut params = value.split(';');
+    let value = params.next().unwrap_or_default();
+    let mut content_type = None;
+    let mut filename = None;
+    for param in params {
+        match param.trim().split_once('=') {
+            Some(("type", t)) => content_type = Some(t.to_string()),
+            Some(("filename", f)) => filename = Some(f.trim_matches('"').to_string()),
+            _ => {}
+        }
+    }
+
+    let part = if let Some(path) = value.strip_prefix('@') {
+        let default_name = std::path::Path::new(path)
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        FormPart {
+            name: name.to_string(),
+            content: read_file(path)?,
+            filename: filename.or(Some(default_name)),
+            content_type,
+        }
+    } else if let Some(path) = value.strip_prefix('<') {
+        FormPart {
+            name: name.to_string(),
+            content: read_file(path)?,
+            filename,
+            content_type,
+        }
+    } else {
+        FormPart {
+            name: name.to_string(),
+            content: value.to_string(),
+            filename,
+            content_type,
+        }
+    };
+
+    Ok(part)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header<'a>(request: &'a Request, name: &str) -> Option<&'a str> {
+        request
+            .headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    #[test]
+    fn parses_a_copied_browser_request() {
+        let request = parse(
+            "curl 'https://api.example.com/users?page=2' \\\n  -H 'Accept: application/json' \\\n  -H $'X-Note: a\\tb' \\\n  --data-raw '{\"name\":\"ada\"}' \\\n  --compressed",
+        )
+        .unwrap();
+
+        assert_eq!(request.method, Method::POST);
+        assert_eq!(request.url, "https://api.example.com/users?page=2");
+        assert_eq!(header(&request, "Accept"), Some("application/json"));
+        assert_eq!(header(&request, "X-Note"), Some("a\tb"));
+        assert_eq!(request.body, "{\"name\":\"ada\"}");
+        assert!(request.options.compressed);
+    }
+
+    #[test]
+    fn splits_short_flag_clusters() {
+        let request = parse("curl -sSLkXPUT -H'Accept: */*' example.com").unwrap();
+
+        assert_eq!(request.method, Method::PUT);
+        assert_eq!(request.url, "http://example.com");
+        assert_eq!(header(&request, "Accept"), Some("*/*"));
+        assert!(request.options.follow_redirects);
+        assert!(request.options.insecure);
+    }
+
+    #[test]
+    fn keeps_values_that_start_with_a_dash() {
+        let request = parse("curl -d -abc -H '-x-foo: 1' -H -x-bar:2 http://a.test").unwrap();
+
+        assert_eq!(request.body, "-abc");
+        assert_eq!(header(&request, "-x-foo"), Some("1"));
+        assert_eq!(header(&request, "-x-bar"), Some("2"));
+        assert_eq!(request.url, "http://a.test");
+    }
+
+    #[test]
+    fn keeps_empty_headers_and_drops_removed_ones() {
+        let request = parse("curl -H 'X-Empty;' -H 'Accept:' http://a.test").unwrap();
+
+        assert_eq!(
+            request.headers,
+            vec![("X-Empty".to_string(), String::new())]
+        );
+    }
+
+    #[test]
+    fn moves_data_into_the_query_with_get() {
+        let request = parse("curl -G -d a=1 --data-urlencode 'q=x y' http://a.test?z=0").unwrap();
+
+        assert_eq!(request.method, Method::GET);
+        assert_eq!(request.url, "http://a.test?z=0&a=1&q=x%20y");
+        assert!(request.body.is_empty());
+    }
+
+    #[test]
+    fn encodes_basic_auth_and_json() {
+        let request = parse("curl -u ada --json '{}' http://a.test").unwrap();
+
+        assert_eq!(header(&request, "Authorization"), Some("Basic YWRhOg=="));
+        assert_eq!(header(&request, "Content-Type"), Some("application/json"));
+        assert_eq!(request.body, "{}");
+    }
+
+    #[test]
+    fn builds_unix_socket_urls() {
+        let request =
+            parse("curl --unix-socket /var/run/docker.sock http://localhost/v1.43/containers/json")
+                .unwrap();
+
+        assert_eq!(
+            request.url,
+            "unix:/var/run/docker.sock:/v1.43/containers/json"
+        );
+    }
+
+    #[test]
+    fn reports_what_it_cannot_import() {
+        assert!(matches!(
+            parse("curl -T file.txt http://a.test"),
+            Err(ImportError::Unsupported(_))
+        ));
+        assert!(matches!(
+            parse("curl -I http://a.test"),
+            Err(ImportError::Unsupported(_))
+        ));
+        assert!(matches!(parse("curl -H"), Err(ImportError::Parse(_))));
+        assert!(matches!(
+            parse("curl 'http://a.test"),
+            Err(ImportError::Parse(_))
+        ));
+        assert!(matches!(
+            parse("wget http://a.test"),
+            Err(ImportError::Parse(_))
+        ));
+    }
+}
@@ -0,0 +1,178 @@
+use super::ImportError;
+use serde_json::Value;
+use std::{fs, path::Path};
+
+/// Rows of variables for a data-driven run. Every row has one value per column.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DataSet {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+impl DataSet {
+    /// Reads a `.json` file as an array of objects, anything else as CSV.
+    pub fn load(path: &Path) -> Result<DataSet, ImportError> {
+        let text = fs::read_to_string(path)?;
+        let is_json = path.extension().is_some_and(|ext| ext == "json");
+        let data = if is_json {
+            parse_json(&text)?
+        } else {
+            parse_csv(&text)?
+        };
+        if data.rows.is_empty() {
+            return Err(ImportError::Parse(format!(
+                "{} has no rows",
+                path.display()
+            )));
+        }
+        Ok(data)
+    }
+
+    /// `column=value` pairs, for naming a row in results.
+    pub fn describe(&self, row: usize) -> String {
+        self.values(row)
+            .map(|(column, value)| format!("{}={}", column, value))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    pub fn values(&self, row: usize) -> impl Iterator<Item = (&str, &str)> {
+        let values = self.rows.get(row).map(Vec::as_slice).unwrap_or_default();
+        self.columns
+            .iter()
+            .map(String::as_str)
+            .zip(values.iter().map(String::as_str))
+    }
+
+    /// The given rows as CSV with a header line, e.g. to re-run only the failures.
+    pub fn to_csv(&self, rows: &[usize]) -> String {
+        let mut csv = csv_line(&self.columns);
+        for row in rows {
+            if let Some(values) = self.rows.get(*row) {
+                csv.push_str(&csv_line(values));
+            }
+        }
+        csv
+    }
+}
+
+/// RFC 4180 CSV: the first record names the columns, quoted fields may contain
+/// commas, quotes (doubled) and line breaks.
+pub fn parse_csv(text: &str) -> Result<DataSet, ImportError> {
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    let mut records: Vec<Vec<String>> = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' if in_quotes => in_quotes = false,
+            '"' if field.is_empty() => in_quotes = true,
+            ',' if !in_quotes => record.push(std::mem::take(&mut field)),
+            '\r' if !in_quotes && chars.peek() == Some(&'\n') => {}
+            '\n' if !in_quotes => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            c => field.push(c),
+        }
+    }
+    if in_quotes {
+        return Err(ImportError::Parse("unterminated quoted field".to_string()));
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+
+    // Blank lines separate nothing
+    records.retain(|r| !(r.len() == 1 && r[0].trim().is_empty()));
+
+    let mut records = records.into_iter();
+    let columns: Vec<String> = records
+        .next()
+        .ok_or_else(|| ImportError::Parse("file is empty".to_string()))?
+        .into_iter()
+        .map(|c| c.trim().to_string())
+        .collect();
+
+    let mut rows = Vec::new();
+    for (line, mut row) in records.enumerate() {
+        if row.len() > columns.len() {
+            return Err(ImportError::Parse(format!(
+                "row {} has {} fields, the header has {}",
+                line + 1,
+                row.len(),
+                columns.len()
+            )));
+        }
+        row.resize(columns.len(), String::new());
+        rows.push(row);
+    }
+
+    Ok(DataSet { columns, rows })
+}
+
+/// An array of objects. Columns are the union of their keys; strings are used
+/// as they are, other values as JSON text.
+pub fn parse_json(text: &str) -> Result<DataSet, ImportError> {
+    let value: Value = serde_json::from_str(text).map_err(|e| ImportError::Parse(e.to_string()))?;
+    let Value::Array(items) = value else {
+        return Err(ImportError::Unsupported(
+            "expected an array of objects".to_string(),
+        ));
+    };
+
+    let mut columns: Vec<String> = Vec::new();
+    for (index, item) in items.iter().enumerate() {
+        let Value::Object(map) = item else {
+            return Err(ImportError::Unsupported(format!(
+                "item {} is not an object",
+                index + 1
+            )));
+        };
+        for key in map.keys() {
+            if !columns.contains(key) {
+                columns.push(key.clone());
+            }
+        }
+    }
+
+    let rows = items
+        .iter()
+        .map(|item| {
+            columns
+                .iter()
+                .map(|column| match item.get(column) {
+                    None | Some(Value::Null) => String::new(),
+                    Some(Value::String(text)) => text.clone(),
+                    Some(other) => other.to_string(),
+                })
+                .collect()
+        })
+        .collect();
+
+    Ok(DataSet { columns, rows })
+}
+
+fn csv_line(fields: &[String]) -> String {
+    let mut line = fields
+        .iter()
+        .map(|field| {
+            if field.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", field.replace('"', "\"\""))
+            } else {
+                field.clone()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",");
+    line.push('\n');
+    line
+}
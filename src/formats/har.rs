@@ -0,0 +1,471 @@
+use super::ImportError;
+use crate::api;
+use crate::database::HistoryItem;
+use crate::models::Method;
+use base64::{Engine, engine::general_purpose::STANDARD};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::time::Duration;
+
+// SQLite's CURRENT_TIMESTAMP format, always UTC
+const DB_TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Har {
+    pub log: Log,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Log {
+    pub version: String,
+    pub creator: Creator,
+    #[serde(default)]
+    pub entries: Vec<Entry>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Creator {
+    pub name: String,
+    pub version: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Entry {
+    pub started_date_time: String,
+    pub time: f64,
+    pub request: HarRequest,
+    pub response: HarResponse,
+    #[serde(default)]
+    pub cache: Value,
+    pub timings: Timings,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HarRequest {
+    pub method: String,
+    pub url: String,
+    #[serde(default)]
+    pub http_version: String,
+    #[serde(default)]
+    pub cookies: Vec<NameValue>,
+    #[serde(default)]
+    pub headers: Vec<NameValue>,
+    #[serde(default)]
+    pub query_string: Vec<NameValue>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post_data: Option<PostData>,
+    #[serde(default = "unknown_size")]
+    pub headers_size: i64,
+    #[serde(default = "unknown_size")]
+    pub body_size: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HarResponse {
+    pub status: u16,
+    #[serde(default)]
+    pub status_text: String,
+    #[serde(default)]
+    pub http_version: String,
+    #[serde(default)]
+    pub cookies: Vec<NameValue>,
+    #[serde(default)]
+    pub headers: Vec<NameValue>,
+    pub content: Content,
+    #[serde(default, rename = "redirectURL")]
+    pub redirect_url: String,
+    #[serde(default = "unknown_size")]
+    pub headers_size: i64,
+    #[serde(default = "unknown_size")]
+    pub body_size: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NameValue {
+    pub name: String,
+    pub value: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PostData {
+    #[serde(default)]
+    pub mime_type: String,
+    #[serde(default)]
+    pub text: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub params: Vec<NameValue>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Content {
+    #[serde(default)]
+    pub size: i64,
+    #[serde(default)]
+    pub mime_type: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encoding: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Timings {
+    #[serde(default = "unknown_size")]
+    pub send: f64,
+    #[serde(default = "unknown_size")]
+    pub wait: f64,
+    #[serde(default = "unknown_size")]
+    pub receive: f64,
+}
+
+fn unknown_size<T: From<i8>>() -> T {
+    T::from(-1)
+}
+
+pub struct HarImport {
+    pub items: Vec<HistoryItem>,
+    pub skipped: usize,
+}
+
+/// Builds a HAR 1.2 document from history entries.
+pub fn export(items: &[HistoryItem]) -> String {
+    let har = Har {
+        log: Log {
+            version: "1.2".to_string(),
+            creator: Creator {
+                name: "Dispatch".to_string(),
+                version: env!("CARGO_PKG_VERSION").to_string(),
+            },
+            entries: items.iter().map(export_entry).collect(),
+        },
+    };
+
+    serde_json::to_string_pretty(&har).unwrap_or_default()
+}
+
+fn export_entry(item: &HistoryItem) -> Entry {
+    let request = item.to_request();
+    let response_headers = api::parse_headers(&item.response_headers);
+    let time = api::parse_duration_ms(&item.time).unwrap_or(0.0);
+
+    // A timestamp in another format is passed on as stored rather than replaced
+    let started_date_time = NaiveDateTime::parse_from_str(&item.timestamp, DB_TIMESTAMP_FORMAT)
+        .map(|t| {
+            t.and_utc()
+                .to_rfc3339_opts(chrono::SecondsFormat::Millis, true)
+        })
+        .unwrap_or_else(|_| item.timestamp.clone());
+
+    let query_string = reqwest::Url::parse(&request.url)
+        .map(|url| {
+            url.query_pairs()
+                .map(|(name, value)| NameValue {
+                    name: name.into_owned(),
+                    value: value.into_owned(),
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let header_value = |headers: &[(String, String)], name: &str| {
+        headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.clone())
+    };
+
+    let post_data = (!request.body.is_empty()).then(|| PostData {
+        mime_type: header_value(&request.headers, "content-type").unwrap_or_default(),
+        text: request.body.clone(),
+        params: Vec::new(),
+    });
+
+    let (status, status_text) = split_status(&item.status);
+    let size = item
+        .size
+        .trim_end_matches(" bytes")
+        .parse::<i64>()
+        .unwrap_or(-1);
+
+    Entry {
+        started_date_time,
+        time,
+        request: HarRequest {
+            method: request.method.as_str().to_string(),
+            url: request.url.clone(),
+            http_version: "HTTP/1.1".to_string(),
+            cookies: Vec::new(),
+            headers: to_name_values(&request.headers),
+            query_string,
+            post_data,
+            headers_size: -1,
+            body_size: request.body.len() as i64,
+        },
+        response: HarResponse {
+            status,
+            status_text,
+            http_version: "HTTP/1.1".to_string(),
+            cookies: Vec::new(),
+            content: Content {
+                size,
+                mime_type: header_value(&response_headers, "content-type").unwrap_or_default(),
+                text: Some(item.response_body.clone()),
+                encoding: None,
+            },
+            headers: to_name_values(&response_headers),
+            redirect_url: header_value(&response_headers, "location").unwrap_or_default(),
+            headers_size: -1,
+            body_size: size,
+        },
+        cache: Value::Object(Default::default()),
+        // Only the time to the response headers is recorded
+        timings: Timings {
+            send: 0.0,
+            wait: time,
+            receive: 0.0,
+        },
+    }
+}
+
+/// Turns the entries of a HAR file (e.g. saved from browser devtools) into
+/// history items. Entries with methods Dispatch cannot send, or with a total
+/// time too large to be a duration, are skipped.
+pub fn import(json: &str) -> Result<HarImport, ImportError> {
+    let har: Har = serde_json::from_str(json)
+        .map_err(|e| ImportError::Parse(format!("invalid HAR: {}", e)))?;
+
+    let mut items = Vec::new();
+    let mut skipped = 0;
+
+    for entry in har.log.entries {
+        let Ok(method) = entry.request.method.parse::<Method>() else {
+            skipped += 1;
+            continue;
+        };
+        // Negative means unknown; HAR has no other way to say so
+        let Ok(time) = Duration::try_from_secs_f64(entry.time.max(0.0) / 1000.0) else {
+            skipped += 1;
+            continue;
+        };
+        items.push(import_entry(method, time, entry));
+    }
+
+    Ok(HarImport { items, skipped })
+}
+
+fn import_entry(method: Method, time: Duration, entry: Entry) -> HistoryItem {
+    let request_headers: Vec<(String, String)> = entry
+        .request
+        .headers
+        .into_iter()
+        // HTTP/2 pseudo headers such as `:authority` cannot be sent back
+        .filter(|h| !h.name.starts_with(':'))
+        .map(|h| (h.name, h.value))
+        .collect();
+
+    let request_body = entry
+        .request
+        .post_data
+        .map(|data| {
+            if data.text.is_empty() && !data.params.is_empty() {
+                data.params
+                    .iter()
+                    .map(|p| format!("{}={}", p.name, p.value))
+                    .collect::<Vec<_>>()
+                    .join("&")
+            } else {
+                data.text
+            }
+        })
+        .unwrap_or_default();
+
+    let mut response_headers = String::new();
+    for header in entry.response.headers {
+        response_headers.push_str(&format!(
+            "{}: {}\n",
+            header.name.to_lowercase(),
+            header.value
+        ));
+    }
+
+    let content = entry.response.content;
+    let raw_body = match (content.text, content.encoding.as_deref()) {
+        (Some(text), Some("base64")) => STANDARD
+            .decode(text.trim())
+            .map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+            .unwrap_or_default(),
+        (Some(text), _) => text,
+        (None, _) => String::new(),
+    };
+    // Store bodies the way `perform_request` would have rendered them
+    let response_body = match serde_json::from_str::<Value>(&raw_body) {
+        Ok(json) => serde_json::to_string_pretty(&json).unwrap_or(raw_body),
+        Err(_) => raw_body,
+    };
+
+    let status_text = if entry.response.status_text.is_empty() {
+        reqwest::StatusCode::from_u16(entry.response.status)
+            .ok()
+            .and_then(|s| s.canonical_reason())
+            .unwrap_or("")
+            .to_string()
+    } else {
+        entry.response.status_text
+    };
+
+    let size = if content.size >= 0 {
+        content.size
+    } else {
+        entry.response.body_size.max(0)
+    };
+
+    let timestamp = DateTime::parse_from_rfc3339(&entry.started_date_time)
+        .map(|t| t.with_timezone(&Utc))
+        .unwrap_or_else(|_| Utc::now())
+        .format(DB_TIMESTAMP_FORMAT)
+        .to_string();
+
+    HistoryItem {
+        id: 0,
+        method: method.as_str().to_string(),
+        url: entry.request.url,
+        request_body,
+        request_headers: serde_json::to_string(&request_headers).unwrap_or_default(),
+        response_body,
+        response_headers,
+        status: format!("{} {}", entry.response.status, status_text),
+        time: format!("{:.2?}", time),
+        size: format!("{} bytes", size),
+        timestamp,
+        ..Default::default()
+    }
+}
+
+fn to_name_values(pairs: &[(String, String)]) -> Vec<NameValue> {
+    pairs
+        .iter()
+        .map(|(name, value)| NameValue {
+            name: name.clone(),
+            value: value.clone(),
+        })
+        .collect()
+}
+
+fn split_status(status: &str) -> (u16, String) {
+    let (code, text) = status.split_once(' ').unwrap_or((status, ""));
+    (code.parse().unwrap_or(0), text.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn exchange() -> HistoryItem {
+        HistoryItem {
+            method: "POST".to_string(),
+            url: "https://api.example.com/users?page=2".to_string(),
+            request_body: "{\"name\":\"ada\"}".to_string(),
+            request_headers: "[[\"Content-Type\",\"application/json\"]]".to_string(),
+            response_body: "{\n  \"id\": 7\n}".to_string(),
+            response_headers: "content-type: application/json\n".to_string(),
+            status: "201 Created".to_string(),
+            time: "1.23s".to_string(),
+            size: "12 bytes".to_string(),
+            timestamp: "2024-05-01 12:30:00".to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn entry(method: &str, time: &str) -> String {
+        format!(
+            r#"{{"startedDateTime": "2024-05-01T12:30:00.000Z", "time": {time},
+                "request": {{"method": "{method}", "url": "http://a.test/"}},
+                "response": {{"status": 200, "content": {{}}}},
+                "timings": {{}}}}"#
+        )
+    }
+
+    fn har(entries: &[String]) -> String {
+        format!(
+            r#"{{"log": {{"version": "1.2", "creator": {{"name": "t", "version": "1"}},
+                "entries": [{}]}}}}"#,
+            entries.join(",")
+        )
+    }
+
+    #[test]
+    fn round_trips_history_entries() {
+        let original = exchange();
+        let import = import(&export(std::slice::from_ref(&original))).unwrap();
+
+        assert_eq!(import.skipped, 0);
+        let item = &import.items[0];
+        assert_eq!(item.method, original.method);
+        assert_eq!(item.url, original.url);
+        assert_eq!(item.request_body, original.request_body);
+        assert_eq!(item.request_headers, original.request_headers);
+        assert_eq!(item.response_body, original.response_body);
+        assert_eq!(item.response_headers, original.response_headers);
+        assert_eq!(item.status, original.status);
+        assert_eq!(item.time, original.time);
+        assert_eq!(item.size, original.size);
+        assert_eq!(item.timestamp, original.timestamp);
+    }
+
+    #[test]
+    fn exports_timestamps_it_cannot_parse_as_stored() {
+        let item = HistoryItem {
+            timestamp: "yesterday".to_string(),
+            ..exchange()
+        };
+        let json: Value = serde_json::from_str(&export(&[item])).unwrap();
+
+        assert_eq!(json["log"]["entries"][0]["startedDateTime"], "yesterday");
+    }
+
+    #[test]
+    fn imports_timings_and_skips_impossible_ones() {
+        let import = import(&har(&[
+            entry("GET", "1234.5"),
+            entry("GET", "-1"),
+            entry("GET", "1e300"),
+            entry("OPTIONS", "1"),
+        ]))
+        .unwrap();
+
+        let times: Vec<_> = import.items.iter().map(|item| item.time.as_str()).collect();
+        assert_eq!(times, ["1.23s", "0.00ns"]);
+        assert_eq!(import.skipped, 2);
+        assert_eq!(import.items[0].timestamp, "2024-05-01 12:30:00");
+        assert_eq!(import.items[0].status, "200 OK");
+    }
+
+    #[test]
+    fn drops_pseudo_headers_and_decodes_base64_bodies() {
+        let json = har(&[r#"{"startedDateTime": "2024-05-01T12:30:00Z", "time": 5,
+            "request": {"method": "GET", "url": "http://a.test/",
+                "headers": [{"name": ":authority", "value": "a.test"},
+                            {"name": "Accept", "value": "*/*"}]},
+            "response": {"status": 200, "statusText": "OK",
+                "headers": [{"name": "Content-Type", "value": "text/plain"}],
+                "content": {"size": 5, "text": "aGVsbG8=", "encoding": "base64"}},
+            "timings": {}}"#
+            .to_string()]);
+        let item = &import(&json).unwrap().items[0];
+
+        assert_eq!(item.request_headers, "[[\"Accept\",\"*/*\"]]");
+        assert_eq!(item.response_headers, "content-type: text/plain\n");
+        assert_eq!(item.response_body, "hello");
+    }
+
+    #[test]
+    fn rejects_files_that_are_not_har() {
+        assert!(matches!(import("{}"), Err(ImportError::Parse(_))));
+    }
+}
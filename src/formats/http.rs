@@ -0,0 +1,482 @@
+use super::ImportError;
+use crate::models::{CollectionNode, CollectionTree, Method, Request, Variable};
+use std::{fs, path::Path};
+
+/// Collection source prefix for `.http` files, followed by the file's path
+pub const COLLECTION_SOURCE: &str = "http:";
+
+/// A parsed `.http` / `.rest` file, as used by the REST Client and JetBrains
+/// HTTP Client editor plugins.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HttpFile {
+    /// `@name = value` declarations
+    pub variables: Vec<Variable>,
+    pub requests: Vec<HttpRequest>,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HttpRequest {
+    /// Unique within the file, taken from `# @name`, the `###` title or the request line
+    pub name: String,
+    pub request: Request,
+    /// The path after `< ` when the body is read from a file
+    pub body_file: Option<String>,
+    named: bool,
+    // Lines from the first directive or the request line to the end of the body
+    span: (usize, usize),
+}
+
+/// Parses a `.http` file. Bodies given as `< ./file` are read relative to `base_dir`.
+pub fn parse(text: &str, base_dir: &Path) -> Result<HttpFile, ImportError> {
+    let lines: Vec<&str> = text.lines().collect();
+    let mut file = HttpFile::default();
+
+    let mut start = 0;
+    let mut title = None;
+    for (index, line) in lines.iter().enumerate() {
+        if let Some(rest) = line.trim_start().strip_prefix("###") {
+            parse_block(&lines, start, index, title.take(), base_dir, &mut file);
+            start = index + 1;
+            title = Some(rest.trim().to_string()).filter(|t| !t.is_empty());
+        }
+    }
+    parse_block(&lines, start, lines.len(), title, base_dir, &mut file);
+
+    if file.requests.is_empty() {
+        return Err(ImportError::Parse("no requests found".to_string()));
+    }
+    Ok(file)
+}
+
+fn parse_block(
+    lines: &[&str],
+    start: usize,
+    end: usize,
+    title: Option<String>,
+    base_dir: &Path,
+    file: &mut HttpFile,
+) {
+    let mut name = title;
+    let mut named = false;
+    let mut follow_redirects = true;
+    let mut span_start = None;
+    let mut index = start;
+
+    // Variables, comments and directives come before the request line
+    let request_line = loop {
+        if index >= end {
+            return;
+        }
+        let line = lines[index].trim();
+        index += 1;
+
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(comment) = comment(line) {
+            if let Some(value) = comment.strip_prefix("@name") {
+                name = Some(value.trim().trim_start_matches('=').trim().to_string());
+                named = true;
+                span_start.get_or_insert(index - 1);
+            } else if comment.starts_with("@no-redirect") {
+                follow_redirects = false;
+                span_start.get_or_insert(index - 1);
+            }
+            continue;
+        }
+        if let Some(declaration) = line.strip_prefix('@')
+            && let Some((key, value)) = declaration.split_once('=')
+        {
+            file.variables.push(Variable {
+                key: key.trim().to_string(),
+                value: value.trim().to_string(),
+                enabled: true,
+                secret: false,
+            });
+            continue;
+        }
+        break line;
+    };
+    let span_start = *span_start.get_or_insert(index - 1);
+
+    let mut parts: Vec<&str> = request_line.split_whitespace().collect();
+    if parts.len() > 1 && parts.last().is_some_and(|p| p.starts_with("HTTP/")) {
+        parts.pop();
+    }
+    let (method, mut url) = match parts.as_slice() {
+        [method, url @ ..] if !url.is_empty() && method.chars().all(|c| c.is_ascii_uppercase()) => {
+            (*method, url.join(" "))
+        }
+        _ => ("GET", parts.join(" ")),
+    };
+
+    // Query parameters may continue on indented `?` / `&` lines
+    while index < end {
+        let line = lines[index].trim();
+        if !line.starts_with('?') && !line.starts_with('&') {
+            break;
+        }
+        url.push_str(line);
+        index += 1;
+    }
+    let mut span_end = index;
+
+    let mut headers = Vec::new();
+    while index < end {
+        let line = lines[index].trim();
+        index += 1;
+        if line.is_empty() {
+            break;
+        }
+        span_end = index;
+        if comment(line).is_some() {
+            continue;
+        }
+        match line.split_once(':') {
+            Some((key, value)) => headers.push((key.trim().to_string(), value.trim().to_string())),
+            None => file
+                .warnings
+                .push(format!("{}: ignored malformed header `{}`", url, line)),
+        }
+    }
+
+    let mut body_lines = Vec::new();
+    while index < end {
+        let line = lines[index];
+        index += 1;
+        // JetBrains response handlers and response references
+        if line.starts_with("> ") || line.starts_with(">> ") || line.starts_with("<> ") {
+            file.warnings
+                .push(format!("{}: response handlers are not supported", url));
+            break;
+        }
+        body_lines.push(line);
+        if !line.trim().is_empty() {
+            span_end = index;
+        }
+    }
+    while body_lines.last().is_some_and(|l| l.trim().is_empty()) {
+        body_lines.pop();
+    }
+
+    let mut body_file = None;
+    let body = match body_lines.as_slice() {
+        [line] if line.trim_start().starts_with("< ") => {
+            let reference = line.trim_start()[2..].trim().to_string();
+            let content = match fs::read_to_string(base_dir.join(&reference)) {
+                Ok(content) => content,
+                Err(e) => {
+                    file.warnings
+                        .push(format!("{}: could not read {}: {}", url, reference, e));
+                    String::new()
+                }
+            };
+            body_file = Some(reference);
+            content
+        }
+        _ => body_lines.join("\n"),
+    };
+
+    let Ok(method) = method.parse::<Method>() else {
+        file.warnings
+            .push(format!("{} {}: method is not supported", method, url));
+        return;
+    };
+
+    let mut name = name
+        .filter(|n| !n.is_empty())
+        .unwrap_or_else(|| format!("{} {}", method.as_str(), url));
+    let base = name.clone();
+    let mut copy = 2;
+    while file.requests.iter().any(|r| r.name == name) {
+        name = format!("{} ({})", base, copy);
+        copy += 1;
+    }
+
+    let mut request = Request {
+        method,
+        url,
+        headers,
+        body,
+        ..Default::default()
+    };
+    request.options.follow_redirects = follow_redirects;
+
+    file.requests.push(HttpRequest {
+        name,
+        request,
+        body_file,
+        named,
+        span: (span_start, span_end),
+    });
+}
+
+fn comment(line: &str) -> Option<&str> {
+    line.strip_prefix('#')
+        .or_else(|| line.strip_prefix("//"))
+        .map(str::trim)
+}
+
+/// Renders one request, without its `###` separator.
+fn write_request(out: &mut String, name: Option<&str>, request: &Request, body_file: Option<&str>) {
+    if let Some(name) = name {
+        out.push_str(&format!("# @name {}\n", name));
+    }
+    if !request.options.follow_redirects {
+        out.push_str("# @no-redirect\n");
+    }
+    out.push_str(&format!("{} {}\n", request.method.as_str(), request.url));
+    for (key, value) in &request.headers {
+        out.push_str(&format!("{}: {}\n", key, value));
+    }
+    match body_file {
+        Some(reference) => out.push_str(&format!("\n< {}\n", reference)),
+        None if !request.body.is_empty() => {
+            out.push('\n');
+            out.push_str(request.body.trim_end_matches('\n'));
+            out.push('\n');
+        }
+        None => {}
+    }
+}
+
+/// Replaces the request called `name` in the file text, leaving everything
+/// around it as written. A `< file` body reference is kept if the body still
+/// matches the referenced file. Returns the new text and the request's name
+/// in it, which changes with the method or URL of an unnamed request.
+pub fn replace_request(
+    text: &str,
+    base_dir: &Path,
+    name: &str,
+    request: &Request,
+) -> Result<(String, String), ImportError> {
+    let file = parse(text, base_dir)?;
+    let existing = file
+        .requests
+        .iter()
+        .find(|r| r.name == name)
+        .ok_or_else(|| ImportError::Parse(format!("“{}” is no longer in the file", name)))?;
+
+    let body_file = existing
+        .body_file
+        .as_deref()
+        .filter(|_| existing.request.body == request.body);
+
+    let mut replacement = String::new();
+    write_request(
+        &mut replacement,
+        existing.named.then_some(existing.name.as_str()),
+        request,
+        body_file,
+    );
+
+    let lines: Vec<&str> = text.lines().collect();
+    let (start, end) = existing.span;
+    let mut out = String::new();
+    for line in &lines[..start] {
+        out.push_str(line);
+        out.push('\n');
+    }
+    out.push_str(&replacement);
+    for line in &lines[end..] {
+        out.push_str(line);
+        out.push('\n');
+    }
+
+    let name = parse(&out, base_dir)?
+        .requests
+        .into_iter()
+        .find(|r| r.span.0 == start)
+        .map(|r| r.name)
+        .unwrap_or_else(|| name.to_string());
+    Ok((out, name))
+}
+
+/// Writes a collection as a `.http` file. Folders are flattened, their names
+/// kept in the request titles.
+pub fn export_collection(tree: &CollectionTree) -> String {
+    let mut out = String::new();
+    for variable in tree.variables.iter().filter(|v| v.enabled) {
+        // Secrets are declared without their value, to be filled in by hand
+        let value = if variable.secret { "" } else { &variable.value };
+        out.push_str(&format!("@{} = {}\n", variable.key, value));
+    }
+    write_nodes(&mut out, &tree.items, "");
+    out
+}
+
+fn write_nodes(out: &mut String, nodes: &[CollectionNode], prefix: &str) {
+    for node in nodes {
+        match node {
+            CollectionNode::Folder { name, children } => {
+                write_nodes(out, children, &format!("{}{} / ", prefix, name));
+            }
+            CollectionNode::Request { name, request } => {
+                if !out.is_empty() {
+                    out.push('\n');
+                }
+                out.push_str(&format!("### {}{}\n", prefix, name));
+                write_request(out, None, request, None);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FILE: &str = "\
+@host = http://a.test
+# a comment
+
+GET {{host}}/users
+    ?page=2
+    &size=10
+Accept: application/json
+
+###
+
+# @name create
+# @no-redirect
+POST {{host}}/users HTTP/1.1
+Content-Type: application/json
+
+{
+  \"name\": \"ada\"
+}
+
+> {% client.global.set(\"id\", response.body.id) %}
+
+### Titled
+DELETE {{host}}/users/1
+
+### Titled
+DELETE {{host}}/users/2
+";
+
+    fn here() -> &'static Path {
+        Path::new(".")
+    }
+
+    #[test]
+    fn parses_requests_variables_and_directives() {
+        let file = parse(FILE, here()).unwrap();
+
+        assert_eq!(file.variables.len(), 1);
+        assert_eq!(file.variables[0].value, "http://a.test");
+
+        let names: Vec<_> = file.requests.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "GET {{host}}/users?page=2&size=10",
+                "create",
+                "Titled",
+                "Titled (2)"
+            ]
+        );
+
+        let list = &file.requests[0].request;
+        assert_eq!(list.url, "{{host}}/users?page=2&size=10");
+        assert_eq!(
+            list.headers,
+            vec![("Accept".to_string(), "application/json".to_string())]
+        );
+        assert!(list.options.follow_redirects);
+
+        let create = &file.requests[1].request;
+        assert_eq!(create.method, Method::POST);
+        assert_eq!(create.url, "{{host}}/users");
+        assert_eq!(create.body, "{\n  \"name\": \"ada\"\n}");
+        assert!(!create.options.follow_redirects);
+
+        assert_eq!(file.warnings.len(), 1);
+    }
+
+    #[test]
+    fn reads_bodies_from_files_next_to_it() {
+        let dir = std::env::temp_dir().join(format!("dispatch-http-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("body.json"), "{}").unwrap();
+
+        let file = parse("POST http://a.test\n\n< ./body.json\n", &dir).unwrap();
+        let _ = fs::remove_dir_all(&dir);
+
+        assert_eq!(file.requests[0].request.body, "{}");
+        assert_eq!(file.requests[0].body_file.as_deref(), Some("./body.json"));
+    }
+
+    #[test]
+    fn replaces_one_request_and_keeps_the_rest() {
+        let file = parse(FILE, here()).unwrap();
+        let mut request = file.requests[1].request.clone();
+        request.method = Method::PUT;
+        request.body = "{}".to_string();
+
+        let (text, name) = replace_request(FILE, here(), "create", &request).unwrap();
+
+        assert_eq!(name, "create");
+        assert!(text.starts_with("@host = http://a.test\n# a comment\n\nGET {{host}}/users\n"));
+        assert!(text.contains(
+            "# @name create\n# @no-redirect\nPUT {{host}}/users\nContent-Type: application/json\n\n{}\n\n> {%"
+        ));
+        assert_eq!(parse(&text, here()).unwrap().requests.len(), 4);
+    }
+
+    #[test]
+    fn renames_unnamed_requests_that_change() {
+        let file = parse(FILE, here()).unwrap();
+        let mut request = file.requests[0].request.clone();
+        request.url = "{{host}}/people".to_string();
+
+        let (text, name) =
+            replace_request(FILE, here(), "GET {{host}}/users?page=2&size=10", &request).unwrap();
+
+        assert_eq!(name, "GET {{host}}/people");
+        assert!(
+            replace_request(&text, here(), "GET {{host}}/users?page=2&size=10", &request).is_err()
+        );
+    }
+
+    #[test]
+    fn exports_collections_it_can_read_back() {
+        let tree = CollectionTree {
+            name: "c".to_string(),
+            variables: vec![Variable {
+                key: "token".to_string(),
+                value: "s3cr3t".to_string(),
+                enabled: true,
+                secret: true,
+            }],
+            items: vec![CollectionNode::Folder {
+                name: "Users".to_string(),
+                children: vec![CollectionNode::Request {
+                    name: "Create".to_string(),
+                    request: Request {
+                        method: Method::POST,
+                        url: "http://a.test/users".to_string(),
+                        body: "{}".to_string(),
+                        ..Default::default()
+                    },
+                }],
+            }],
+        };
+
+        let text = export_collection(&tree);
+        assert!(!text.contains("s3cr3t"));
+
+        let file = parse(&text, here()).unwrap();
+        assert_eq!(file.requests[0].name, "Users / Create");
+        assert_eq!(file.requests[0].request.body, "{}");
+    }
+
+    #[test]
+    fn rejects_files_without_requests() {
+        assert!(matches!(
+            parse("@a = 1\n# nothing here\n", here()),
+            Err(ImportError::Parse(_))
+        ));
+    }
+}
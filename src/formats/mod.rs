@@ -0,0 +1,103 @@
+pub mod curl;
+pub mod data;
+pub mod har;
+pub mod http;
+pub mod openapi;
+pub mod postman;
+pub mod report;
+
+use serde_json::Value;
+
+pub const FORM_BOUNDARY: &str = "----DispatchFormBoundary7MA4YWxkTrZu0gW";
+
+#[derive(Debug, Clone)]
+pub enum ImportError {
+    Parse(String),
+    Unsupported(String),
+    Io(String),
+}
+
+impl std::fmt::Display for ImportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImportError::Parse(msg) => write!(f, "Parse error: {}", msg),
+            ImportError::Unsupported(msg) => write!(f, "Unsupported: {}", msg),
+            ImportError::Io(msg) => write!(f, "I/O error: {}", msg),
+        }
+    }
+}
+
+impl From<std::io::Error> for ImportError {
+    fn from(e: std::io::Error) -> Self {
+        ImportError::Io(e.to_string())
+    }
+}
+
+pub fn percent_encode(value: &str) -> String {
+    let mut encoded = String::new();
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+pub struct FormPart {
+    pub name: String,
+    pub content: String,
+    pub filename: Option<String>,
+    pub content_type: Option<String>,
+}
+
+/// Renders form fields as a `multipart/form-data` body delimited by `FORM_BOUNDARY`.
+pub fn build_multipart(parts: &[FormPart]) -> String {
+    let mut body = String::new();
+    for part in parts {
+        body.push_str(&format!("--{}\r\n", FORM_BOUNDARY));
+        body.push_str(&format!(
+            "Content-Disposition: form-data; name=\"{}\"",
+            part.name
+        ));
+        if let Some(filename) = &part.filename {
+            body.push_str(&format!("; filename=\"{}\"", filename));
+        }
+        body.push_str("\r\n");
+        if let Some(content_type) = &part.content_type {
+            body.push_str(&format!("Content-Type: {}\r\n", content_type));
+        }
+        body.push_str("\r\n");
+        body.push_str(&part.content);
+        body.push_str("\r\n");
+    }
+    body.push_str(&format!("--{}--\r\n", FORM_BOUNDARY));
+    body
+}
+
+/// Adds the header unless the request already sets it, in any case.
+pub fn set_default_header(headers: &mut Vec<(String, String)>, name: &str, value: &str) {
+    if !headers.iter().any(|(k, _)| k.eq_ignore_ascii_case(name)) {
+        headers.push((name.to_string(), value.to_string()));
+    }
+}
+
+/// The string at `key`, or empty when it is missing or not a string.
+pub fn str_field(value: &Value, key: &str) -> String {
+    value
+        .get(key)
+        .and_then(Value::as_str)
+        .unwrap_or("")
+        .to_string()
+}
+
+/// Strings without their quotes, `null` as empty and anything else as JSON.
+pub fn value_to_string(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
@@ -0,0 +1,831 @@
+use super::{
+    FORM_BOUNDARY, FormPart, ImportError, build_multipart, percent_encode, str_field,
+    value_to_string,
+};
+use crate::models::{Method, Request, SourcedRequest, Variable};
+use serde_json::{Map, Value, json};
+use std::path::Path;
+
+// Guards example synthesis against recursive schemas
+const MAX_SCHEMA_DEPTH: usize = 8;
+
+const HTTP_METHODS: [&str; 8] = [
+    "get", "put", "post", "delete", "options", "head", "patch", "trace",
+];
+
+/// Collection source prefix for imported specs, followed by the spec's path
+pub const COLLECTION_SOURCE: &str = "openapi:";
+
+pub struct OpenApiImport {
+    pub name: String,
+    pub variables: Vec<Variable>,
+    pub requests: Vec<SourcedRequest>,
+    pub warnings: Vec<String>,
+}
+
+/// Identifies the spec at `path` across re-imports. Two specs that share a
+/// title stay apart, and one opened through another path stays together.
+pub fn collection_source(path: &Path) -> String {
+    let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    format!("{}{}", COLLECTION_SOURCE, path.display())
+}
+
+/// Reads an OpenAPI 3.x or Swagger 2.0 document, in YAML or JSON.
+pub fn import(text: &str) -> Result<OpenApiImport, ImportError> {
+    // JSON is valid YAML, so one parser covers both
+    let root: Value = serde_yaml::from_str(text)
+        .map_err(|e| ImportError::Parse(format!("invalid YAML or JSON: {}", e)))?;
+
+    // Unquoted in YAML, `openapi: 3.0` and `swagger: 2.0` are numbers
+    let version = if let Some(version) = root.get("openapi").map(value_to_string) {
+        if !version.starts_with('3') {
+            return Err(ImportError::Unsupported(format!("OpenAPI {}", version)));
+        }
+        SpecVersion::OpenApi3
+    } else if root.get("swagger").map(value_to_string).as_deref() == Some("2.0") {
+        SpecVersion::Swagger2
+    } else {
+        return Err(ImportError::Parse(
+            "missing `openapi` or `swagger` version, not an API specification".to_string(),
+        ));
+    };
+
+    let title = root
+        .pointer("/info/title")
+        .and_then(Value::as_str)
+        .filter(|t| !t.trim().is_empty())
+        .unwrap_or("Imported API")
+        .to_string();
+
+    let mut spec = Spec {
+        root: &root,
+        version,
+        warnings: Vec::new(),
+        path_variables: Vec::new(),
+        expanding: Vec::new(),
+    };
+
+    let mut variables = spec.server_variables();
+    let mut requests = Vec::new();
+
+    let paths = root.get("paths").and_then(Value::as_object);
+    for (path, path_item) in paths.into_iter().flatten() {
+        let path_item = spec.resolve(path_item);
+        let shared_parameters = path_item
+            .get("parameters")
+            .and_then(Value::as_array)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+
+        for method_name in HTTP_METHODS {
+            let Some(operation) = path_item.get(method_name) else {
+                continue;
+            };
+            let key = format!("{} {}", method_name.to_uppercase(), path);
+
+            let Ok(method) = method_name.to_uppercase().parse::<Method>() else {
+                spec.warnings.push(format!(
+                    "{}: method is not supported, operation skipped",
+                    key
+                ));
+                continue;
+            };
+
+            let request = spec.operation_request(method, path, operation, shared_parameters, &key);
+
+            requests.push(SourcedRequest {
+                name: operation_name(operation, &key),
+                folder: operation
+                    .pointer("/tags/0")
+                    .and_then(Value::as_str)
+                    .map(str::to_string),
+                key,
+                request,
+            });
+        }
+    }
+
+    variables.append(&mut spec.path_variables);
+
+    Ok(OpenApiImport {
+        name: title,
+        variables,
+        requests,
+        warnings: spec.warnings,
+    })
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum SpecVersion {
+    OpenApi3,
+    Swagger2,
+}
+
+struct Spec<'a> {
+    root: &'a Value,
+    version: SpecVersion,
+    warnings: Vec<String>,
+    /// Path parameters with an example value; the rest stay unresolved `{{name}}`s
+    path_variables: Vec<Variable>,
+    /// `$ref`s currently being sampled
+    expanding: Vec<String>,
+}
+
+impl<'a> Spec<'a> {
+    /// Follows local `$ref`s; external references resolve to `null`.
+    fn resolve(&mut self, value: &'a Value) -> &'a Value {
+        let mut value = value;
+        for _ in 0..MAX_SCHEMA_DEPTH {
+            let Some(reference) = value.get("$ref").and_then(Value::as_str) else {
+                return value;
+            };
+            match reference
+                .strip_prefix('#')
+                .and_then(|pointer| self.root.pointer(pointer))
+            {
+                Some(target) => value = target,
+                None => {
+                    let warning = format!("{}: only local references are supported", reference);
+                    if !self.warnings.contains(&warning) {
+                        self.warnings.push(warning);
+                    }
+                    return &Value::Null;
+                }
+            }
+        }
+        value
+    }
+
+    /// The first server becomes `baseUrl`, any others `baseUrl2`, `baseUrl3`...
+    fn server_variables(&mut self) -> Vec<Variable> {
+        let urls: Vec<String> = match self.version {
+            SpecVersion::OpenApi3 => self
+                .root
+                .get("servers")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(|server| {
+                    let mut url = server.get("url")?.as_str()?.to_string();
+                    // Server variables take their default values
+                    if let Some(vars) = server.get("variables").and_then(Value::as_object) {
+                        for (name, var) in vars {
+                            let default = var.get("default").map(value_to_string);
+                            url =
+                                url.replace(&format!("{{{}}}", name), &default.unwrap_or_default());
+                        }
+                    }
+                    Some(url.trim_end_matches('/').to_string())
+                })
+                .collect(),
+            SpecVersion::Swagger2 => {
+                let host = self.root.get("host").and_then(Value::as_str);
+                let base_path = self
+                    .root
+                    .get("basePath")
+                    .and_then(Value::as_str)
+                    .unwrap_or("")
+                    .trim_end_matches('/');
+                let schemes: Vec<&str> = self
+                    .root
+                    .get("schemes")
+                    .and_then(Value::as_array)
+                    .map(|s| s.iter().filter_map(Value::as_str).collect())
+                    .unwrap_or_default();
+                match host {
+                    Some(host) => {
+                        let schemes = if schemes.is_empty() {
+                            vec!["https"]
+                        } else {
+                            schemes
+                        };
+                        schemes
+                            .iter()
+                            .map(|scheme| format!("{}://{}{}", scheme, host, base_path))
+                            .collect()
+                    }
+                    None => vec![base_path.to_string()],
+                }
+            }
+        };
+
+        if urls.is_empty()
+            || urls
+                .iter()
+                .any(|url| url.starts_with('/') || url.is_empty())
+        {
+            self.warnings.push(
+                "the spec has no absolute server URL, set the `baseUrl` variable".to_string(),
+            );
+        }
+
+        let mut urls = urls;
+        if urls.is_empty() {
+            urls.push(String::new());
+        }
+
+        urls.into_iter()
+            .enumerate()
+            .map(|(index, url)| Variable {
+                key: if index == 0 {
+                    "baseUrl".to_string()
+                } else {
+                    format!("baseUrl{}", index + 1)
+                },
+                value: url,
+                enabled: true,
+                secret: false,
+            })
+            .collect()
+    }
+
+    fn operation_request(
+        &mut self,
+        method: Method,
+        path: &str,
+        operation: &'a Value,
+        shared_parameters: &'a [Value],
+        key: &str,
+    ) -> Request {
+        let mut query = Vec::new();
+        let mut headers = Vec::new();
+        let mut form_fields = Vec::new();
+        let mut body_schema = None;
+
+        // Operation parameters override path-level ones with the same name and location
+        let mut parameters: Vec<&Value> = Vec::new();
+        for parameter in operation
+            .get("parameters")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .chain(shared_parameters)
+        {
+            let parameter = self.resolve(parameter);
+            let same = |p: &&Value| {
+                p.get("name") == parameter.get("name") && p.get("in") == parameter.get("in")
+            };
+            if !parameters.iter().any(same) {
+                parameters.push(parameter);
+            }
+        }
+
+        for parameter in parameters {
+            let name = str_field(parameter, "name");
+            let required = parameter
+                .get("required")
+                .and_then(Value::as_bool)
+                .unwrap_or(false);
+
+            match str_field(parameter, "in").as_str() {
+                "path" => {
+                    if let Some(value) = self.parameter_value(parameter, false)
+                        && !self.path_variables.iter().any(|v| v.key == name)
+                    {
+                        self.path_variables.push(Variable {
+                            key: name,
+                            value,
+                            enabled: true,
+                            secret: false,
+                        });
+                    }
+                }
+                "query" => {
+                    if let Some(value) = self.parameter_value(parameter, required) {
+                        query.push(format!(
+                            "{}={}",
+                            percent_encode(&name),
+                            percent_encode(&value)
+                        ));
+                    }
+                }
+                "header" => {
+                    if let Some(value) = self.parameter_value(parameter, required) {
+                        headers.push((name, value));
+                    }
+                }
+                "cookie" if required => {
+                    self.warnings.push(format!(
+                        "{}: cookie parameter `{}` was not added",
+                        key, name
+                    ));
+                }
+                // Swagger 2 bodies and forms are parameters
+                "body" => body_schema = parameter.get("schema"),
+                "formData" => {
+                    if str_field(parameter, "type") == "file" {
+                        self.warnings
+                            .push(format!("{}: file field `{}` was not added", key, name));
+                    } else {
+                        let value = self.parameter_value(parameter, true).unwrap_or_default();
+                        form_fields.push((name, value));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let mut url = format!("{{{{baseUrl}}}}{}", template_path(path));
+        if !query.is_empty() {
+            url.push('?');
+            url.push_str(&query.join("&"));
+        }
+
+        let body = match self.version {
+            SpecVersion::OpenApi3 => self.openapi3_body(operation, &mut headers, key),
+            SpecVersion::Swagger2 => {
+                self.swagger2_body(operation, body_schema, form_fields, &mut headers)
+            }
+        };
+
+        Request {
+            method,
+            url,
+            headers,
+            body,
+            ..Default::default()
+        }
+    }
+
+    /// Value for a query or header parameter. Optional parameters are only
+    /// included when the spec gives an example or default.
+    fn parameter_value(&mut self, parameter: &'a Value, required: bool) -> Option<String> {
+        let schema = parameter.get("schema").map(|s| self.resolve(s));
+
+        let given = parameter
+            .get("example")
+            .or_else(|| {
+                parameter
+                    .get("examples")
+                    .and_then(Value::as_object)
+                    .and_then(|examples| examples.values().next())
+                    .and_then(|example| example.get("value"))
+            })
+            .or_else(|| parameter.get("default"))
+            .or_else(|| schema.and_then(|s| s.get("example")))
+            .or_else(|| schema.and_then(|s| s.get("default")))
+            .or_else(|| parameter.pointer("/enum/0"))
+            .or_else(|| schema.and_then(|s| s.pointer("/enum/0")));
+
+        match given {
+            Some(value) => Some(value_to_string(value)),
+            None if required => {
+                // Swagger 2 puts the type on the parameter itself
+                let sample = match schema {
+                    Some(schema) => self.sample(schema, 0),
+                    None => self.sample(parameter, 0),
+                };
+                Some(value_to_string(&sample))
+            }
+            None => None,
+        }
+    }
+
+    fn openapi3_body(
+        &mut self,
+        operation: &'a Value,
+        headers: &mut Vec<(String, String)>,
+        key: &str,
+    ) -> String {
+        let Some(request_body) = operation.get("requestBody") else {
+            return String::new();
+        };
+        let request_body = self.resolve(request_body);
+        let Some(content) = request_body.get("content").and_then(Value::as_object) else {
+            return String::new();
+        };
+
+        let Some((content_type, media)) = preferred_media_type(content) else {
+            return String::new();
+        };
+
+        let example = media.get("example").cloned().or_else(|| {
+            media
+                .get("examples")
+                .and_then(Value::as_object)
+                .and_then(|examples| examples.values().next())
+                .map(|example| self.resolve(example))
+                .and_then(|example| example.get("value"))
+                .cloned()
+        });
+        let sample = match example {
+            Some(example) => example,
+            None => match media.get("schema") {
+                Some(schema) => self.sample(schema, 0),
+                None => Value::Null,
+            },
+        };
+
+        self.render_body(content_type, sample, headers, key)
+    }
+
+    fn swagger2_body(
+        &mut self,
+        operation: &'a Value,
+        body_schema: Option<&'a Value>,
+        form_fields: Vec<(String, String)>,
+        headers: &mut Vec<(String, String)>,
+    ) -> String {
+        let consumes: Vec<&str> = operation
+            .get("consumes")
+            .or_else(|| self.root.get("consumes"))
+            .and_then(Value::as_array)
+            .map(|c| c.iter().filter_map(Value::as_str).collect())
+            .unwrap_or_default();
+
+        if let Some(schema) = body_schema {
+            let content_type = consumes
+                .iter()
+                .find(|c| c.contains("json"))
+                .or(consumes.first())
+                .copied()
+                .unwrap_or("application/json");
+            let sample = self.sample(schema, 0);
+            return self.render_body(content_type, sample, headers, "");
+        }
+
+        if form_fields.is_empty() {
+            return String::new();
+        }
+
+        let content_type = if consumes.contains(&"multipart/form-data") {
+            "multipart/form-data"
+        } else {
+            "application/x-www-form-urlencoded"
+        };
+        let fields = form_fields
+            .into_iter()
+            .map(|(name, value)| (name, Value::String(value)))
+            .collect::<Map<_, _>>();
+        self.render_body(content_type, Value::Object(fields), headers, "")
+    }
+
+    fn render_body(
+        &mut self,
+        content_type: &str,
+        sample: Value,
+        headers: &mut Vec<(String, String)>,
+        key: &str,
+    ) -> String {
+        let fields = || -> Vec<(String, String)> {
+            sample
+                .as_object()
+                .map(|fields| {
+                    fields
+                        .iter()
+                        .map(|(name, value)| (name.clone(), value_to_string(value)))
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+
+        let (header, body) = if content_type.contains("json") {
+            let body = if sample.is_null() {
+                String::new()
+            } else {
+                serde_json::to_string_pretty(&sample).unwrap_or_default()
+            };
+            (content_type.to_string(), body)
+        } else if content_type == "application/x-www-form-urlencoded" {
+            let body = fields()
+                .iter()
+                .map(|(name, value)| format!("{}={}", percent_encode(name), percent_encode(value)))
+                .collect::<Vec<_>>()
+                .join("&");
+            (content_type.to_string(), body)
+        } else if content_type == "multipart/form-data" {
+            let parts: Vec<FormPart> = fields()
+                .into_iter()
+                .map(|(name, content)| FormPart {
+                    name,
+                    content,
+                    filename: None,
+                    content_type: None,
+                })
+                .collect();
+            (
+                format!("multipart/form-data; boundary={}", FORM_BOUNDARY),
+                build_multipart(&parts),
+            )
+        } else if let Value::String(text) = &sample {
+            (content_type.to_string(), text.clone())
+        } else {
+            if !key.is_empty() {
+                self.warnings.push(format!(
+                    "{}: no example body could be generated for {}",
+                    key, content_type
+                ));
+            }
+            (content_type.to_string(), String::new())
+        };
+
+        if !headers
+            .iter()
+            .any(|(k, _)| k.eq_ignore_ascii_case("content-type"))
+        {
+            headers.push(("Content-Type".to_string(), header));
+        }
+        body
+    }
+
+    /// Synthesizes an example value from a schema, preferring examples and
+    /// defaults the spec provides.
+    fn sample(&mut self, schema: &'a Value, depth: usize) -> Value {
+        if depth > MAX_SCHEMA_DEPTH {
+            return Value::Null;
+        }
+
+        if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+            // A schema that contains itself is only expanded once
+            if self.expanding.iter().any(|r| r == reference) {
+                return Value::Null;
+            }
+            self.expanding.push(reference.to_string());
+            let target = self.resolve(schema);
+            let value = self.sample(target, depth + 1);
+            self.expanding.pop();
+            return value;
+        }
+
+        if let Some(example) = schema.get("example").or_else(|| schema.get("default")) {
+            return example.clone();
+        }
+        if let Some(value) = schema.pointer("/enum/0").or_else(|| schema.get("const")) {
+            return value.clone();
+        }
+
+        if let Some(all_of) = schema.get("allOf").and_then(Value::as_array) {
+            let mut merged = Map::new();
+            for part in all_of {
+                if let Value::Object(fields) = self.sample(part, depth + 1) {
+                    merged.extend(fields);
+                }
+            }
+            return Value::Object(merged);
+        }
+        if let Some(first) = schema
+            .pointer("/oneOf/0")
+            .or_else(|| schema.pointer("/anyOf/0"))
+        {
+            return self.sample(first, depth + 1);
+        }
+
+        // OpenAPI 3.1 allows a list of types, e.g. ["string", "null"]
+        let schema_type = match schema.get("type") {
+            Some(Value::String(t)) => t.as_str(),
+            Some(Value::Array(types)) => types
+                .iter()
+                .filter_map(Value::as_str)
+                .find(|t| *t != "null")
+                .unwrap_or("null"),
+            _ if schema.get("properties").is_some() => "object",
+            _ if schema.get("items").is_some() => "array",
+            _ => "",
+        };
+
+        match schema_type {
+            "object" => {
+                let required: Vec<&str> = schema
+                    .get("required")
+                    .and_then(Value::as_array)
+                    .map(|names| names.iter().filter_map(Value::as_str).collect())
+                    .unwrap_or_default();
+
+                let mut fields = Map::new();
+                for (name, property) in schema
+                    .get("properties")
+                    .and_then(Value::as_object)
+                    .into_iter()
+                    .flatten()
+                {
+                    let property_schema = self.resolve(property);
+                    if property_schema
+                        .get("readOnly")
+                        .and_then(Value::as_bool)
+                        .unwrap_or(false)
+                    {
+                        continue;
+                    }
+                    let value = self.sample(property, depth + 1);
+                    if !value.is_null() || required.contains(&name.as_str()) {
+                        fields.insert(name.clone(), value);
+                    }
+                }
+                Value::Object(fields)
+            }
+            "array" => match schema.get("items") {
+                Some(items) => Value::Array(vec![self.sample(items, depth + 1)]),
+                None => json!([]),
+            },
+            "string" => json!(match str_field(schema, "format").as_str() {
+                "date-time" => "2024-01-01T00:00:00Z",
+                "date" => "2024-01-01",
+                "email" => "user@example.com",
+                "uuid" => "3fa85f64-5717-4562-b3fc-2c963f66afa6",
+                "uri" | "url" => "https://example.com",
+                "ipv4" => "192.0.2.1",
+                _ => "string",
+            }),
+            "integer" => json!(0),
+            "number" => json!(0.0),
+            "boolean" => json!(false),
+            _ => Value::Null,
+        }
+    }
+}
+
+fn preferred_media_type(content: &Map<String, Value>) -> Option<(&str, &Value)> {
+    let find = |predicate: &dyn Fn(&str) -> bool| {
+        content
+            .iter()
+            .find(|(content_type, _)| predicate(content_type))
+            .map(|(content_type, media)| (content_type.as_str(), media))
+    };
+
+    find(&|c| c == "application/json")
+        .or_else(|| find(&|c| c.contains("json")))
+        .or_else(|| find(&|c| c == "application/x-www-form-urlencoded"))
+        .or_else(|| find(&|c| c == "multipart/form-data"))
+        .or_else(|| content.iter().next().map(|(c, m)| (c.as_str(), m)))
+}
+
+/// `/users/{id}` becomes `/users/{{id}}`.
+fn template_path(path: &str) -> String {
+    path.replace('{', "{{").replace('}', "}}")
+}
+
+fn operation_name(operation: &Value, key: &str) -> String {
+    ["summary", "operationId"]
+        .iter()
+        .map(|field| str_field(operation, field))
+        .find(|name| !name.trim().is_empty())
+        .unwrap_or_else(|| key.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request<'a>(import: &'a OpenApiImport, key: &str) -> &'a Request {
+        &import
+            .requests
+            .iter()
+            .find(|r| r.key == key)
+            .unwrap_or_else(|| panic!("no request {}", key))
+            .request
+    }
+
+    fn variable<'a>(import: &'a OpenApiImport, key: &str) -> Option<&'a str> {
+        import
+            .variables
+            .iter()
+            .find(|v| v.key == key)
+            .map(|v| v.value.as_str())
+    }
+
+    const OPENAPI: &str = r#"
+openapi: 3.0.3
+info:
+  title: Users
+servers:
+  - url: https://{region}.example.com/v1/
+    variables:
+      region:
+        default: eu
+  - url: http://localhost:8080
+paths:
+  /users/{id}:
+    parameters:
+      - name: id
+        in: path
+        required: true
+        example: 42
+    get:
+      summary: Get user
+      tags: [users]
+      parameters:
+        - name: fields
+          in: query
+          required: true
+          schema: {type: string, enum: [name, email]}
+        - name: page
+          in: query
+          schema: {type: integer}
+        - name: X-Trace
+          in: header
+          example: abc
+    put:
+      operationId: updateUser
+      requestBody:
+        content:
+          application/json:
+            schema:
+              $ref: '#/components/schemas/User'
+    trace:
+      responses: {}
+components:
+  schemas:
+    User:
+      type: object
+      required: [name, manager]
+      properties:
+        id: {type: integer, readOnly: true}
+        name: {type: string}
+        email: {type: string, format: email}
+        manager:
+          $ref: '#/components/schemas/User'
+"#;
+
+    #[test]
+    fn imports_openapi_3_operations() {
+        let import = import(OPENAPI).unwrap();
+
+        assert_eq!(import.name, "Users");
+        assert_eq!(
+            variable(&import, "baseUrl"),
+            Some("https://eu.example.com/v1")
+        );
+        assert_eq!(variable(&import, "baseUrl2"), Some("http://localhost:8080"));
+        assert_eq!(variable(&import, "id"), Some("42"));
+
+        let get = &import.requests[0];
+        assert_eq!(get.name, "Get user");
+        assert_eq!(get.folder.as_deref(), Some("users"));
+        assert_eq!(get.request.url, "{{baseUrl}}/users/{{id}}?fields=name");
+        assert_eq!(
+            get.request.headers,
+            vec![("X-Trace".to_string(), "abc".to_string())]
+        );
+
+        let put = request(&import, "PUT /users/{id}");
+        assert_eq!(put.method, Method::PUT);
+        let body: Value = serde_json::from_str(&put.body).unwrap();
+        // Read-only fields are left out and the recursive `manager` stops at one level
+        assert_eq!(
+            body,
+            json!({"name": "string", "email": "user@example.com", "manager": null})
+        );
+    }
+
+    #[test]
+    fn warns_about_operations_it_skips() {
+        let import = import(OPENAPI).unwrap();
+
+        assert!(import.requests.iter().all(|r| r.key != "TRACE /users/{id}"));
+        assert!(
+            import
+                .warnings
+                .iter()
+                .any(|w| w.starts_with("TRACE /users/{id}"))
+        );
+    }
+
+    #[test]
+    fn imports_swagger_2_forms() {
+        let import = import(
+            r#"{"swagger": "2.0", "info": {"title": "Pets"}, "host": "pets.test",
+                "basePath": "/api/", "schemes": ["http", "https"],
+                "paths": {"/pets": {"post": {
+                    "consumes": ["application/x-www-form-urlencoded"],
+                    "parameters": [
+                        {"name": "name", "in": "formData", "type": "string", "default": "Rex Jr"},
+                        {"name": "photo", "in": "formData", "type": "file"}
+                    ]}}}}"#,
+        )
+        .unwrap();
+
+        assert_eq!(variable(&import, "baseUrl"), Some("http://pets.test/api"));
+        assert_eq!(variable(&import, "baseUrl2"), Some("https://pets.test/api"));
+        let post = request(&import, "POST /pets");
+        assert_eq!(post.body, "name=Rex%20Jr");
+        assert_eq!(import.warnings.len(), 1);
+    }
+
+    #[test]
+    fn asks_for_a_base_url_when_the_spec_has_none() {
+        let import = import("openapi: 3.0\npaths: {}\n").unwrap();
+
+        assert_eq!(import.name, "Imported API");
+        assert_eq!(variable(&import, "baseUrl"), Some(""));
+        assert_eq!(import.warnings.len(), 1);
+    }
+
+    #[test]
+    fn rejects_other_documents() {
+        assert!(matches!(
+            import("openapi: '2.5'\n"),
+            Err(ImportError::Unsupported(_))
+        ));
+        assert!(matches!(import("title: x\n"), Err(ImportError::Parse(_))));
+    }
+
+    #[test]
+    fn identifies_a_spec_by_its_canonical_path() {
+        let dir = std::env::temp_dir();
+        assert_eq!(
+            collection_source(&dir.join("spec.yaml")),
+            collection_source(&dir.join(".").join("spec.yaml"))
+        );
+        assert!(collection_source(Path::new("spec.yaml")).starts_with(COLLECTION_SOURCE));
+    }
+}
@@ -0,0 +1,730 @@
+use super::{
+    FormPart, ImportError, build_multipart, percent_encode, set_default_header, str_field,
+    value_to_string,
+};
+use crate::models::{CollectionNode, CollectionTree, Method, Request, RequestOptions, Variable};
+use base64::{Engine, engine::general_purpose::STANDARD};
+use serde_json::{Value, json};
+
+const SCHEMA_V21: &str = "https://schema.getpostman.com/json/collection/v2.1.0/collection.json";
+
+/// Result of an import, with a note for every feature that did not survive the conversion.
+pub struct PostmanImport<T> {
+    pub value: T,
+    pub warnings: Vec<String>,
+}
+
+pub fn import_collection(json: &str) -> Result<PostmanImport<CollectionTree>, ImportError> {
+    let root: Value = serde_json::from_str(json)
+        .map_err(|e| ImportError::Parse(format!("invalid JSON: {}", e)))?;
+
+    let info = root.get("info").ok_or_else(|| {
+        ImportError::Parse("missing `info`, not a Postman collection".to_string())
+    })?;
+
+    let schema = str_field(info, "schema");
+    if !schema.is_empty() && !schema.contains("v2.1") && !schema.contains("v2.0") {
+        return Err(ImportError::Unsupported(format!(
+            "collection schema {}, export it as v2.1",
+            schema
+        )));
+    }
+
+    let mut warnings = Vec::new();
+    let name = non_empty(str_field(info, "name"), "Imported Collection");
+
+    if root.get("event").is_some_and(has_scripts) {
+        warnings.push(format!("{}: collection scripts were not imported", name));
+    }
+
+    let auth = root.get("auth").cloned();
+    let items = import_items(
+        root.get("item").and_then(Value::as_array),
+        &name,
+        auth.as_ref(),
+        &mut warnings,
+    );
+
+    Ok(PostmanImport {
+        value: CollectionTree {
+            name,
+            variables: import_variables(root.get("variable")),
+            items,
+        },
+        warnings,
+    })
+}
+
+pub fn import_environment(
+    json: &str,
+) -> Result<PostmanImport<(String, Vec<Variable>)>, ImportError> {
+    let root: Value = serde_json::from_str(json)
+        .map_err(|e| ImportError::Parse(format!("invalid JSON: {}", e)))?;
+
+    let values = root.get("values").ok_or_else(|| {
+        ImportError::Parse("missing `values`, not a Postman environment".to_string())
+    })?;
+
+    let name = non_empty(str_field(&root, "name"), "Imported Environment");
+    let variables = import_variables(Some(values));
+
+    Ok(PostmanImport {
+        value: (name, variables),
+        warnings: Vec::new(),
+    })
+}
+
+fn import_items(
+    items: Option<&Vec<Value>>,
+    path: &str,
+    inherited_auth: Option<&Value>,
+    warnings: &mut Vec<String>,
+) -> Vec<CollectionNode> {
+    let mut nodes = Vec::new();
+
+    for item in items.into_iter().flatten() {
+        let name = non_empty(str_field(item, "name"), "Untitled");
+        let item_path = format!("{} / {}", path, name);
+
+        if item.get("event").is_some_and(has_scripts) {
+            warnings.push(format!(
+                "{}: pre-request and test scripts were not imported",
+                item_path
+            ));
+        }
+
+        // Folders carry `item`, requests carry `request`
+        if let Some(children) = item.get("item").and_then(Value::as_array) {
+            let auth = item.get("auth").or(inherited_auth);
+            nodes.push(CollectionNode::Folder {
+                name,
+                children: import_items(Some(children), &item_path, auth, warnings),
+            });
+            continue;
+        }
+
+        let Some(request) = item.get("request") else {
+            continue;
+        };
+
+        if item
+            .get("response")
+            .and_then(Value::as_array)
+            .is_some_and(|responses| !responses.is_empty())
+        {
+            warnings.push(format!(
+                "{}: saved example responses were not imported",
+                item_path
+            ));
+        }
+
+        match import_request(request, item, inherited_auth, &item_path, warnings) {
+            Some(request) => nodes.push(CollectionNode::Request { name, request }),
+            None => continue,
+        }
+    }
+
+    nodes
+}
+
+fn import_request(
+    request: &Value,
+    item: &Value,
+    inherited_auth: Option<&Value>,
+    path: &str,
+    warnings: &mut Vec<String>,
+) -> Option<Request> {
+    // v2.1 allows a bare URL string as the whole request
+    if let Some(url) = request.as_str() {
+        return Some(Request {
+            url: url.to_string(),
+            ..Default::default()
+        });
+    }
+
+    let method_name = str_field(request, "method").to_uppercase();
+    let method = if method_name.is_empty() {
+        Method::GET
+    } else {
+        match method_name.parse::<Method>() {
+            Ok(method) => method,
+            Err(_) => {
+                warnings.push(format!(
+                    "{}: method {} is not supported, request skipped",
+                    path, method_name
+                ));
+                return None;
+            }
+        }
+    };
+
+    let mut url = import_url(request.get("url"));
+
+    let mut headers = Vec::new();
+    let mut disabled_headers = 0;
+    for header in request
+        .get("header")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        if header
+            .get("disabled")
+            .and_then(Value::as_bool)
+            .unwrap_or(false)
+        {
+            disabled_headers += 1;
+            continue;
+        }
+        let key = str_field(header, "key");
+        if !key.is_empty() {
+            headers.push((key, value_field(header, "value")));
+        }
+    }
+    if disabled_headers > 0 {
+        warnings.push(format!(
+            "{}: {} disabled headers were dropped",
+            path, disabled_headers
+        ));
+    }
+
+    let body = request
+        .get("body")
+        .map(|body| import_body(body, &mut headers, path, warnings))
+        .unwrap_or_default();
+
+    let auth = request.get("auth").or(inherited_auth);
+    if let Some(auth) = auth {
+        import_auth(auth, &mut headers, &mut url, path, warnings);
+    }
+
+    let mut options = RequestOptions::default();
+    if let Some(behavior) = item.get("protocolProfileBehavior") {
+        if let Some(follow) = behavior.get("followRedirects").and_then(Value::as_bool) {
+            options.follow_redirects = follow;
+        }
+        if let Some(strict) = behavior.get("strictSSL").and_then(Value::as_bool) {
+            options.insecure = !strict;
+        }
+    }
+
+    Some(Request {
+        method,
+        url,
+        headers,
+        body,
+        options,
+        ..Default::default()
+    })
+}
+
+fn import_url(url: Option<&Value>) -> String {
+    let Some(url) = url else {
+        return String::new();
+    };
+
+    if let Some(raw) = url.as_str() {
+        return raw.to_string();
+    }
+
+    let raw = str_field(url, "raw");
+    if !raw.is_empty() {
+        return raw;
+    }
+
+    // No `raw`: rebuild it from the parts
+    let join = |key: &str, separator: &str| match url.get(key) {
+        Some(Value::Array(parts)) => parts
+            .iter()
+            .filter_map(Value::as_str)
+            .collect::<Vec<_>>()
+            .join(separator),
+        Some(Value::String(part)) => part.clone(),
+        _ => String::new(),
+    };
+
+    let protocol = str_field(url, "protocol");
+    let mut result = if protocol.is_empty() {
+        join("host", ".")
+    } else {
+        format!("{}://{}", protocol, join("host", "."))
+    };
+
+    let port = value_field(url, "port");
+    if !port.is_empty() {
+        result.push_str(&format!(":{}", port));
+    }
+
+    let path = join("path", "/");
+    if !path.is_empty() {
+        result.push_str(&format!("/{}", path));
+    }
+
+    let query: Vec<String> = url
+        .get("query")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter(|q| !q.get("disabled").and_then(Value::as_bool).unwrap_or(false))
+        .map(|q| format!("{}={}", str_field(q, "key"), value_field(q, "value")))
+        .collect();
+    if !query.is_empty() {
+        result.push_str(&format!("?{}", query.join("&")));
+    }
+
+    result
+}
+
+fn import_body(
+    body: &Value,
+    headers: &mut Vec<(String, String)>,
+    path: &str,
+    warnings: &mut Vec<String>,
+) -> String {
+    let enabled = |field: &&Value| {
+        !field
+            .get("disabled")
+            .and_then(Value::as_bool)
+            .unwrap_or(false)
+    };
+
+    match str_field(body, "mode").as_str() {
+        "raw" => {
+            let language = body
+                .pointer("/options/raw/language")
+                .and_then(Value::as_str)
+                .unwrap_or("");
+            let content_type = match language {
+                "json" => Some("application/json"),
+                "xml" => Some("application/xml"),
+                "html" => Some("text/html"),
+                "javascript" => Some("application/javascript"),
+                _ => None,
+            };
+            if let Some(content_type) = content_type {
+                set_default_header(headers, "Content-Type", content_type);
+            }
+            str_field(body, "raw")
+        }
+        "urlencoded" => {
+            set_default_header(headers, "Content-Type", "application/x-www-form-urlencoded");
+            body.get("urlencoded")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter(enabled)
+                .map(|field| {
+                    format!(
+                        "{}={}",
+                        percent_encode(&str_field(field, "key")),
+                        percent_encode(&value_field(field, "value"))
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("&")
+        }
+        "formdata" => {
+            let mut parts = Vec::new();
+            for field in body
+                .get("formdata")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter(enabled)
+            {
+                let name = str_field(field, "key");
+                if str_field(field, "type") == "file" {
+                    warnings.push(format!("{}: file field `{}` was not imported", path, name));
+                    continue;
+                }
+                let content_type = str_field(field, "contentType");
+                parts.push(FormPart {
+                    name,
+                    content: value_field(field, "value"),
+                    filename: None,
+                    content_type: (!content_type.is_empty()).then_some(content_type),
+                });
+            }
+            set_default_header(
+                headers,
+                "Content-Type",
+                &format!("multipart/form-data; boundary={}", super::FORM_BOUNDARY),
+            );
+            build_multipart(&parts)
+        }
+        "graphql" => {
+            set_default_header(headers, "Content-Type", "application/json");
+            let graphql = body.get("graphql").cloned().unwrap_or(Value::Null);
+            let variables = graphql
+                .get("variables")
+                .and_then(Value::as_str)
+                .and_then(|v| serde_json::from_str::<Value>(v).ok())
+                .unwrap_or(Value::Null);
+            let payload = json!({
+                "query": str_field(&graphql, "query"),
+                "variables": variables,
+            });
+            serde_json::to_string_pretty(&payload).unwrap_or_default()
+        }
+        "file" => {
+            warnings.push(format!("{}: binary file body was not imported", path));
+            String::new()
+        }
+        _ => String::new(),
+    }
+}
+
+fn import_auth(
+    auth: &Value,
+    headers: &mut Vec<(String, String)>,
+    url: &mut String,
+    path: &str,
+    warnings: &mut Vec<String>,
+) {
+    let auth_type = str_field(auth, "type");
+
+    // v2.1 stores auth parameters as `[{key, value}]`, v2.0 as an object
+    let param = |name: &str| -> String {
+        match auth.get(&auth_type) {
+            Some(Value::Array(params)) => params
+                .iter()
+                .find(|p| str_field(p, "key") == name)
+                .map(|p| value_field(p, "value"))
+                .unwrap_or_default(),
+            Some(Value::Object(params)) => {
+                params.get(name).map(value_to_string).unwrap_or_default()
+            }
+            _ => String::new(),
+        }
+    };
+
+    match auth_type.as_str() {
+        "noauth" | "" => {}
+        "basic" => {
+            let credentials = format!("{}:{}", param("username"), param("password"));
+            if credentials.contains("{{") {
+                warnings.push(format!(
+                    "{}: basic auth built from variables was not imported, set the Authorization header manually",
+                    path
+                ));
+            } else {
+                set_default_header(
+                    headers,
+                    "Authorization",
+                    &format!("Basic {}", STANDARD.encode(credentials)),
+                );
+            }
+        }
+        "bearer" => {
+            set_default_header(
+                headers,
+                "Authorization",
+                &format!("Bearer {}", param("token")),
+            );
+        }
+        "apikey" => {
+            let key = non_empty(param("key"), "X-API-Key");
+            let value = param("value");
+            if param("in") == "query" {
+                let separator = if url.contains('?') { '&' } else { '?' };
+                url.push_str(&format!("{}{}={}", separator, key, value));
+            } else {
+                set_default_header(headers, &key, &value);
+            }
+        }
+        other => {
+            warnings.push(format!(
+                "{}: {} authentication is not supported",
+                path, other
+            ));
+        }
+    }
+}
+
+fn import_variables(variables: Option<&Value>) -> Vec<Variable> {
+    variables
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|v| {
+            let key = str_field(v, "key");
+            if key.is_empty() {
+                return None;
+            }
+            let enabled = v.get("enabled").and_then(Value::as_bool).unwrap_or(true)
+                && !v.get("disabled").and_then(Value::as_bool).unwrap_or(false);
+            Some(Variable {
+                key,
+                value: value_field(v, "value"),
+                enabled,
+                secret: str_field(v, "type") == "secret",
+            })
+        })
+        .collect()
+}
+
+/// Renders a collection as Postman Collection v2.1 JSON.
+pub fn export_collection(tree: &CollectionTree) -> String {
+    let collection = json!({
+        "info": {
+            "name": tree.name,
+            "schema": SCHEMA_V21,
+        },
+        "item": export_items(&tree.items),
+        "variable": tree.variables.iter().map(export_variable).collect::<Vec<_>>(),
+    });
+
+    serde_json::to_string_pretty(&collection).unwrap_or_default()
+}
+
+/// Renders an environment as a Postman environment file, which
+/// `import_environment` reads back.
+pub fn export_environment(name: &str, variables: &[Variable]) -> String {
+    let values = variables
+        .iter()
+        .map(|variable| {
+            json!({
+                "key": variable.key,
+                "value": if variable.secret { "" } else { variable.value.as_str() },
+                "type": if variable.secret { "secret" } else { "default" },
+                "enabled": variable.enabled,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let environment = json!({
+        "name": name,
+        "values": values,
+        "_postman_variable_scope": "environment",
+    });
+
+    serde_json::to_string_pretty(&environment).unwrap_or_default()
+}
+
+/// Secret values are never exported, only that the variable exists.
+fn export_variable(variable: &Variable) -> Value {
+    if variable.secret {
+        return json!({
+            "key": variable.key,
+            "value": "",
+            "type": "secret",
+            "disabled": !variable.enabled,
+        });
+    }
+    json!({
+        "key": variable.key,
+        "value": variable.value,
+        "disabled": !variable.enabled,
+    })
+}
+
+fn export_items(nodes: &[CollectionNode]) -> Vec<Value> {
+    nodes
+        .iter()
+        .map(|node| match node {
+            CollectionNode::Folder { name, children } => json!({
+                "name": name,
+                "item": export_items(children),
+            }),
+            CollectionNode::Request { name, request } => export_request(name, request),
+        })
+        .collect()
+}
+
+fn export_request(name: &str, request: &Request) -> Value {
+    let mut value = json!({
+        "name": name,
+        "request": {
+            "method": request.method.as_str(),
+            "header": request.headers.iter().map(|(k, v)| json!({
+                "key": k,
+                "value": v,
+            })).collect::<Vec<_>>(),
+            "url": {
+                "raw": request.url,
+            },
+        },
+        "protocolProfileBehavior": {
+            "followRedirects": request.options.follow_redirects,
+            "strictSSL": !request.options.insecure,
+        },
+        "response": [],
+    });
+
+    if !request.body.is_empty() {
+        let is_json = request.headers.iter().any(|(k, v)| {
+            k.eq_ignore_ascii_case("content-type") && v.to_lowercase().contains("json")
+        });
+
+        let mut body = json!({
+            "mode": "raw",
+            "raw": request.body,
+        });
+        if is_json {
+            body["options"] = json!({ "raw": { "language": "json" } });
+        }
+        value["request"]["body"] = body;
+    }
+
+    value
+}
+
+fn has_scripts(events: &Value) -> bool {
+    events.as_array().is_some_and(|events| {
+        events.iter().any(|event| {
+            event
+                .pointer("/script/exec")
+                .and_then(Value::as_array)
+                .is_some_and(|lines| {
+                    lines
+                        .iter()
+                        .any(|l| !l.as_str().unwrap_or("").trim().is_empty())
+                })
+        })
+    })
+}
+
+// Postman allows numbers and booleans where strings are expected
+fn value_field(value: &Value, key: &str) -> String {
+    value.get(key).map(value_to_string).unwrap_or_default()
+}
+
+fn non_empty(value: String, fallback: &str) -> String {
+    if value.trim().is_empty() {
+        fallback.to_string()
+    } else {
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn variable(key: &str, value: &str, secret: bool) -> Variable {
+        Variable {
+            key: key.to_string(),
+            value: value.to_string(),
+            enabled: true,
+            secret,
+        }
+    }
+
+    #[test]
+    fn round_trips_collections() {
+        let tree = CollectionTree {
+            name: "Users".to_string(),
+            variables: vec![variable("host", "http://a.test", false)],
+            items: vec![CollectionNode::Folder {
+                name: "Admin".to_string(),
+                children: vec![CollectionNode::Request {
+                    name: "Create".to_string(),
+                    request: Request {
+                        method: Method::POST,
+                        url: "{{host}}/users".to_string(),
+                        headers: vec![("Content-Type".to_string(), "application/json".to_string())],
+                        body: "{\"name\": \"ada\"}".to_string(),
+                        options: RequestOptions {
+                            insecure: true,
+                            follow_redirects: false,
+                            compressed: false,
+                        },
+                        ..Default::default()
+                    },
+                }],
+            }],
+        };
+
+        let import = import_collection(&export_collection(&tree)).unwrap();
+
+        assert_eq!(import.value, tree);
+        assert!(import.warnings.is_empty());
+    }
+
+    #[test]
+    fn exports_environments_without_secret_values() {
+        let variables = vec![
+            variable("host", "http://a.test", false),
+            variable("token", "s3cr3t", true),
+        ];
+
+        let json = export_environment("Staging", &variables);
+        assert!(!json.contains("s3cr3t"));
+
+        let (name, imported) = import_environment(&json).unwrap().value;
+        assert_eq!(name, "Staging");
+        assert_eq!(
+            imported,
+            vec![variables[0].clone(), variable("token", "", true)]
+        );
+    }
+
+    #[test]
+    fn rebuilds_urls_and_applies_auth() {
+        let import = import_collection(
+            r#"{"info": {"name": "c"}, "auth": {"type": "bearer", "bearer": [{"key": "token", "value": "{{token}}"}]},
+                "item": [
+                    {"name": "parts", "request": {"method": "get", "url": {
+                        "protocol": "https", "host": ["api", "example", "com"], "port": 8443,
+                        "path": ["v1", "users"],
+                        "query": [{"key": "page", "value": 2}, {"key": "off", "value": "1", "disabled": true}]}}},
+                    {"name": "key", "request": {"url": "http://a.test?x=1",
+                        "auth": {"type": "apikey", "apikey": {"key": "api_key", "value": "k", "in": "query"}}}}
+                ]}"#,
+        )
+        .unwrap();
+
+        let requests: Vec<&Request> = import
+            .value
+            .items
+            .iter()
+            .filter_map(|node| match node {
+                CollectionNode::Request { request, .. } => Some(request),
+                CollectionNode::Folder { .. } => None,
+            })
+            .collect();
+        assert_eq!(
+            requests[0].url,
+            "https://api.example.com:8443/v1/users?page=2"
+        );
+        assert_eq!(
+            requests[0].headers,
+            vec![("Authorization".to_string(), "Bearer {{token}}".to_string())]
+        );
+        assert_eq!(requests[1].url, "http://a.test?x=1&api_key=k");
+    }
+
+    #[test]
+    fn warns_about_what_it_drops() {
+        let import = import_collection(
+            r#"{"info": {"name": "c"}, "item": [
+                {"name": "r", "event": [{"script": {"exec": ["pm.test()"]}}],
+                 "request": {"method": "GET", "url": "http://a.test",
+                    "header": [{"key": "X-Off", "value": "1", "disabled": true}]}},
+                {"name": "h", "request": {"method": "HEAD", "url": "http://a.test"}}
+            ]}"#,
+        )
+        .unwrap();
+
+        assert_eq!(import.value.items.len(), 1);
+        assert_eq!(import.warnings.len(), 3);
+    }
+
+    #[test]
+    fn rejects_other_formats() {
+        assert!(matches!(
+            import_collection(
+                r#"{"info": {"schema": "https://schema.getpostman.com/json/collection/v1.0.0/"}}"#
+            ),
+            Err(ImportError::Unsupported(_))
+        ));
+        assert!(matches!(
+            import_collection("{}"),
+            Err(ImportError::Parse(_))
+        ));
+        assert!(matches!(
+            import_environment("[]"),
+            Err(ImportError::Parse(_))
+        ));
+    }
+}
@@ -0,0 +1,230 @@
+use crate::api::ApiError;
+use crate::runner::{RunReport, RunStep};
+use serde_json::{Value, json};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReportFormat {
+    JUnit,
+    Json,
+}
+
+impl ReportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ReportFormat::JUnit => "xml",
+            ReportFormat::Json => "json",
+        }
+    }
+
+    pub fn render(&self, report: &RunReport) -> String {
+        match self {
+            ReportFormat::JUnit => to_junit(report),
+            ReportFormat::Json => to_json(report),
+        }
+    }
+}
+
+/// JUnit XML with one `<testsuite>` per iteration and one `<testcase>` per
+/// request, the layout CI servers expect.
+pub fn to_junit(report: &RunReport) -> String {
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str(&format!(
+        "<testsuites name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"{}\" time=\"{:.3}\">\n",
+        escape(&report.name),
+        report.steps.len(),
+        report.steps.iter().filter(|s| is_failure(s)).count(),
+        report.steps.iter().filter(|s| s.response.is_err()).count(),
+        report.duration_ms / 1000.0,
+    ));
+
+    let iterations = report.steps.last().map_or(0, |s| s.iteration);
+    for iteration in 1..=iterations {
+        let steps: Vec<&RunStep> = report
+            .steps
+            .iter()
+            .filter(|s| s.iteration == iteration)
+            .collect();
+        let name = if iterations > 1 {
+            format!("{} #{}", report.name, iteration)
+        } else {
+            report.name.clone()
+        };
+        let time = steps
+            .iter()
+            .filter_map(|s| s.time_ms())
+            .fold(0.0, |a, b| a + b);
+
+        xml.push_str(&format!(
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"{}\" time=\"{:.3}\" timestamp=\"{}\">\n",
+            escape(&name),
+            steps.len(),
+            steps.iter().filter(|s| is_failure(s)).count(),
+            steps.iter().filter(|s| s.response.is_err()).count(),
+            time / 1000.0,
+            report.started.format("%Y-%m-%dT%H:%M:%S"),
+        ));
+        for step in steps {
+            testcase(&mut xml, report, step);
+        }
+        xml.push_str("  </testsuite>\n");
+    }
+
+    xml.push_str("</testsuites>\n");
+    xml
+}
+
+fn testcase(xml: &mut String, report: &RunReport, step: &RunStep) {
+    // Data rows make otherwise identical names unique
+    let row = match (&report.data, step.row) {
+        (Some(data), Some(row)) => format!(" [{}]", data.describe(row)),
+        _ => String::new(),
+    };
+    xml.push_str(&format!(
+        "    <testcase name=\"{} {}{}\" classname=\"{}\" time=\"{:.3}\"",
+        escape(step.request.method.as_str()),
+        escape(&step.name),
+        escape(&row),
+        escape(&report.name),
+        step.time_ms().unwrap_or(0.0) / 1000.0,
+    ));
+
+    let mut body = String::new();
+    match &step.response {
+        Err(ApiError::RequestFailed(msg)) => {
+            body.push_str(&format!(
+                "      <error message=\"{}\">{}</error>\n",
+                escape(msg),
+                escape(&step.request.url)
+            ));
+        }
+        Ok(response) if !step.passed() => {
+            let failed: Vec<String> = step
+                .assertions
+                .iter()
+                .filter(|a| !a.passed)
+                .map(|a| format!("{}: {}", a.description, a.message))
+                .collect();
+            let message = match failed.first() {
+                Some(first) => first.clone(),
+                None => format!("status {}", response.status_code),
+            };
+            body.push_str(&format!(
+                "      <failure message=\"{}\">{}</failure>\n",
+                escape(&message),
+                escape(&failed.join("\n"))
+            ));
+        }
+        Ok(_) => {}
+    }
+    // Script console output, shown next to the test by CI servers
+    if !step.logs.is_empty() {
+        body.push_str(&format!(
+            "      <system-out>{}</system-out>\n",
+            escape(&step.logs.join("\n"))
+        ));
+    }
+
+    if body.is_empty() {
+        xml.push_str("/>\n");
+    } else {
+        xml.push_str(">\n");
+        xml.push_str(&body);
+        xml.push_str("    </testcase>\n");
+    }
+}
+
+/// The whole run as JSON: totals, then every step with its status, timing and
+/// assertion results. Response bodies are left out.
+pub fn to_json(report: &RunReport) -> String {
+    serde_json::to_string_pretty(&to_value(report)).unwrap_or_default()
+}
+
+pub fn to_value(report: &RunReport) -> Value {
+    json!({
+        "name": report.name,
+        "started": report.started.to_rfc3339(),
+        "duration_ms": report.duration_ms,
+        "passed": report.passed(),
+        "failed": report.failed(),
+        "stopped": report.stopped,
+        "steps": report
+            .steps
+            .iter()
+            .map(|step| step_value(report, step))
+            .collect::<Vec<_>>(),
+    })
+}
+
+fn step_value(report: &RunReport, step: &RunStep) -> Value {
+    let mut value = json!({
+        "iteration": step.iteration,
+        "name": step.name,
+        "ok": step.passed(),
+        "request": {
+            "method": step.request.method.as_str(),
+            "url": step.request.url,
+        },
+    });
+    if let (Some(data), Some(row)) = (&report.data, step.row) {
+        value["row"] = json!(row + 1);
+        value["data"] = Value::Object(
+            data.values(row)
+                .map(|(column, value)| (column.to_string(), json!(value)))
+                .collect(),
+        );
+    }
+    if !step.assertions.is_empty() {
+        value["assertions"] = json!(step.assertions);
+    }
+    if !step.extracted.is_empty() {
+        value["captured"] = Value::Object(
+            step.extracted
+                .iter()
+                .map(|e| {
+                    let captured = match &e.value {
+                        Ok(value) => json!({ "value": value }),
+                        Err(reason) => json!({ "error": reason }),
+                    };
+                    (e.variable.clone(), captured)
+                })
+                .collect(),
+        );
+    }
+    if !step.logs.is_empty() {
+        value["console"] = json!(step.logs);
+    }
+    match &step.response {
+        Ok(response) => {
+            value["response"] = json!({
+                "status": response.status_code,
+                "status_text": response.status,
+                "time_ms": step.time_ms(),
+                "size": response.size,
+            });
+        }
+        Err(ApiError::RequestFailed(msg)) => value["error"] = json!(msg),
+    }
+    value
+}
+
+/// Failed, but did get a response; send errors count as JUnit errors instead.
+fn is_failure(step: &RunStep) -> bool {
+    step.response.is_ok() && !step.passed()
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            // Control characters other than tab and newlines are not allowed in XML 1.0
+            c if c.is_control() && !matches!(c, '\t' | '\n' | '\r') => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,135 @@
+use serde_json::Value;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Step {
+    Key(String),
+    Index(i64),
+    Wildcard,
+    /// `..`, applies the next step at every depth
+    Descend,
+}
+
+/// Selects the values a JSONPath points at. Supports `$`, `.key`, `['key']`,
+/// `[n]` (negative counts from the end), `*` / `[*]` and `..` descent.
+pub fn select<'a>(root: &'a Value, path: &str) -> Result<Vec<&'a Value>, String> {
+    let steps = parse(path)?;
+    let mut current = vec![root];
+
+    let mut steps = steps.iter();
+    while let Some(step) = steps.next() {
+        current = match step {
+            Step::Descend => {
+                let mut all = Vec::new();
+                for value in current {
+                    descendants(value, &mut all);
+                }
+                match steps.next() {
+                    Some(next) => all.into_iter().flat_map(|v| apply(next, v)).collect(),
+                    None => return Err("`..` must be followed by a key".to_string()),
+                }
+            }
+            step => current.into_iter().flat_map(|v| apply(step, v)).collect(),
+        };
+    }
+
+    Ok(current)
+}
+
+fn apply<'a>(step: &Step, value: &'a Value) -> Vec<&'a Value> {
+    match (step, value) {
+        (Step::Key(key), Value::Object(map)) => map.get(key).into_iter().collect(),
+        (Step::Index(index), Value::Array(items)) => {
+            let index = if *index < 0 {
+                items.len() as i64 + index
+            } else {
+                *index
+            };
+            usize::try_from(index)
+                .ok()
+                .and_then(|i| items.get(i))
+                .into_iter()
+                .collect()
+        }
+        (Step::Wildcard, Value::Object(map)) => map.values().collect(),
+        (Step::Wildcard, Value::Array(items)) => items.iter().collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn descendants<'a>(value: &'a Value, out: &mut Vec<&'a Value>) {
+    out.push(value);
+    match value {
+        Value::Object(map) => map.values().for_each(|v| descendants(v, out)),
+        Value::Array(items) => items.iter().for_each(|v| descendants(v, out)),
+        _ => {}
+    }
+}
+
+fn parse(path: &str) -> Result<Vec<Step>, String> {
+    let path = path.trim();
+    let rest = path
+        .strip_prefix('$')
+        .ok_or_else(|| format!("`{}` must start with `$`", path))?;
+    let chars: Vec<char> = rest.chars().collect();
+    let mut steps = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        match chars[i] {
+            '.' if chars.get(i + 1) == Some(&'.') => {
+                steps.push(Step::Descend);
+                i += 2;
+                // `$..key` has no dot before the key
+                if chars.get(i).is_some_and(|c| *c != '[') {
+                    i = parse_name(&chars, i, &mut steps)?;
+                }
+            }
+            '.' => i = parse_name(&chars, i + 1, &mut steps)?,
+            '[' => {
+                let close = chars[i..]
+                    .iter()
+                    .position(|c| *c == ']')
+                    .map(|p| i + p)
+                    .ok_or_else(|| format!("unclosed `[` in `{}`", path))?;
+                let inner: String = chars[i + 1..close].iter().collect();
+                let inner = inner.trim();
+
+                let step = if inner == "*" {
+                    Step::Wildcard
+                } else if let Some(quoted) = inner
+                    .strip_prefix('\'')
+                    .and_then(|s| s.strip_suffix('\''))
+                    .or_else(|| inner.strip_prefix('"').and_then(|s| s.strip_suffix('"')))
+                {
+                    Step::Key(quoted.to_string())
+                } else {
+                    Step::Index(
+                        inner
+                            .parse()
+                            .map_err(|_| format!("unsupported selector `[{}]`", inner))?,
+                    )
+                };
+                steps.push(step);
+                i = close + 1;
+            }
+            c => return Err(format!("unexpected `{}` in `{}`", c, path)),
+        }
+    }
+
+    Ok(steps)
+}
+
+fn parse_name(chars: &[char], start: usize, steps: &mut Vec<Step>) -> Result<usize, String> {
+    let end = chars[start..]
+        .iter()
+        .position(|c| *c == '.' || *c == '[')
+        .map_or(chars.len(), |p| start + p);
+    let name: String = chars[start..end].iter().collect();
+
+    match name.as_str() {
+        "" => return Err("empty key in path".to_string()),
+        "*" => steps.push(Step::Wildcard),
+        _ => steps.push(Step::Key(name)),
+    }
+    Ok(end)
+}
@@ -1,14 +1,37 @@
 use adw::Application;
 use adw::prelude::*;
+use clap::Parser;
 
 mod api;
+mod assertions;
+mod cli;
+mod codegen;
 mod config;
+mod contract;
 mod database;
+mod diff;
+mod extractors;
+mod formats;
+mod jsonpath;
 mod models;
+mod redaction;
+mod runner;
+mod schema;
+mod scripting;
+mod secrets;
+mod snapshot;
 mod state;
 mod ui;
+mod variables;
 
 fn main() {
+    // Subcommands run headless, without initializing GTK
+    if cli::handles(std::env::args())
+        && let Some(command) = cli::Cli::parse().command
+    {
+        std::process::exit(cli::run(command));
+    }
+
     let app = Application::builder()
         .application_id("com.github.lopesmarcello.dispatch")
         .build();
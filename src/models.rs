@@ -1,7 +1,9 @@
 use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
 
-#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
 pub enum Method {
+    #[default]
     GET,
     POST,
     PUT,
@@ -61,3 +63,290 @@ impl std::str::FromStr for Method {
         }
     }
 }
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RequestOptions {
+    pub insecure: bool,
+    pub follow_redirects: bool,
+    pub compressed: bool,
+}
+
+impl Default for RequestOptions {
+    fn default() -> Self {
+        RequestOptions {
+            insecure: false,
+            follow_redirects: true,
+            compressed: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Request {
+    pub method: Method,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+    pub options: RequestOptions,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contract: Option<ContractLink>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub assertions: Vec<Assertion>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extractors: Vec<Extractor>,
+    #[serde(default, skip_serializing_if = "Scripts::is_empty")]
+    pub scripts: Scripts,
+}
+
+/// The document describing what responses to a request should look like.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ContractLink {
+    /// An operation in an OpenAPI or Swagger spec, keyed like `GET /pets/{id}`
+    OpenApi { spec: PathBuf, operation: String },
+    /// A JSON Schema the response body must satisfy
+    JsonSchema { path: PathBuf },
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum AssertionKind {
+    #[default]
+    StatusEquals,
+    StatusInRange,
+    HeaderExists,
+    HeaderMatches,
+    JsonPathEquals,
+    JsonPathContains,
+    MatchesSchema,
+    TimeBelow,
+}
+
+impl AssertionKind {
+    pub const ALL: [AssertionKind; 8] = [
+        AssertionKind::StatusEquals,
+        AssertionKind::StatusInRange,
+        AssertionKind::HeaderExists,
+        AssertionKind::HeaderMatches,
+        AssertionKind::JsonPathEquals,
+        AssertionKind::JsonPathContains,
+        AssertionKind::MatchesSchema,
+        AssertionKind::TimeBelow,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            AssertionKind::StatusEquals => "Status equals",
+            AssertionKind::StatusInRange => "Status in range",
+            AssertionKind::HeaderExists => "Header exists",
+            AssertionKind::HeaderMatches => "Header matches",
+            AssertionKind::JsonPathEquals => "JSONPath equals",
+            AssertionKind::JsonPathContains => "JSONPath contains",
+            AssertionKind::MatchesSchema => "Body matches schema",
+            AssertionKind::TimeBelow => "Time below (ms)",
+        }
+    }
+
+    pub fn from_index(index: u32) -> Self {
+        Self::ALL.get(index as usize).copied().unwrap_or_default()
+    }
+
+    pub fn to_index(self) -> u32 {
+        Self::ALL.iter().position(|k| *k == self).unwrap_or(0) as u32
+    }
+}
+
+/// A check run against every response to a request. `target` names what is
+/// checked (a header, a JSONPath, a schema file) and `expected` the value it is
+/// compared with; each kind uses the fields it needs.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Assertion {
+    pub kind: AssertionKind,
+    #[serde(default)]
+    pub target: String,
+    #[serde(default)]
+    pub expected: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AssertionResult {
+    pub description: String,
+    pub passed: bool,
+    /// Why it failed, or the actual value when it passed
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum ExtractorSource {
+    #[default]
+    JsonPath,
+    Regex,
+    Header,
+    Cookie,
+}
+
+impl ExtractorSource {
+    pub const ALL: [ExtractorSource; 4] = [
+        ExtractorSource::JsonPath,
+        ExtractorSource::Regex,
+        ExtractorSource::Header,
+        ExtractorSource::Cookie,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ExtractorSource::JsonPath => "JSONPath",
+            ExtractorSource::Regex => "Body regex",
+            ExtractorSource::Header => "Header",
+            ExtractorSource::Cookie => "Cookie",
+        }
+    }
+
+    pub fn from_index(index: u32) -> Self {
+        Self::ALL.get(index as usize).copied().unwrap_or_default()
+    }
+
+    pub fn to_index(self) -> u32 {
+        Self::ALL.iter().position(|s| *s == self).unwrap_or(0) as u32
+    }
+}
+
+/// Captures a value from each response into a variable, for chaining
+/// requests. `expression` is a JSONPath, a regex, or a header or cookie name.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Extractor {
+    pub source: ExtractorSource,
+    #[serde(default)]
+    pub expression: String,
+    pub variable: String,
+}
+
+/// What an extractor captured from one response.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Extraction {
+    pub variable: String,
+    /// The captured value, or why nothing was captured
+    pub value: Result<String, String>,
+}
+
+/// Rhai scripts run around a request: `pre_request` can change it before it is
+/// sent, `post_response` inspects the response, sets variables and adds tests.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Scripts {
+    #[serde(default)]
+    pub pre_request: String,
+    #[serde(default)]
+    pub post_response: String,
+}
+
+impl Scripts {
+    pub fn is_empty(&self) -> bool {
+        self.pre_request.trim().is_empty() && self.post_response.trim().is_empty()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Variable {
+    pub key: String,
+    pub value: String,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Stored encrypted, masked in the UI and kept out of history and exports
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub secret: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Environment {
+    pub id: i64,
+    pub name: String,
+    pub variables: Vec<Variable>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Collection {
+    pub id: i64,
+    pub name: String,
+    pub variables: Vec<Variable>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ItemKind {
+    Folder,
+    Request,
+}
+
+impl ItemKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ItemKind::Folder => "folder",
+            ItemKind::Request => "request",
+        }
+    }
+}
+
+impl std::str::FromStr for ItemKind {
+    type Err = ();
+    fn from_str(s: &str) -> Result<Self, ()> {
+        match s {
+            "folder" => Ok(ItemKind::Folder),
+            "request" => Ok(ItemKind::Request),
+            _ => Err(()),
+        }
+    }
+}
+
+/// A folder or saved request stored in a collection.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CollectionItem {
+    pub id: i64,
+    pub collection_id: i64,
+    pub parent_id: Option<i64>,
+    pub kind: ItemKind,
+    pub name: String,
+    pub position: i64,
+    pub request: Request,
+}
+
+/// A whole collection as a tree, the shape importers produce and exporters consume.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CollectionTree {
+    pub name: String,
+    pub variables: Vec<Variable>,
+    pub items: Vec<CollectionNode>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CollectionNode {
+    Folder {
+        name: String,
+        children: Vec<CollectionNode>,
+    },
+    Request {
+        name: String,
+        request: Request,
+    },
+}
+
+/// A request generated from an external source such as an API spec. `key`
+/// identifies it across re-imports so edited requests can be preserved.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourcedRequest {
+    pub key: String,
+    pub folder: Option<String>,
+    pub name: String,
+    pub request: Request,
+}
+
+/// What a re-import changed in an existing collection.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SyncSummary {
+    pub collection_id: i64,
+    pub added: usize,
+    pub updated: usize,
+    pub removed: usize,
+    /// Names of requests that differ from the source but were edited locally
+    pub kept: Vec<String>,
+}
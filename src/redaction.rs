@@ -0,0 +1,305 @@
+use crate::database::HistoryItem;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Written in place of a redacted value.
+pub const REDACTED: &str = "[REDACTED]";
+
+/// What is masked in history before it is saved or exported: values of
+/// headers and JSON keys with these names, compared case-insensitively, and
+/// any text matching one of the patterns.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RedactionPolicy {
+    pub headers: Vec<String>,
+    pub json_keys: Vec<String>,
+    pub patterns: Vec<String>,
+    /// Keep the full exchanges in memory until the app closes, so entries
+    /// from this session still open unredacted
+    pub keep_session: bool,
+}
+
+impl Default for RedactionPolicy {
+    fn default() -> Self {
+        let strings = |items: &[&str]| items.iter().map(|s| s.to_string()).collect();
+        RedactionPolicy {
+            headers: strings(&[
+                "Authorization",
+                "Proxy-Authorization",
+                "Cookie",
+                "Set-Cookie",
+                "X-Api-Key",
+                "X-Auth-Token",
+            ]),
+            json_keys: strings(&[
+                "password",
+                "secret",
+                "client_secret",
+                "token",
+                "access_token",
+                "refresh_token",
+                "id_token",
+                "api_key",
+            ]),
+            // JSON Web Tokens wherever they appear
+            patterns: strings(&[r"eyJ[A-Za-z0-9_-]+\.[A-Za-z0-9_-]+\.[A-Za-z0-9_-]*"]),
+            keep_session: false,
+        }
+    }
+}
+
+impl RedactionPolicy {
+    /// Checks that every pattern is a valid regular expression.
+    pub fn validate(&self) -> Result<(), String> {
+        for pattern in &self.patterns {
+            Regex::new(pattern).map_err(|e| format!("invalid pattern `{}`: {}", pattern, e))?;
+        }
+        Ok(())
+    }
+}
+
+/// A policy ready to apply.
+pub struct Redactor {
+    headers: Vec<String>,
+    json_keys: Vec<String>,
+    patterns: Vec<Regex>,
+}
+
+impl Redactor {
+    /// Invalid patterns are skipped; `RedactionPolicy::validate` reports them.
+    pub fn new(policy: &RedactionPolicy) -> Redactor {
+        let lowercase = |names: &[String]| names.iter().map(|n| n.trim().to_lowercase()).collect();
+        Redactor {
+            headers: lowercase(&policy.headers),
+            json_keys: lowercase(&policy.json_keys),
+            patterns: policy
+                .patterns
+                .iter()
+                .filter_map(|p| Regex::new(p).ok())
+                .collect(),
+        }
+    }
+
+    /// Text with every pattern match replaced.
+    pub fn text(&self, text: &str) -> String {
+        let mut text = text.to_string();
+        for pattern in &self.patterns {
+            if pattern.is_match(&text) {
+                text = pattern.replace_all(&text, REDACTED).into_owned();
+            }
+        }
+        text
+    }
+
+    pub fn header_pairs(&self, headers: &[(String, String)]) -> Vec<(String, String)> {
+        headers
+            .iter()
+            .map(|(name, value)| (name.clone(), self.header_value(name, value)))
+            .collect()
+    }
+
+    /// Headers as raw `Name: value` lines, the way responses are stored.
+    pub fn header_text(&self, headers: &str) -> String {
+        headers
+            .split('\n')
+            .map(|line| match line.split_once(':') {
+                Some((name, value)) => format!("{}:{}", name, self.header_value(name, value)),
+                None => line.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Masks the query parameters named like a sensitive JSON key.
+    pub fn url(&self, url: &str) -> String {
+        let (url, fragment) = match url.split_once('#') {
+            Some((url, fragment)) => (url, Some(fragment)),
+            None => (url, None),
+        };
+        let redacted = match url.split_once('?') {
+            Some((base, query)) => {
+                let query: Vec<String> = query
+                    .split('&')
+                    .map(|pair| match pair.split_once('=') {
+                        Some((name, _)) if self.is_json_key(name) => {
+                            format!("{}={}", name, REDACTED)
+                        }
+                        _ => pair.to_string(),
+                    })
+                    .collect();
+                format!("{}?{}", base, query.join("&"))
+            }
+            None => url.to_string(),
+        };
+        let redacted = match fragment {
+            Some(fragment) => format!("{}#{}", redacted, fragment),
+            None => redacted,
+        };
+        self.text(&redacted)
+    }
+
+    /// Masks sensitive keys anywhere in a JSON body, then pattern matches.
+    /// A body that is not JSON only gets the patterns.
+    pub fn body(&self, body: &str) -> String {
+        let mut json = match serde_json::from_str::<Value>(body) {
+            Ok(json) => json,
+            Err(_) => return self.text(body),
+        };
+        if !self.redact_json(&mut json) {
+            return self.text(body);
+        }
+        // Keep pretty-printed bodies readable
+        let body = if body.contains('\n') {
+            serde_json::to_string_pretty(&json)
+        } else {
+            serde_json::to_string(&json)
+        }
+        .unwrap_or_else(|_| body.to_string());
+        self.text(&body)
+    }
+
+    pub fn history(&self, item: &HistoryItem) -> HistoryItem {
+        let request_headers: Vec<(String, String)> =
+            serde_json::from_str(&item.request_headers).unwrap_or_default();
+        HistoryItem {
+            url: self.url(&item.url),
+            request_body: self.body(&item.request_body),
+            request_headers: serde_json::to_string(&self.header_pairs(&request_headers))
+                .unwrap_or_else(|_| item.request_headers.clone()),
+            response_body: self.body(&item.response_body),
+            response_headers: self.header_text(&item.response_headers),
+            ..item.clone()
+        }
+    }
+
+    fn header_value(&self, name: &str, value: &str) -> String {
+        if self.headers.contains(&name.trim().to_lowercase()) {
+            // Keeps the space after the colon of a raw header line
+            let indent = &value[..value.len() - value.trim_start().len()];
+            format!("{}{}", indent, REDACTED)
+        } else {
+            self.text(value)
+        }
+    }
+
+    fn is_json_key(&self, key: &str) -> bool {
+        self.json_keys.contains(&key.to_lowercase())
+    }
+
+    /// Returns whether anything was replaced.
+    fn redact_json(&self, value: &mut Value) -> bool {
+        let mut changed = false;
+        match value {
+            Value::Object(map) => {
+                for (key, value) in map.iter_mut() {
+                    if !self.is_json_key(key) {
+                        changed |= self.redact_json(value);
+                    } else if !value.is_null() && value.as_str() != Some(REDACTED) {
+                        *value = Value::String(REDACTED.to_string());
+                        changed = true;
+                    }
+                }
+            }
+            Value::Array(items) => {
+                for item in items {
+                    changed |= self.redact_json(item);
+                }
+            }
+            _ => {}
+        }
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::formats::har;
+
+    fn redactor() -> Redactor {
+        Redactor::new(&RedactionPolicy::default())
+    }
+
+    #[test]
+    fn masks_json_keys_keeping_key_order() {
+        let body = r#"{"user":"ada","password":"hunter2","nested":{"token":"t","id":1}}"#;
+        assert_eq!(
+            redactor().body(body),
+            r#"{"user":"ada","password":"[REDACTED]","nested":{"token":"[REDACTED]","id":1}}"#
+        );
+    }
+
+    #[test]
+    fn keeps_pretty_printed_bodies_pretty() {
+        let body = "{\n  \"zeta\": 1,\n  \"secret\": \"s\"\n}";
+        assert_eq!(
+            redactor().body(body),
+            "{\n  \"zeta\": 1,\n  \"secret\": \"[REDACTED]\"\n}"
+        );
+    }
+
+    #[test]
+    fn leaves_bodies_without_matches_as_they_are() {
+        let body = "{ \"b\" : 2,   \"a\" : [1,2] }";
+        assert_eq!(redactor().body(body), body);
+        assert_eq!(redactor().body("plain text"), "plain text");
+    }
+
+    #[test]
+    fn masks_patterns_in_text_bodies() {
+        let jwt = "eyJhbGciOiJIUzI1NiJ9.eyJzdWIiOiIxIn0.sig";
+        assert_eq!(
+            redactor().body(&format!("bearer {} end", jwt)),
+            "bearer [REDACTED] end"
+        );
+    }
+
+    #[test]
+    fn masks_headers_and_query_parameters() {
+        let redactor = redactor();
+        assert_eq!(
+            redactor.header_text("authorization: Basic abc\ncontent-type: text/plain"),
+            "authorization: [REDACTED]\ncontent-type: text/plain"
+        );
+        assert_eq!(
+            redactor.url("https://a.test/?api_key=k&page=2#top"),
+            "https://a.test/?api_key=[REDACTED]&page=2#top"
+        );
+    }
+
+    #[test]
+    fn redacts_imported_har_entries() {
+        let json = r#"{"log": {"version": "1.2", "creator": {"name": "t", "version": "1"},
+            "entries": [{"startedDateTime": "2024-05-01T12:30:00Z", "time": 5,
+                "request": {"method": "POST", "url": "http://a.test/?token=abc",
+                    "headers": [{"name": "Authorization", "value": "Bearer abc"}],
+                    "postData": {"mimeType": "application/json",
+                        "text": "{\"name\":\"ada\",\"password\":\"pw\"}"}},
+                "response": {"status": 200, "statusText": "OK",
+                    "headers": [{"name": "Set-Cookie", "value": "session=1"}],
+                    "content": {"size": 2, "text": "{}"}},
+                "timings": {}}]}}"#;
+        let imported = &har::import(json).unwrap().items[0];
+        let item = redactor().history(imported);
+
+        assert_eq!(item.url, "http://a.test/?token=[REDACTED]");
+        assert_eq!(item.request_headers, "[[\"Authorization\",\"[REDACTED]\"]]");
+        assert_eq!(
+            item.request_body,
+            r#"{"name":"ada","password":"[REDACTED]"}"#
+        );
+        assert_eq!(item.response_headers, "set-cookie: [REDACTED]\n");
+        assert_eq!(item.timestamp, imported.timestamp);
+    }
+
+    #[test]
+    fn rejects_invalid_patterns() {
+        let policy = RedactionPolicy {
+            patterns: vec!["(".to_string()],
+            ..Default::default()
+        };
+        assert!(policy.validate().is_err());
+        assert!(RedactionPolicy::default().validate().is_ok());
+    }
+}
@@ -0,0 +1,579 @@
+use crate::api::{self, ApiError, ApiResponse};
+use crate::assertions;
+use crate::database::{Database, HistoryItem};
+use crate::extractors;
+use crate::formats::{data::DataSet, http};
+use crate::models::{AssertionResult, Extraction, ItemKind, Request, Variable};
+use crate::redaction::Redactor;
+use crate::scripting::{self, ScriptOutput};
+use crate::secrets;
+use crate::snapshot::{self, Masking, Snapshot, SnapshotStore};
+use crate::variables::{self, Scope};
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use std::{fs, path::Path, thread};
+
+/// Requests to run together with the variables they were defined with,
+/// from a saved collection or a `.http` file.
+#[derive(Debug, Clone, Default)]
+pub struct Target {
+    pub name: String,
+    pub variables: Vec<Variable>,
+    pub requests: Vec<(String, Request)>,
+    /// Parts of the source that were skipped while loading
+    pub warnings: Vec<String>,
+    /// Where each request's snapshot is kept, by name
+    pub snapshot_stores: HashMap<String, SnapshotStore>,
+    /// The snapshots recorded so far, by request name
+    pub snapshots: HashMap<String, Snapshot>,
+    /// Applied to responses before they are compared with `snapshots`
+    pub masking: Masking,
+}
+
+/// Resolves a `.http` / `.rest` path, or a collection by name or id. `folder`
+/// narrows a collection down to one of its folders, by name.
+pub fn load_target(db: &Database, target: &str, folder: Option<&str>) -> Result<Target, String> {
+    let path = Path::new(target);
+    let is_http_file = path
+        .extension()
+        .is_some_and(|ext| ext == "http" || ext == "rest");
+
+    if is_http_file {
+        if folder.is_some() {
+            return Err(format!("{} has no folders", target));
+        }
+        let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", target, e))?;
+        let base_dir = path.parent().unwrap_or(Path::new("."));
+        let file = http::parse(&text, base_dir).map_err(|e| format!("{}: {}", target, e))?;
+        let sidecar = snapshot::sidecar_path(path);
+        let mut target = Target {
+            name: path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_else(|| target.to_string()),
+            variables: file.variables,
+            snapshot_stores: file
+                .requests
+                .iter()
+                .map(|r| {
+                    let store = SnapshotStore::File(sidecar.clone(), r.name.clone());
+                    (r.name.clone(), store)
+                })
+                .collect(),
+            requests: file
+                .requests
+                .into_iter()
+                .map(|r| (r.name, r.request))
+                .collect(),
+            warnings: file.warnings,
+            snapshots: HashMap::new(),
+            masking: Masking::default(),
+        };
+        load_snapshots(db, &mut target)?;
+        return Ok(target);
+    }
+
+    let collections = db.get_collections().map_err(|e| e.to_string())?;
+    let collection = collections
+        .iter()
+        .find(|c| c.name == target)
+        .or_else(|| {
+            let id = target.parse::<i64>().ok()?;
+            collections.iter().find(|c| c.id == id)
+        })
+        .ok_or_else(|| format!("no collection or .http file named `{}`", target))?;
+
+    let folder_id = match folder {
+        Some(folder) => Some(
+            db.get_collection_items(collection.id)
+                .map_err(|e| e.to_string())?
+                .iter()
+                .find(|item| item.kind == ItemKind::Folder && item.name == folder)
+                .map(|item| item.id)
+                .ok_or_else(|| format!("“{}” has no folder named `{}`", collection.name, folder))?,
+        ),
+        None => None,
+    };
+    collection_target(db, collection.id, folder_id)
+}
+
+/// The requests of a collection, or of one folder or request in it, depth
+/// first and named `Folder / Request` below the starting point.
+pub fn collection_target(
+    db: &Database,
+    collection_id: i64,
+    item_id: Option<i64>,
+) -> Result<Target, String> {
+    let collection = db
+        .get_collection(collection_id)
+        .map_err(|e| e.to_string())?;
+    let items = db
+        .get_collection_items(collection_id)
+        .map_err(|e| e.to_string())?;
+
+    let mut name = collection.name;
+    let mut prefixes: HashMap<Option<i64>, String> = HashMap::from([(item_id, String::new())]);
+    let mut requests = Vec::new();
+    let mut snapshot_stores = HashMap::new();
+
+    // Parents come before their children, so anything outside the folder has no prefix
+    for item in &items {
+        if Some(item.id) == item_id {
+            name = format!("{} / {}", name, item.name);
+            if item.kind == ItemKind::Request {
+                requests.push((item.name.clone(), item.request.clone()));
+                snapshot_stores.insert(item.name.clone(), SnapshotStore::for_item(db, item.id)?);
+            }
+        }
+        let Some(prefix) = prefixes.get(&item.parent_id).cloned() else {
+            continue;
+        };
+        match item.kind {
+            ItemKind::Folder => {
+                prefixes.insert(Some(item.id), format!("{}{} / ", prefix, item.name));
+            }
+            ItemKind::Request => {
+                let name = format!("{}{}", prefix, item.name);
+                snapshot_stores.insert(name.clone(), SnapshotStore::for_item(db, item.id)?);
+                requests.push((name, item.request.clone()));
+            }
+        }
+    }
+
+    let mut target = Target {
+        name,
+        variables: collection.variables,
+        requests,
+        warnings: Vec::new(),
+        snapshot_stores,
+        snapshots: HashMap::new(),
+        masking: Masking::default(),
+    };
+    load_snapshots(db, &mut target)?;
+    Ok(target)
+}
+
+/// Fills `target.snapshots` and `target.masking`; snapshots that cannot be
+/// read become warnings.
+fn load_snapshots(db: &Database, target: &mut Target) -> Result<(), String> {
+    target.masking = Masking::load(db)?;
+    for (name, store) in &target.snapshot_stores {
+        match store.load(db) {
+            Ok(Some(snapshot)) => {
+                target.snapshots.insert(name.clone(), snapshot);
+            }
+            Ok(None) => {}
+            Err(e) => target.warnings.push(format!("snapshot of {}: {}", name, e)),
+        }
+    }
+    Ok(())
+}
+
+/// Records the last response each request got in `report` as its snapshot,
+/// keeping the ignored paths. Snapshots the responses still match are left
+/// as they are. Returns how many were written.
+pub fn update_snapshots(
+    db: &Database,
+    target: &Target,
+    report: &RunReport,
+) -> Result<usize, String> {
+    let mut latest: HashMap<&str, &ApiResponse> = HashMap::new();
+    for step in &report.steps {
+        if let Ok(response) = &step.response {
+            latest.insert(&step.name, response);
+        }
+    }
+
+    let mut updated = 0;
+    for (name, response) in latest {
+        let Some(store) = target.snapshot_stores.get(name) else {
+            continue;
+        };
+        let old = target.snapshots.get(name);
+        if old.is_some_and(|old| {
+            old.differences(&response.status, &response.body, &target.masking)
+                .is_empty()
+        }) {
+            continue;
+        }
+        let ignored = old.map(|old| old.ignored.clone()).unwrap_or_default();
+        let snapshot = Snapshot::record(&response.status, &response.body, ignored, &target.masking);
+        store.save(db, Some(&snapshot))?;
+        updated += 1;
+    }
+    Ok(updated)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RunOptions {
+    pub stop_on_failure: bool,
+    /// Pause between requests
+    pub delay_ms: u64,
+    /// How many times the whole sequence runs, over every data row if any
+    pub iterations: u32,
+    /// Leave snapshots unchecked, since the responses are to replace them
+    pub update_snapshots: bool,
+}
+
+impl Default for RunOptions {
+    fn default() -> Self {
+        RunOptions {
+            stop_on_failure: false,
+            delay_ms: 0,
+            iterations: 1,
+            update_snapshots: false,
+        }
+    }
+}
+
+/// One request sent during a run.
+#[derive(Debug, Clone)]
+pub struct RunStep {
+    /// Counted from 1
+    pub iteration: u32,
+    /// Index into the report's data rows, for data-driven runs
+    pub row: Option<usize>,
+    pub name: String,
+    /// As sent, with variables resolved
+    pub request: Request,
+    pub response: Result<ApiResponse, ApiError>,
+    pub assertions: Vec<AssertionResult>,
+    pub extracted: Vec<Extraction>,
+    /// Script console output
+    pub logs: Vec<String>,
+}
+
+impl RunStep {
+    /// Assertions decide when the request has any, otherwise a 4xx/5xx status fails.
+    pub fn passed(&self) -> bool {
+        match &self.response {
+            Ok(_) if !self.assertions.is_empty() => self.assertions.iter().all(|a| a.passed),
+            Ok(response) => response.status_code < 400,
+            Err(_) => false,
+        }
+    }
+
+    pub fn time_ms(&self) -> Option<f64> {
+        self.response
+            .as_ref()
+            .ok()
+            .and_then(|r| api::parse_duration_ms(&r.time))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RunReport {
+    pub name: String,
+    pub started: DateTime<Utc>,
+    pub duration_ms: f64,
+    pub steps: Vec<RunStep>,
+    /// Stopped early, by the user or by `stop_on_failure`
+    pub stopped: bool,
+    pub data: Option<DataSet>,
+}
+
+impl RunReport {
+    pub fn passed(&self) -> usize {
+        self.steps.iter().filter(|s| s.passed()).count()
+    }
+
+    pub fn failed(&self) -> usize {
+        self.steps.len() - self.passed()
+    }
+
+    /// Data rows with at least one failing step, in order.
+    pub fn failed_rows(&self) -> Vec<usize> {
+        let mut rows: Vec<usize> = self
+            .steps
+            .iter()
+            .filter(|s| !s.passed())
+            .filter_map(|s| s.row)
+            .collect();
+        rows.sort_unstable();
+        rows.dedup();
+        rows
+    }
+}
+
+/// Sends every request of `target` in order, `options.iterations` times.
+/// All requests share one variable scope: the target's variables overridden by
+/// `environment`. With `data`, the sequence runs once per row and the row's
+/// columns override both. Values captured or set by scripts apply to the rest of
+/// the pass, without touching the environment. `on_step` sees each step as it
+/// completes; setting `cancel` stops the run before the next request.
+pub fn run(
+    target: &Target,
+    environment: &[Variable],
+    data: Option<&DataSet>,
+    options: RunOptions,
+    cancel: &AtomicBool,
+    mut on_step: impl FnMut(&RunStep),
+) -> RunReport {
+    let started = Utc::now();
+    let clock = Instant::now();
+    let base_scope = variables::scope(&[&target.variables, environment]);
+
+    let rows: Vec<Option<usize>> = match data {
+        Some(data) => (0..data.rows.len()).map(Some).collect(),
+        None => vec![None],
+    };
+    let passes = (0..options.iterations.max(1)).flat_map(|_| rows.iter().copied());
+
+    let mut steps = Vec::new();
+    let mut stopped = false;
+
+    'run: for (iteration, row) in (1..).zip(passes) {
+        let mut scope = base_scope.clone();
+        if let (Some(data), Some(row)) = (data, row) {
+            for (column, value) in data.values(row) {
+                scope.insert(column.to_string(), value.to_string());
+            }
+        }
+
+        for (name, request) in &target.requests {
+            if cancel.load(Ordering::Relaxed) {
+                stopped = true;
+                break 'run;
+            }
+            if !steps.is_empty() && options.delay_ms > 0 {
+                thread::sleep(Duration::from_millis(options.delay_ms));
+            }
+
+            let mut script = ScriptOutput::default();
+            let (request, response) = match prepare(request, &mut scope, &mut script) {
+                Ok(request) => {
+                    let response = send(&request);
+                    (request, response)
+                }
+                Err(e) => (
+                    variables::resolve_request(request, &scope),
+                    Err(ApiError::RequestFailed(e)),
+                ),
+            };
+            let (assertions, extracted) = match &response {
+                Ok(response) => {
+                    let (mut assertions, extracted) =
+                        check(&request, response, &mut scope, &mut script);
+                    if let Some(snapshot) = target.snapshots.get(name)
+                        && !options.update_snapshots
+                    {
+                        assertions.push(snapshot.check(response, &target.masking));
+                    }
+                    (assertions, extracted)
+                }
+                Err(_) => (Vec::new(), Vec::new()),
+            };
+
+            let step = RunStep {
+                iteration,
+                row,
+                name: name.clone(),
+                request,
+                response,
+                assertions,
+                extracted,
+                logs: script.logs,
+            };
+            on_step(&step);
+
+            let failed = !step.passed();
+            steps.push(step);
+            if failed && options.stop_on_failure {
+                stopped = true;
+                break 'run;
+            }
+        }
+    }
+
+    RunReport {
+        name: target.name.clone(),
+        started,
+        duration_ms: clock.elapsed().as_secs_f64() * 1000.0,
+        steps,
+        stopped,
+        data: data.cloned(),
+    }
+}
+
+/// Resolves `request` against `scope`, then runs its pre-request script on the
+/// result. Variables the script sets join `scope` and fill the placeholders
+/// still left.
+pub fn prepare(
+    request: &Request,
+    scope: &mut Scope,
+    output: &mut ScriptOutput,
+) -> Result<Request, String> {
+    let mut resolved = variables::resolve_request(request, scope);
+    scripting::pre_request(&request.scripts.pre_request, &mut resolved, scope, output)
+        .map_err(|e| format!("pre-request script: {}", e))?;
+    for variable in &output.variables {
+        if let Ok(value) = &variable.value {
+            scope.insert(variable.variable.clone(), value.clone());
+        }
+    }
+    Ok(variables::resolve_request(&resolved, scope))
+}
+
+/// Checks a response with the request's assertions, extractors and
+/// post-response script, in that order. Script tests join the assertion
+/// results, a script error as a failed one, and variables set by either script
+/// join the captures. Everything captured is added to `scope`.
+pub fn check(
+    request: &Request,
+    response: &ApiResponse,
+    scope: &mut Scope,
+    output: &mut ScriptOutput,
+) -> (Vec<AssertionResult>, Vec<Extraction>) {
+    let mut results = assertions::evaluate(&request.assertions, response);
+    let mut extracted = extractors::extract(&request.extractors, response);
+    insert_captured(scope, &extracted);
+
+    let script = &request.scripts.post_response;
+    if let Err(e) = scripting::post_response(script, request, response, scope, output) {
+        output.tests.push(AssertionResult {
+            description: "post-response script".to_string(),
+            passed: false,
+            message: e,
+        });
+    }
+    insert_captured(scope, &output.variables);
+
+    results.append(&mut output.tests);
+    extracted.extend(output.variables.iter().cloned());
+    (results, extracted)
+}
+
+fn insert_captured(scope: &mut Scope, extracted: &[Extraction]) {
+    for extraction in extracted {
+        if let Ok(value) = &extraction.value {
+            scope.insert(extraction.variable.clone(), value.clone());
+        }
+    }
+}
+
+pub fn send(request: &Request) -> Result<ApiResponse, ApiError> {
+    api::perform_request(
+        request.method,
+        &request.url,
+        &request.body,
+        request.headers.clone(),
+        request.options,
+    )
+}
+
+/// Masks secret values, and what the redaction policy covers, in a history
+/// entry: for exports, and to scrub entries saved before the policy.
+pub fn history_redaction(
+    db: &Database,
+) -> rusqlite::Result<impl Fn(&HistoryItem) -> HistoryItem + use<>> {
+    let secret_values = db.secret_values()?;
+    let redactor = Redactor::new(&db.redaction_policy()?);
+    Ok(move |item: &HistoryItem| redactor.history(&secrets::redact_history(item, &secret_values)))
+}
+
+/// A copy of a run report fit to write to a file, with secret values and what
+/// the redaction policy covers masked in requests, captured values, assertion
+/// messages and console output.
+pub fn redact_report(db: &Database, report: &RunReport) -> rusqlite::Result<RunReport> {
+    let secret_values = db.secret_values()?;
+    let redactor = Redactor::new(&db.redaction_policy()?);
+    let text = |text: &str| redactor.text(&secrets::redact(text, &secret_values));
+
+    let steps = report
+        .steps
+        .iter()
+        .map(|step| {
+            let request = secrets::redact_request(&step.request, &secret_values);
+            RunStep {
+                request: Request {
+                    url: redactor.url(&request.url),
+                    headers: redactor.header_pairs(&request.headers),
+                    body: redactor.body(&request.body),
+                    ..request
+                },
+                response: step
+                    .response
+                    .clone()
+                    .map_err(|ApiError::RequestFailed(msg)| ApiError::RequestFailed(text(&msg))),
+                assertions: step
+                    .assertions
+                    .iter()
+                    .map(|a| AssertionResult {
+                        message: text(&a.message),
+                        ..a.clone()
+                    })
+                    .collect(),
+                extracted: step
+                    .extracted
+                    .iter()
+                    .map(|e| Extraction {
+                        variable: e.variable.clone(),
+                        value: e.value.as_deref().map(text).map_err(|reason| text(reason)),
+                    })
+                    .collect(),
+                logs: step.logs.iter().map(|line| text(line)).collect(),
+                ..step.clone()
+            }
+        })
+        .collect();
+
+    Ok(RunReport {
+        steps,
+        ..report.clone()
+    })
+}
+
+/// Saves an exchange and its assertion results to history. `request` should
+/// already have its variables resolved; secret values are written back as
+/// their placeholders, and the redaction policy masks the rest.
+pub fn record(
+    db: &Database,
+    request: &Request,
+    response: &ApiResponse,
+    results: &[AssertionResult],
+) -> rusqlite::Result<i64> {
+    let secret_values = db.secret_values()?;
+    let policy = db.redaction_policy()?;
+    let redactor = Redactor::new(&policy);
+
+    let redacted = secrets::redact_request(request, &secret_values);
+    let headers_json =
+        serde_json::to_string(&redactor.header_pairs(&redacted.headers)).unwrap_or_default();
+
+    let id = db.save_exchange(
+        request.method.as_str(),
+        &redactor.url(&redacted.url),
+        &redactor.body(&redacted.body),
+        &headers_json,
+        &redactor.body(&secrets::redact(&response.body, &secret_values)),
+        &redactor.header_text(&secrets::redact(&response.headers, &secret_values)),
+        &response.status,
+        &response.time,
+        &response.size,
+    )?;
+    if !results.is_empty() {
+        let results: Vec<AssertionResult> = results
+            .iter()
+            .map(|r| AssertionResult {
+                message: redactor.text(&secrets::redact(&r.message, &secret_values)),
+                ..r.clone()
+            })
+            .collect();
+        db.save_assertion_results(id, &results)?;
+    }
+
+    if policy.keep_session {
+        let stored = db.get_request_by_id(id)?;
+        db.keep_original(HistoryItem {
+            url: request.url.clone(),
+            request_body: request.body.clone(),
+            request_headers: serde_json::to_string(&request.headers).unwrap_or_default(),
+            response_body: response.body.clone(),
+            response_headers: response.headers.clone(),
+            ..stored
+        });
+    }
+    Ok(id)
+}
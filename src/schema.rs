@@ -0,0 +1,375 @@
+use regex::Regex;
+use serde_json::Value;
+
+// Stops runaway `$ref` chains
+const MAX_DEPTH: usize = 64;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Violation {
+    /// JSON pointer to the offending value, `""` for the document itself
+    pub pointer: String,
+    pub message: String,
+}
+
+/// Validates `instance` against a JSON Schema (draft 4 to 2020-12 keywords,
+/// plus OpenAPI's `nullable`). `root` is the document local `$ref`s point into.
+pub fn validate(schema: &Value, root: &Value, instance: &Value) -> Vec<Violation> {
+    let mut validator = Validator {
+        root,
+        violations: Vec::new(),
+    };
+    validator.check(schema, instance, "", 0);
+    validator.violations
+}
+
+struct Validator<'a> {
+    root: &'a Value,
+    violations: Vec<Violation>,
+}
+
+impl<'a> Validator<'a> {
+    fn fail(&mut self, pointer: &str, message: String) {
+        self.violations.push(Violation {
+            pointer: pointer.to_string(),
+            message,
+        });
+    }
+
+    /// Validates without recording violations, for `anyOf`, `oneOf` and `not`.
+    fn matches(&self, schema: &Value, instance: &Value, depth: usize) -> bool {
+        let mut probe = Validator {
+            root: self.root,
+            violations: Vec::new(),
+        };
+        probe.check(schema, instance, "", depth);
+        probe.violations.is_empty()
+    }
+
+    fn check(&mut self, schema: &Value, instance: &Value, pointer: &str, depth: usize) {
+        if depth > MAX_DEPTH {
+            return;
+        }
+
+        let schema = match schema {
+            Value::Bool(true) => return,
+            Value::Bool(false) => {
+                self.fail(pointer, "no value is allowed here".to_string());
+                return;
+            }
+            Value::Object(_) => schema,
+            _ => return,
+        };
+
+        if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+            match reference
+                .strip_prefix('#')
+                .and_then(|target| self.root.pointer(target))
+            {
+                Some(target) => self.check(target, instance, pointer, depth + 1),
+                None => self.fail(pointer, format!("cannot resolve reference {}", reference)),
+            }
+            // Before 2019-09, `$ref` replaces its sibling keywords
+            return;
+        }
+
+        if instance.is_null()
+            && schema
+                .get("nullable")
+                .and_then(Value::as_bool)
+                .unwrap_or(false)
+        {
+            return;
+        }
+
+        if let Some(expected) = schema.get("type") {
+            let allowed: Vec<&str> = match expected {
+                Value::String(t) => vec![t.as_str()],
+                Value::Array(types) => types.iter().filter_map(Value::as_str).collect(),
+                _ => Vec::new(),
+            };
+            if !allowed.is_empty() && !allowed.iter().any(|t| is_type(instance, t)) {
+                self.fail(
+                    pointer,
+                    format!(
+                        "expected {}, found {}",
+                        allowed.join(" or "),
+                        type_name(instance)
+                    ),
+                );
+                return;
+            }
+        }
+
+        if let Some(options) = schema.get("enum").and_then(Value::as_array)
+            && !options.contains(instance)
+        {
+            self.fail(
+                pointer,
+                format!("{} is not one of the allowed values", short(instance)),
+            );
+        }
+        if let Some(expected) = schema.get("const")
+            && expected != instance
+        {
+            self.fail(pointer, format!("expected {}", short(expected)));
+        }
+
+        for part in schema
+            .get("allOf")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+        {
+            self.check(part, instance, pointer, depth + 1);
+        }
+        if let Some(options) = schema.get("anyOf").and_then(Value::as_array)
+            && !options.iter().any(|s| self.matches(s, instance, depth + 1))
+        {
+            self.fail(
+                pointer,
+                "does not match any of the `anyOf` schemas".to_string(),
+            );
+        }
+        if let Some(options) = schema.get("oneOf").and_then(Value::as_array) {
+            let matching = options
+                .iter()
+                .filter(|s| self.matches(s, instance, depth + 1))
+                .count();
+            if matching != 1 {
+                self.fail(
+                    pointer,
+                    format!("matches {} of the `oneOf` schemas, expected 1", matching),
+                );
+            }
+        }
+        if let Some(forbidden) = schema.get("not")
+            && self.matches(forbidden, instance, depth + 1)
+        {
+            self.fail(pointer, "matches a schema it must not match".to_string());
+        }
+
+        match instance {
+            Value::Object(fields) => self.check_object(schema, fields, pointer, depth),
+            Value::Array(items) => self.check_array(schema, items, pointer, depth),
+            Value::String(text) => self.check_string(schema, text, pointer),
+            Value::Number(_) => self.check_number(schema, instance, pointer),
+            _ => {}
+        }
+    }
+
+    fn check_object(
+        &mut self,
+        schema: &Value,
+        fields: &serde_json::Map<String, Value>,
+        pointer: &str,
+        depth: usize,
+    ) {
+        for name in schema
+            .get("required")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+        {
+            if !fields.contains_key(name) {
+                self.fail(pointer, format!("missing required property `{}`", name));
+            }
+        }
+
+        let properties = schema.get("properties").and_then(Value::as_object);
+        for (name, value) in fields {
+            let child = format!("{}/{}", pointer, escape(name));
+            match properties.and_then(|p| p.get(name)) {
+                Some(property) => self.check(property, value, &child, depth + 1),
+                None => match schema.get("additionalProperties") {
+                    Some(Value::Bool(false)) => {
+                        self.fail(&child, format!("property `{}` is not allowed", name))
+                    }
+                    Some(additional) => self.check(additional, value, &child, depth + 1),
+                    None => {}
+                },
+            }
+        }
+
+        let count = fields.len() as u64;
+        if let Some(min) = schema.get("minProperties").and_then(Value::as_u64)
+            && count < min
+        {
+            self.fail(
+                pointer,
+                format!("has {} properties, at least {} required", count, min),
+            );
+        }
+        if let Some(max) = schema.get("maxProperties").and_then(Value::as_u64)
+            && count > max
+        {
+            self.fail(
+                pointer,
+                format!("has {} properties, at most {} allowed", count, max),
+            );
+        }
+    }
+
+    fn check_array(&mut self, schema: &Value, items: &[Value], pointer: &str, depth: usize) {
+        // 2020-12 `prefixItems`, or draft 4-7 tuple-style `items`
+        let (prefix, rest) = match (schema.get("prefixItems"), schema.get("items")) {
+            (Some(Value::Array(prefix)), rest) => (prefix.as_slice(), rest),
+            (None, Some(Value::Array(prefix))) => {
+                (prefix.as_slice(), schema.get("additionalItems"))
+            }
+            (_, rest) => (&[][..], rest),
+        };
+
+        for (index, item) in items.iter().enumerate() {
+            let child = format!("{}/{}", pointer, index);
+            if let Some(item_schema) = prefix.get(index).or(rest) {
+                self.check(item_schema, item, &child, depth + 1)
+            }
+        }
+
+        let count = items.len() as u64;
+        if let Some(min) = schema.get("minItems").and_then(Value::as_u64)
+            && count < min
+        {
+            self.fail(
+                pointer,
+                format!("has {} items, at least {} required", count, min),
+            );
+        }
+        if let Some(max) = schema.get("maxItems").and_then(Value::as_u64)
+            && count > max
+        {
+            self.fail(
+                pointer,
+                format!("has {} items, at most {} allowed", count, max),
+            );
+        }
+        if schema.get("uniqueItems").and_then(Value::as_bool) == Some(true) {
+            for (index, item) in items.iter().enumerate() {
+                if items[..index].contains(item) {
+                    self.fail(
+                        &format!("{}/{}", pointer, index),
+                        "duplicate item".to_string(),
+                    );
+                }
+            }
+        }
+    }
+
+    fn check_string(&mut self, schema: &Value, text: &str, pointer: &str) {
+        let length = text.chars().count() as u64;
+        if let Some(min) = schema.get("minLength").and_then(Value::as_u64)
+            && length < min
+        {
+            self.fail(
+                pointer,
+                format!("is {} characters, at least {} required", length, min),
+            );
+        }
+        if let Some(max) = schema.get("maxLength").and_then(Value::as_u64)
+            && length > max
+        {
+            self.fail(
+                pointer,
+                format!("is {} characters, at most {} allowed", length, max),
+            );
+        }
+        if let Some(pattern) = schema.get("pattern").and_then(Value::as_str) {
+            match Regex::new(pattern) {
+                Ok(regex) if !regex.is_match(text) => {
+                    self.fail(pointer, format!("does not match pattern `{}`", pattern))
+                }
+                Ok(_) => {}
+                Err(_) => self.fail(pointer, format!("invalid pattern `{}` in schema", pattern)),
+            }
+        }
+    }
+
+    fn check_number(&mut self, schema: &Value, instance: &Value, pointer: &str) {
+        let Some(number) = instance.as_f64() else {
+            return;
+        };
+        let bound = |key: &str| schema.get(key).and_then(Value::as_f64);
+
+        // Draft 4 spells exclusive bounds as booleans next to minimum/maximum
+        let exclusive_flag = |key: &str| schema.get(key).and_then(Value::as_bool).unwrap_or(false);
+
+        if let Some(min) = bound("minimum")
+            && (number < min || (exclusive_flag("exclusiveMinimum") && number == min))
+        {
+            self.fail(
+                pointer,
+                format!("{} is below the minimum of {}", number, min),
+            );
+        }
+        if let Some(min) = bound("exclusiveMinimum")
+            && number <= min
+        {
+            self.fail(pointer, format!("{} must be greater than {}", number, min));
+        }
+        if let Some(max) = bound("maximum")
+            && (number > max || (exclusive_flag("exclusiveMaximum") && number == max))
+        {
+            self.fail(
+                pointer,
+                format!("{} is above the maximum of {}", number, max),
+            );
+        }
+        if let Some(max) = bound("exclusiveMaximum")
+            && number >= max
+        {
+            self.fail(pointer, format!("{} must be less than {}", number, max));
+        }
+        if let Some(divisor) = bound("multipleOf").filter(|d| *d > 0.0) {
+            let quotient = number / divisor;
+            if (quotient - quotient.round()).abs() > 1e-9 {
+                self.fail(
+                    pointer,
+                    format!("{} is not a multiple of {}", number, divisor),
+                );
+            }
+        }
+    }
+}
+
+fn is_type(instance: &Value, name: &str) -> bool {
+    match name {
+        "null" => instance.is_null(),
+        "boolean" => instance.is_boolean(),
+        "object" => instance.is_object(),
+        "array" => instance.is_array(),
+        "string" => instance.is_string(),
+        "number" => instance.is_number(),
+        "integer" => {
+            instance.is_i64()
+                || instance.is_u64()
+                || instance.as_f64().is_some_and(|n| n.fract() == 0.0)
+        }
+        _ => true,
+    }
+}
+
+fn type_name(instance: &Value) -> &'static str {
+    match instance {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn short(value: &Value) -> String {
+    let text = value.to_string();
+    if text.chars().count() > 40 {
+        format!("{}…", text.chars().take(40).collect::<String>())
+    } else {
+        text
+    }
+}
+
+/// Escapes a property name for use in a JSON pointer (RFC 6901).
+pub fn escape(segment: &str) -> String {
+    segment.replace('~', "~0").replace('/', "~1")
+}
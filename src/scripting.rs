@@ -0,0 +1,295 @@
+use crate::api::{self, ApiResponse};
+use crate::config;
+use crate::models::{AssertionResult, Extraction, Method, Request};
+use crate::variables::Scope;
+use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
+use hmac::{Hmac, Mac};
+use rhai::{Dynamic, Engine, EvalAltResult, Map, module_resolvers::DummyModuleResolver};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::{
+    cell::RefCell,
+    rc::Rc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// What scripts produced while a request was sent: console lines, variables
+/// set with `set_var` and results added with `test`.
+#[derive(Debug, Clone, Default)]
+pub struct ScriptOutput {
+    pub logs: Vec<String>,
+    pub variables: Vec<Extraction>,
+    pub tests: Vec<AssertionResult>,
+}
+
+/// Runs a pre-request script. It sees the request as `request`, a map of
+/// `method`, `url`, `headers` and `body`, and whatever it changes there is sent.
+pub fn pre_request(
+    script: &str,
+    request: &mut Request,
+    scope: &Scope,
+    output: &mut ScriptOutput,
+) -> Result<(), String> {
+    if script.trim().is_empty() {
+        return Ok(());
+    }
+
+    let mut script_scope = rhai::Scope::new();
+    script_scope.push("request", request_map(request));
+    execute(script, &mut script_scope, scope, output, "pre-request")?;
+
+    let map = script_scope
+        .get_value::<Map>("request")
+        .ok_or("`request` is no longer a map")?;
+    update_request(request, &map)
+}
+
+/// Runs a post-response script with `request` and `response` in scope; the
+/// response map has `status`, `status_text`, `headers`, `body`, `time_ms`
+/// and, when the body is JSON, `json`.
+pub fn post_response(
+    script: &str,
+    request: &Request,
+    response: &ApiResponse,
+    scope: &Scope,
+    output: &mut ScriptOutput,
+) -> Result<(), String> {
+    if script.trim().is_empty() {
+        return Ok(());
+    }
+
+    let mut script_scope = rhai::Scope::new();
+    script_scope.push_constant("request", request_map(request));
+    script_scope.push_constant("response", response_map(response));
+    execute(script, &mut script_scope, scope, output, "post-response")
+}
+
+/// Evaluates `script` in a sandboxed engine: no file or module access, and
+/// bounded operations so a runaway loop ends with an error.
+fn execute(
+    script: &str,
+    script_scope: &mut rhai::Scope,
+    scope: &Scope,
+    output: &mut ScriptOutput,
+    phase: &str,
+) -> Result<(), String> {
+    // Functions registered on the engine share these until it is dropped
+    let logs = Rc::new(RefCell::new(Vec::new()));
+    let variables = Rc::new(RefCell::new(scope.clone()));
+    let set = Rc::new(RefCell::new(Vec::<Extraction>::new()));
+    let tests = Rc::new(RefCell::new(Vec::new()));
+
+    let mut engine = Engine::new();
+    engine.set_max_operations(config::SCRIPT_MAX_OPERATIONS);
+    engine.set_max_call_levels(config::SCRIPT_MAX_CALL_LEVELS);
+    engine.set_max_string_size(config::SCRIPT_MAX_STRING_SIZE);
+    engine.set_module_resolver(DummyModuleResolver::new());
+    engine.disable_symbol("eval");
+
+    let log = logs.clone();
+    engine.on_print(move |text| log.borrow_mut().push(text.to_string()));
+    let log = logs.clone();
+    engine.on_debug(move |text, _, position| {
+        log.borrow_mut().push(format!("[{}] {}", position, text))
+    });
+
+    let vars = variables.clone();
+    engine.register_fn("get_var", move |name: &str| -> Dynamic {
+        vars.borrow()
+            .get(name)
+            .map_or(Dynamic::UNIT, |value| value.clone().into())
+    });
+    let vars = variables.clone();
+    let changed = set.clone();
+    engine.register_fn("set_var", move |name: &str, value: Dynamic| {
+        let value = value.to_string();
+        vars.borrow_mut().insert(name.to_string(), value.clone());
+        let mut changed = changed.borrow_mut();
+        changed.retain(|e| e.variable != name);
+        changed.push(Extraction {
+            variable: name.to_string(),
+            value: Ok(value),
+        });
+    });
+
+    let results = tests.clone();
+    engine.register_fn("test", move |name: &str, passed: bool| {
+        results.borrow_mut().push(AssertionResult {
+            description: name.to_string(),
+            passed,
+            message: if passed { "passed" } else { "failed" }.to_string(),
+        });
+    });
+    let results = tests.clone();
+    engine.register_fn("test", move |name: &str, passed: bool, message: &str| {
+        results.borrow_mut().push(AssertionResult {
+            description: name.to_string(),
+            passed,
+            message: message.to_string(),
+        });
+    });
+
+    register_helpers(&mut engine);
+
+    let result = engine.run_with_scope(script_scope, script);
+    drop(engine);
+
+    output.logs.extend(
+        logs.take()
+            .into_iter()
+            .map(|l| format!("[{}] {}", phase, l)),
+    );
+    for extraction in set.take() {
+        output
+            .variables
+            .retain(|e| e.variable != extraction.variable);
+        output.variables.push(extraction);
+    }
+    output.tests.extend(tests.take());
+
+    result.map_err(|e| describe_error(&e))
+}
+
+/// Functions for values declarative features cannot produce: hashes and
+/// signatures, encodings, nonces and timestamps.
+fn register_helpers(engine: &mut Engine) {
+    engine.register_fn("sha256", |text: &str| hex(&Sha256::digest(text.as_bytes())));
+    engine.register_fn("hmac_sha256", |key: &str, message: &str| {
+        hex(&hmac_sha256(key, message))
+    });
+    engine.register_fn("hmac_sha256_base64", |key: &str, message: &str| {
+        BASE64.encode(hmac_sha256(key, message))
+    });
+    engine.register_fn("base64_encode", |text: &str| BASE64.encode(text));
+    engine.register_fn(
+        "base64_decode",
+        |text: &str| -> Result<String, Box<EvalAltResult>> {
+            let bytes = BASE64
+                .decode(text.trim())
+                .map_err(|e| format!("invalid base64: {}", e))?;
+            Ok(String::from_utf8_lossy(&bytes).into_owned())
+        },
+    );
+    engine.register_fn("uuid", || uuid::Uuid::new_v4().to_string());
+    engine.register_fn("timestamp", || unix_time().as_secs() as i64);
+    engine.register_fn("timestamp_ms", || unix_time().as_millis() as i64);
+    engine.register_fn("now_iso", || chrono::Utc::now().to_rfc3339());
+    engine.register_fn(
+        "parse_json",
+        |text: &str| -> Result<Dynamic, Box<EvalAltResult>> {
+            let value: Value =
+                serde_json::from_str(text).map_err(|e| format!("invalid JSON: {}", e))?;
+            rhai::serde::to_dynamic(value)
+        },
+    );
+    engine.register_fn(
+        "to_json",
+        |value: Dynamic| -> Result<String, Box<EvalAltResult>> {
+            let value: Value = rhai::serde::from_dynamic(&value)?;
+            Ok(value.to_string())
+        },
+    );
+}
+
+fn request_map(request: &Request) -> Map {
+    let headers: Map = request
+        .headers
+        .iter()
+        .map(|(key, value)| (key.as_str().into(), value.clone().into()))
+        .collect();
+
+    let mut map = Map::new();
+    map.insert("method".into(), request.method.as_str().into());
+    map.insert("url".into(), request.url.clone().into());
+    map.insert("headers".into(), headers.into());
+    map.insert("body".into(), request.body.clone().into());
+    map
+}
+
+fn response_map(response: &ApiResponse) -> Map {
+    let headers: Map = api::parse_headers(&response.headers)
+        .into_iter()
+        .map(|(key, value)| (key.to_lowercase().into(), value.into()))
+        .collect();
+
+    let mut map = Map::new();
+    map.insert("status".into(), (response.status_code as i64).into());
+    map.insert("status_text".into(), response.status.clone().into());
+    map.insert("headers".into(), headers.into());
+    map.insert("body".into(), response.body.clone().into());
+    if let Some(time) = api::parse_duration_ms(&response.time) {
+        map.insert("time_ms".into(), time.into());
+    }
+    if let Some(json) = serde_json::from_str::<Value>(&response.body)
+        .ok()
+        .and_then(|v| rhai::serde::to_dynamic(v).ok())
+    {
+        map.insert("json".into(), json);
+    }
+    map
+}
+
+/// Copies the script's edits back. Headers keep their order; ones the script
+/// removed are dropped and new ones are appended.
+fn update_request(request: &mut Request, map: &Map) -> Result<(), String> {
+    if let Some(method) = map.get("method") {
+        let method = method.to_string().to_uppercase();
+        request.method = method
+            .parse::<Method>()
+            .map_err(|_| format!("unsupported method `{}`", method))?;
+    }
+    if let Some(url) = map.get("url") {
+        request.url = url.to_string();
+    }
+    if let Some(body) = map.get("body") {
+        request.body = body.to_string();
+    }
+    if let Some(headers) = map.get("headers") {
+        let headers = headers
+            .clone()
+            .try_cast::<Map>()
+            .ok_or("`request.headers` must be a map")?;
+
+        let mut updated: Vec<(String, String)> = request
+            .headers
+            .iter()
+            .filter_map(|(key, _)| {
+                let value = headers.get(key.as_str())?;
+                Some((key.clone(), value.to_string()))
+            })
+            .collect();
+        for (key, value) in &headers {
+            if !updated.iter().any(|(k, _)| k == key.as_str()) {
+                updated.push((key.to_string(), value.to_string()));
+            }
+        }
+        request.headers = updated;
+    }
+    Ok(())
+}
+
+fn describe_error(error: &EvalAltResult) -> String {
+    match error {
+        EvalAltResult::ErrorTooManyOperations(_) => {
+            "script stopped after too many operations".to_string()
+        }
+        error => error.to_string(),
+    }
+}
+
+fn hmac_sha256(key: &str, message: &str) -> Vec<u8> {
+    // HMAC accepts keys of any length
+    let mut mac = Hmac::<Sha256>::new_from_slice(key.as_bytes()).unwrap();
+    mac.update(message.as_bytes());
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn unix_time() -> std::time::Duration {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+}
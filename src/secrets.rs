@@ -0,0 +1,291 @@
+use crate::database::HistoryItem;
+use crate::models::{CollectionNode, CollectionTree, Request, Variable};
+use argon2::Argon2;
+use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
+use chacha20poly1305::{ChaCha20Poly1305, Key, KeyInit, Nonce, aead::Aead};
+
+/// Marks a stored value as encrypted: the prefix, then the nonce and
+/// ciphertext in Base64.
+const ENCRYPTED_PREFIX: &str = "enc:v1:";
+const NONCE_LENGTH: usize = 12;
+
+/// Plaintext encrypted when a passphrase is set, to check it on unlock.
+const CHECK_TEXT: &str = "dispatch secrets";
+
+/// Secrets shorter than this are refused when stored, since replacing every
+/// occurrence of a couple of characters would mangle the text around them.
+pub const MIN_SECRET_LENGTH: usize = 4;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SecretsError {
+    /// No passphrase was entered this session
+    Locked,
+    WrongPassphrase,
+    Crypto(String),
+    Storage(String),
+    /// A secret, by name, whose value is too short to redact
+    TooShort(String),
+}
+
+impl std::fmt::Display for SecretsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SecretsError::Locked => write!(f, "secret variables are locked"),
+            SecretsError::WrongPassphrase => write!(f, "wrong passphrase"),
+            SecretsError::Crypto(msg) => write!(f, "encryption error: {}", msg),
+            SecretsError::Storage(msg) => write!(f, "database error: {}", msg),
+            SecretsError::TooShort(name) => write!(
+                f,
+                "secret {} needs at least {} characters",
+                name, MIN_SECRET_LENGTH
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SecretsError {}
+
+/// Encrypts and decrypts secret variable values with a key derived from the
+/// user's passphrase. It only lives in memory, for one session.
+pub struct Vault {
+    cipher: ChaCha20Poly1305,
+}
+
+impl Vault {
+    /// Derives the key with Argon2id. `salt` is random and stored next to the data.
+    pub fn derive(passphrase: &str, salt: &[u8]) -> Result<Vault, SecretsError> {
+        let mut key = [0u8; 32];
+        Argon2::default()
+            .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+            .map_err(|e| SecretsError::Crypto(e.to_string()))?;
+        Ok(Vault {
+            cipher: ChaCha20Poly1305::new(Key::from_slice(&key)),
+        })
+    }
+
+    /// A new vault for `passphrase`, with the random salt and check value to
+    /// store for `open`.
+    pub fn create(passphrase: &str) -> Result<(Vault, String, String), SecretsError> {
+        let salt: [u8; 16] = rand::random();
+        let vault = Vault::derive(passphrase, &salt)?;
+        let check = vault.encrypt(CHECK_TEXT)?;
+        Ok((vault, BASE64.encode(salt), check))
+    }
+
+    /// Opens the vault for `passphrase` if it decrypts the stored check value.
+    pub fn open(passphrase: &str, salt: &str, check: &str) -> Result<Vault, SecretsError> {
+        let salt = BASE64
+            .decode(salt)
+            .map_err(|e| SecretsError::Crypto(e.to_string()))?;
+        let vault = Vault::derive(passphrase, &salt)?;
+        match vault.decrypt(check) {
+            Ok(text) if text == CHECK_TEXT => Ok(vault),
+            _ => Err(SecretsError::WrongPassphrase),
+        }
+    }
+
+    pub fn encrypt(&self, plaintext: &str) -> Result<String, SecretsError> {
+        let nonce: [u8; NONCE_LENGTH] = rand::random();
+        let ciphertext = self
+            .cipher
+            .encrypt(Nonce::from_slice(&nonce), plaintext.as_bytes())
+            .map_err(|e| SecretsError::Crypto(e.to_string()))?;
+
+        let mut data = nonce.to_vec();
+        data.extend(ciphertext);
+        Ok(format!("{}{}", ENCRYPTED_PREFIX, BASE64.encode(data)))
+    }
+
+    pub fn decrypt(&self, value: &str) -> Result<String, SecretsError> {
+        let encoded = value
+            .strip_prefix(ENCRYPTED_PREFIX)
+            .ok_or_else(|| SecretsError::Crypto("value is not encrypted".to_string()))?;
+        let data = BASE64
+            .decode(encoded)
+            .map_err(|e| SecretsError::Crypto(e.to_string()))?;
+
+        if data.len() < NONCE_LENGTH {
+            return Err(SecretsError::Crypto("value is truncated".to_string()));
+        }
+        let (nonce, ciphertext) = data.split_at(NONCE_LENGTH);
+        let plaintext = self
+            .cipher
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| SecretsError::WrongPassphrase)?;
+        String::from_utf8(plaintext).map_err(|e| SecretsError::Crypto(e.to_string()))
+    }
+}
+
+/// Whether a stored value is still encrypted, i.e. its secret is locked.
+pub fn is_encrypted(value: &str) -> bool {
+    value.starts_with(ENCRYPTED_PREFIX)
+}
+
+/// Secret variables whose value could not be decrypted this session.
+pub fn is_locked(variable: &Variable) -> bool {
+    variable.secret && is_encrypted(&variable.value)
+}
+
+/// Fails for a secret with a value too short to redact. Empty ones are
+/// allowed, having nothing to give away.
+pub fn check_length(variable: &Variable) -> Result<(), SecretsError> {
+    let length = variable.value.chars().count();
+    if variable.secret && length > 0 && length < MIN_SECRET_LENGTH {
+        return Err(SecretsError::TooShort(variable.key.clone()));
+    }
+    Ok(())
+}
+
+/// Replaces every secret value in `text` with its `{{name}}` placeholder.
+/// `secrets` holds `(name, value)` pairs.
+pub fn redact(text: &str, secrets: &[(String, String)]) -> String {
+    let mut secrets: Vec<&(String, String)> = secrets
+        .iter()
+        .filter(|(_, value)| !value.is_empty())
+        .collect();
+    // Longer values first, in case one secret contains another
+    secrets.sort_by_key(|(_, value)| std::cmp::Reverse(value.len()));
+
+    let mut text = text.to_string();
+    for (name, value) in secrets {
+        if text.contains(value.as_str()) {
+            text = text.replace(value.as_str(), &format!("{{{{{}}}}}", name));
+        }
+    }
+    text
+}
+
+pub fn redact_request(request: &Request, secrets: &[(String, String)]) -> Request {
+    Request {
+        url: redact(&request.url, secrets),
+        headers: request
+            .headers
+            .iter()
+            .map(|(k, v)| (k.clone(), redact(v, secrets)))
+            .collect(),
+        body: redact(&request.body, secrets),
+        ..request.clone()
+    }
+}
+
+/// A history entry ready to export, with secret values replaced.
+pub fn redact_history(item: &HistoryItem, secrets: &[(String, String)]) -> HistoryItem {
+    HistoryItem {
+        url: redact(&item.url, secrets),
+        request_body: redact(&item.request_body, secrets),
+        request_headers: redact(&item.request_headers, secrets),
+        response_body: redact(&item.response_body, secrets),
+        response_headers: redact(&item.response_headers, secrets),
+        ..item.clone()
+    }
+}
+
+/// Whether `request` uses a locked secret, as a `{{placeholder}}` or by name
+/// in one of its scripts, so it cannot be sent until secrets are unlocked.
+pub fn uses_locked(request: &Request, variables: &[Variable]) -> bool {
+    let scripts = [&request.scripts.pre_request, &request.scripts.post_response];
+    variables
+        .iter()
+        .filter(|v| v.enabled && is_locked(v))
+        .any(|v| {
+            let placeholder = format!("{{{{{}}}}}", v.key);
+            let name = format!("\"{}\"", v.key);
+            request.url.contains(&placeholder)
+                || request.body.contains(&placeholder)
+                || request
+                    .headers
+                    .iter()
+                    .any(|(_, value)| value.contains(&placeholder))
+                || scripts.iter().any(|script| script.contains(&name))
+        })
+}
+
+/// A collection ready to leave the app: secret values typed into requests
+/// are replaced by placeholders.
+pub fn redact_tree(tree: &CollectionTree, secrets: &[(String, String)]) -> CollectionTree {
+    fn redact_nodes(nodes: &[CollectionNode], secrets: &[(String, String)]) -> Vec<CollectionNode> {
+        nodes
+            .iter()
+            .map(|node| match node {
+                CollectionNode::Folder { name, children } => CollectionNode::Folder {
+                    name: name.clone(),
+                    children: redact_nodes(children, secrets),
+                },
+                CollectionNode::Request { name, request } => CollectionNode::Request {
+                    name: name.clone(),
+                    request: redact_request(request, secrets),
+                },
+            })
+            .collect()
+    }
+
+    CollectionTree {
+        items: redact_nodes(&tree.items, secrets),
+        ..tree.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn secret(key: &str, value: &str) -> Variable {
+        Variable {
+            key: key.to_string(),
+            value: value.to_string(),
+            enabled: true,
+            secret: true,
+        }
+    }
+
+    fn pairs(secrets: &[(&str, &str)]) -> Vec<(String, String)> {
+        secrets
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn redacts_every_secret_longest_first() {
+        let secrets = pairs(&[("pin", "1234"), ("token", "abc1234")]);
+        assert_eq!(
+            redact("token=abc1234&pin=1234", &secrets),
+            "token={{token}}&pin={{pin}}"
+        );
+    }
+
+    #[test]
+    fn skips_empty_secrets() {
+        let secrets = pairs(&[("blank", "")]);
+        assert_eq!(redact("unchanged", &secrets), "unchanged");
+    }
+
+    #[test]
+    fn refuses_secrets_too_short_to_redact() {
+        assert_eq!(
+            check_length(&secret("pin", "123")),
+            Err(SecretsError::TooShort("pin".to_string()))
+        );
+        assert_eq!(check_length(&secret("pin", "1234")), Ok(()));
+        assert_eq!(check_length(&secret("blank", "")), Ok(()));
+        let plain = Variable {
+            secret: false,
+            ..secret("id", "7")
+        };
+        assert_eq!(check_length(&plain), Ok(()));
+    }
+
+    #[test]
+    fn encrypts_and_decrypts_with_the_same_passphrase() {
+        let (vault, salt, check) = Vault::create("pw").unwrap();
+        let stored = vault.encrypt("s3cret").unwrap();
+        assert!(is_encrypted(&stored));
+
+        let reopened = Vault::open("pw", &salt, &check).unwrap();
+        assert_eq!(reopened.decrypt(&stored).unwrap(), "s3cret");
+        assert!(matches!(
+            Vault::open("other", &salt, &check),
+            Err(SecretsError::WrongPassphrase)
+        ));
+    }
+}
@@ -0,0 +1,206 @@
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use crate::{
+    api::ApiResponse,
+    config,
+    database::Database,
+    diff::{self, DiffSide, JsonChange, LineTag},
+    formats::http,
+    models::AssertionResult,
+    redaction::{RedactionPolicy, Redactor},
+    secrets,
+};
+
+/// Description of the assertion result `Snapshot::check` gives.
+pub const CHECK: &str = "matches snapshot";
+
+/// The response a saved request is expected to keep returning. Headers are
+/// left out, since so many of them change on every send.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Snapshot {
+    pub status: String,
+    pub body: String,
+    /// JSONPath expressions for values that differ on every send, such as
+    /// `$.id` or `$..created_at`
+    pub ignored: Vec<String>,
+    /// When it was recorded, RFC 3339
+    pub recorded: String,
+}
+
+impl Snapshot {
+    /// `status` and `body` as the expected output from now on.
+    pub fn record(status: &str, body: &str, ignored: Vec<String>, masking: &Masking) -> Self {
+        Snapshot {
+            status: status.to_string(),
+            body: masking.body(body),
+            ignored,
+            recorded: Utc::now().to_rfc3339(),
+        }
+    }
+
+    /// What a response with `status` and `body` does differently, one line
+    /// each; empty when it matches. JSON bodies are compared by value.
+    pub fn differences(&self, status: &str, body: &str, masking: &Masking) -> Vec<String> {
+        let side = |status: &str, body: String| DiffSide {
+            status: status.to_string(),
+            body,
+            ..Default::default()
+        };
+        let diff = diff::compare(
+            &side(&self.status, self.body.clone()),
+            &side(status, masking.body(body)),
+            &self.ignored,
+        );
+
+        let mut differences = Vec::new();
+        if let Some((old, new)) = diff.status {
+            differences.push(format!("status {} → {}", old, new));
+        }
+        match diff.json {
+            Some(changes) => differences.extend(changes.iter().map(|change| match change {
+                JsonChange::Added(path, _) => format!("{} added", path),
+                JsonChange::Removed(path, _) => format!("{} removed", path),
+                JsonChange::Changed(path, _, _) => format!("{} changed", path),
+            })),
+            None => {
+                let changed = diff
+                    .lines
+                    .iter()
+                    .filter(|(tag, _)| *tag != LineTag::Same)
+                    .count();
+                if changed > 0 {
+                    differences.push(format!("{} body lines differ", changed));
+                }
+            }
+        }
+        differences
+    }
+
+    /// The result reported next to the request's assertions.
+    pub fn check(&self, response: &ApiResponse, masking: &Masking) -> AssertionResult {
+        let differences = self.differences(&response.status, &response.body, masking);
+        let message = if differences.is_empty() {
+            format!("recorded {}", self.recorded)
+        } else {
+            let mut listed =
+                differences[..differences.len().min(config::SNAPSHOT_MAX_LISTED)].join(", ");
+            if differences.len() > config::SNAPSHOT_MAX_LISTED {
+                listed.push_str(&format!(
+                    " and {} more",
+                    differences.len() - config::SNAPSHOT_MAX_LISTED
+                ));
+            }
+            listed
+        };
+        AssertionResult {
+            description: CHECK.to_string(),
+            passed: differences.is_empty(),
+            message,
+        }
+    }
+}
+
+/// What is masked in a body before it is recorded: secret values and what
+/// the history redaction covers, since snapshots end up next to `.http`
+/// files. Responses are masked the same way before they are compared.
+#[derive(Debug, Clone, Default)]
+pub struct Masking {
+    secret_values: Vec<(String, String)>,
+    policy: RedactionPolicy,
+}
+
+impl Masking {
+    pub fn load(db: &Database) -> Result<Self, String> {
+        Ok(Masking {
+            secret_values: db.secret_values().map_err(|e| e.to_string())?,
+            policy: db.redaction_policy().map_err(|e| e.to_string())?,
+        })
+    }
+
+    pub fn body(&self, body: &str) -> String {
+        Redactor::new(&self.policy).body(&secrets::redact(body, &self.secret_values))
+    }
+}
+
+/// Where a request's snapshot is kept.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SnapshotStore {
+    /// A collection request, in the database
+    Item(i64),
+    /// A request of a `.http` file, by name, in the file's `sidecar_path`
+    File(PathBuf, String),
+}
+
+impl SnapshotStore {
+    /// Requests opened from a `.http` file share the file's snapshots, so
+    /// the app and `dispatch run file.http` check against the same ones.
+    pub fn for_item(db: &Database, item_id: i64) -> Result<Self, String> {
+        let item = db.get_collection_item(item_id).map_err(|e| e.to_string())?;
+        let source = db
+            .get_collection_source(item.collection_id)
+            .map_err(|e| e.to_string())?;
+        let path = source
+            .as_deref()
+            .and_then(|source| source.strip_prefix(http::COLLECTION_SOURCE));
+        let key = db.get_item_source_key(item_id).map_err(|e| e.to_string())?;
+        Ok(match (path, key) {
+            (Some(path), Some(key)) => SnapshotStore::File(sidecar_path(Path::new(path)), key),
+            _ => SnapshotStore::Item(item_id),
+        })
+    }
+
+    pub fn load(&self, db: &Database) -> Result<Option<Snapshot>, String> {
+        match self {
+            SnapshotStore::Item(id) => db.get_snapshot(*id).map_err(|e| e.to_string()),
+            SnapshotStore::File(path, name) => Ok(read_file(path)?.remove(name)),
+        }
+    }
+
+    /// Replaces the snapshot, or deletes it with `None`.
+    pub fn save(&self, db: &Database, snapshot: Option<&Snapshot>) -> Result<(), String> {
+        match self {
+            SnapshotStore::Item(id) => db.set_snapshot(*id, snapshot).map_err(|e| e.to_string()),
+            SnapshotStore::File(path, name) => {
+                let mut snapshots = read_file(path)?;
+                match snapshot {
+                    Some(snapshot) => snapshots.insert(name.clone(), snapshot.clone()),
+                    None => snapshots.remove(name),
+                };
+                write_file(path, &snapshots)
+            }
+        }
+    }
+}
+
+/// `api.http` keeps its snapshots in `api.snapshots.json`, by request name.
+pub fn sidecar_path(http_file: &Path) -> PathBuf {
+    http_file.with_extension("snapshots.json")
+}
+
+fn read_file(path: &Path) -> Result<BTreeMap<String, Snapshot>, String> {
+    match fs::read_to_string(path) {
+        Ok(text) => serde_json::from_str(&text).map_err(|e| format!("{}: {}", path.display(), e)),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(BTreeMap::new()),
+        Err(e) => Err(format!("{}: {}", path.display(), e)),
+    }
+}
+
+/// Removes the file once it holds no snapshots.
+fn write_file(path: &Path, snapshots: &BTreeMap<String, Snapshot>) -> Result<(), String> {
+    let result = if snapshots.is_empty() {
+        match fs::remove_file(path) {
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
+            other => other,
+        }
+    } else {
+        let text = serde_json::to_string_pretty(snapshots).unwrap_or_default();
+        fs::write(path, text + "\n")
+    };
+    result.map_err(|e| format!("{}: {}", path.display(), e))
+}
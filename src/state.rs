@@ -1,5 +1,43 @@
 use crate::api::{ApiError, ApiResponse};
-use crate::models::Method;
+use crate::database::{HistoryFilter, HistoryGrouping, MaintenanceReport, RetentionPolicy};
+use crate::diff::DiffSide;
+use crate::formats::{data::DataSet, report::ReportFormat};
+use crate::models::{
+    Assertion, ContractLink, Environment, Extractor, Method, Request, RequestOptions, Scripts,
+};
+use crate::redaction::RedactionPolicy;
+use crate::runner::{RunReport, RunStep};
+use std::path::PathBuf;
+use std::sync::{Arc, atomic::AtomicBool};
+
+/// Session state the widgets cannot hold themselves.
+#[derive(Debug, Default)]
+pub struct AppState {
+    pub environments: Vec<Environment>,
+    pub active_environment: Option<i64>,
+    /// Collection of the loaded request, whose variables apply when sending
+    pub active_collection: Option<i64>,
+    /// Saved request being edited, if any
+    pub active_item: Option<i64>,
+    /// Collection, and optionally folder, the runner dialog is set up for
+    pub run_target: Option<(i64, Option<i64>)>,
+    /// Set while a run is in progress; storing `true` stops it
+    pub run_cancel: Option<Arc<AtomicBool>>,
+    /// Rows for the next run, one pass each
+    pub run_data: Option<DataSet>,
+    /// Requests the current run will send, across all iterations
+    pub run_total: usize,
+    pub run_done: usize,
+    pub last_report: Option<RunReport>,
+    /// Sent again once secrets are unlocked, after it asked for the passphrase
+    pub after_unlock: Option<Action>,
+    /// Set while retention is being enforced in the background
+    pub maintaining: bool,
+    /// Whether to enforce it again once that finishes
+    pub maintain_again: bool,
+    /// What the response view shows, for comparing history with
+    pub shown_response: Option<DiffSide>,
+}
 
 #[derive(Debug, Clone)]
 pub enum Action {
@@ -8,15 +46,100 @@ pub enum Action {
     UpdateMethod(Method),
     UpdateBody(String),
     UpdateHeaders(Vec<(String, String)>),
+    UpdateOptions(RequestOptions),
+    UpdateContract(Option<ContractLink>),
+    UpdateAssertions(Vec<Assertion>),
+    UpdateExtractors(Vec<Extractor>),
+    UpdateScripts(Scripts),
+    ImportCurl(String),
+    SelectEnvironment(Option<i64>),
 
     // Commands
     SendRequest,
     NewRequest,
     ClearHistory,
     LoadHistoryItem(i64),
+    LoadCollectionItem(i64),
+    SaveRequest,
+    GenerateCode,
+    GenerateCodeForHistory(i64),
+    ExportHar(Option<i64>),
+    ExportHarTo(Option<i64>, PathBuf),
+    ImportHar,
+    ImportHarFrom(PathBuf),
+    EditHistoryPreferences,
+    SaveRedactionPolicy(RedactionPolicy),
+    SaveRetentionPolicy(RetentionPolicy),
+    ScrubHistory(RedactionPolicy),
+    RunMaintenance,
+    TogglePinHistoryItem(i64),
+    RenameHistoryItem(i64),
+    SetHistoryLabel(i64, String),
+    AnnotateHistoryItem(i64),
+    SetHistoryNotes(i64, String, Vec<String>),
+    CopyHistoryUrl(i64),
+    DuplicateHistoryItem(i64),
+    /// Deletes the selected entries when this one is among them
+    DeleteHistoryItem(i64),
+    DeleteSelectedHistory,
+    CompareWithResponse(i64),
+    /// Compares the two selected entries, older first
+    CompareSelectedHistory,
+    SaveDiffIgnored(Vec<String>),
+    RestoreHistory(i64),
+    PurgeHistory(i64),
+    /// The host filter is replaced by the URL bar's host when the search
+    /// follows it
+    SearchHistory(HistoryFilter, HistoryGrouping),
+    ImportPostmanCollection,
+    ImportPostmanCollectionFrom(PathBuf),
+    ExportPostmanCollection(i64),
+    ExportPostmanCollectionTo(i64, PathBuf),
+    DeleteCollection(i64),
+    ImportOpenApi,
+    ImportOpenApiFrom(PathBuf),
+    OpenHttpFile,
+    OpenHttpFileFrom(PathBuf),
+    ExportHttpCollection(i64),
+    ExportHttpCollectionTo(i64, PathBuf),
+    ImportPostmanEnvironment,
+    ImportPostmanEnvironmentFrom(PathBuf),
+    ExportPostmanEnvironment,
+    ExportPostmanEnvironmentTo(i64, PathBuf),
+    DeleteEnvironment,
+    NewEnvironment,
+    EditEnvironment,
+    SaveEnvironment(Environment),
+    PromptUnlockSecrets,
+    UnlockSecrets(String),
+    LinkOpenApiContract,
+    LinkOpenApiContractFrom(PathBuf),
+    LinkJsonSchemaContract,
+    /// Keeps the shown response as the saved request's snapshot
+    RecordSnapshot,
+    DeleteSnapshot,
+    CompareWithSnapshot,
+    SetSnapshotIgnored(Vec<String>),
+    RunCollection(i64),
+    RunItem(i64),
+    ChooseRunData,
+    LoadRunData(PathBuf),
+    ClearRunData,
+    StartRun,
+    StopRun,
+    ExportRunReport(ReportFormat),
+    ExportRunReportTo(ReportFormat, PathBuf),
+    ExportFailedRows,
+    ExportFailedRowsTo(PathBuf),
+    /// Records the last run's responses as the new snapshots
+    AcceptRunSnapshots,
 
     // Async
     RequestStarted,
-    RequestCompleted(Result<ApiResponse, ApiError>),
-    HistorySaved(i64),
+    RequestCompleted(Request, Result<ApiResponse, ApiError>),
+    HistorySaved,
+    RunStepCompleted(RunStep),
+    RunFinished(RunReport),
+    MaintenanceFinished(Result<MaintenanceReport, String>),
+    ShowToast(String),
 }
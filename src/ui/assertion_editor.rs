@@ -0,0 +1,166 @@
+use gtk::{Box, Button, DropDown, Entry, ScrolledWindow, StringList, prelude::*};
+use std::{cell::RefCell, rc::Rc};
+
+use crate::{
+    config,
+    models::{Assertion, AssertionKind},
+    ui::helpers::add_box_margins,
+};
+
+#[derive(Clone)]
+pub struct AssertionRow {
+    pub container: Box,
+    pub kind_dropdown: DropDown,
+    pub target_entry: Entry,
+    pub expected_entry: Entry,
+}
+
+/// The request "Tests" tab: a list of assertions checked against each response.
+#[derive(Clone)]
+pub struct AssertionEditor {
+    pub container: Box,
+    pub rows_box: Box,
+
+    pub rows: Rc<RefCell<Vec<AssertionRow>>>,
+}
+
+impl AssertionEditor {
+    pub fn new() -> Self {
+        let container = Box::new(gtk::Orientation::Vertical, config::SPACING_NONE);
+
+        let rows_box = Box::new(gtk::Orientation::Vertical, config::SPACING_EXTRA_SMALL);
+        add_box_margins(&rows_box, config::SPACING_MEDIUM);
+
+        let scrolled = ScrolledWindow::builder()
+            .hscrollbar_policy(gtk::PolicyType::Never)
+            .child(&rows_box)
+            .vexpand(true)
+            .build();
+
+        container.append(&scrolled);
+
+        let add_btn = Button::with_label("Add Assertion");
+        add_btn.add_css_class("suggested-action");
+        add_btn.set_margin_bottom(config::SPACING_MEDIUM);
+        add_btn.set_margin_start(config::SPACING_MEDIUM);
+        add_btn.set_margin_end(config::SPACING_MEDIUM);
+
+        container.append(&add_btn);
+
+        let editor = AssertionEditor {
+            container,
+            rows_box,
+            rows: Rc::new(RefCell::new(Vec::new())),
+        };
+
+        let editor_clone = editor.clone();
+        add_btn.connect_clicked(move |_| editor_clone.add_row(&Assertion::default()));
+
+        editor
+    }
+
+    pub fn add_row(&self, assertion: &Assertion) {
+        let row_container = Box::new(gtk::Orientation::Horizontal, config::SPACING_EXTRA_SMALL);
+
+        let labels: Vec<&str> = AssertionKind::ALL.iter().map(|k| k.label()).collect();
+        let kind_dropdown = DropDown::new(Some(StringList::new(&labels)), gtk::Expression::NONE);
+        kind_dropdown.set_selected(assertion.kind.to_index());
+
+        let target_entry = Entry::builder()
+            .hexpand(true)
+            .text(assertion.target.as_str())
+            .build();
+
+        let expected_entry = Entry::builder()
+            .hexpand(true)
+            .text(assertion.expected.as_str())
+            .build();
+
+        let del_btn = Button::builder()
+            .icon_name("user-trash-symbolic")
+            .css_classes(vec!["flat".to_string()])
+            .build();
+
+        row_container.append(&kind_dropdown);
+        row_container.append(&target_entry);
+        row_container.append(&expected_entry);
+        row_container.append(&del_btn);
+
+        self.rows_box.append(&row_container);
+
+        let row_data = AssertionRow {
+            container: row_container.clone(),
+            kind_dropdown: kind_dropdown.clone(),
+            target_entry,
+            expected_entry,
+        };
+        update_fields(&row_data);
+
+        let row_ref = row_data.clone();
+        kind_dropdown.connect_selected_notify(move |_| update_fields(&row_ref));
+
+        self.rows.borrow_mut().push(row_data);
+
+        let rows_ref = self.rows.clone();
+        let rows_box_ref = self.rows_box.clone();
+        let container_ref = row_container.clone();
+
+        del_btn.connect_clicked(move |_| {
+            rows_box_ref.remove(&container_ref);
+
+            rows_ref
+                .borrow_mut()
+                .retain(|row| row.container != container_ref)
+        });
+    }
+
+    pub fn get_data(&self) -> Vec<Assertion> {
+        self.rows
+            .borrow()
+            .iter()
+            .map(|row| Assertion {
+                kind: AssertionKind::from_index(row.kind_dropdown.selected()),
+                target: row.target_entry.text().to_string(),
+                expected: row.expected_entry.text().to_string(),
+            })
+            .collect()
+    }
+
+    pub fn set_data(&self, data: Vec<Assertion>) {
+        let mut rows = self.rows.borrow_mut();
+
+        for row in rows.iter() {
+            self.rows_box.remove(&row.container);
+        }
+
+        rows.clear();
+        drop(rows);
+
+        for assertion in &data {
+            self.add_row(assertion);
+        }
+    }
+
+    pub fn clear(&self) {
+        self.set_data(Vec::new());
+    }
+}
+
+/// Shows only the entries the selected kind uses, with a hint for each.
+fn update_fields(row: &AssertionRow) {
+    let (target, expected) = match AssertionKind::from_index(row.kind_dropdown.selected()) {
+        AssertionKind::StatusEquals => (None, Some("200")),
+        AssertionKind::StatusInRange => (None, Some("200-299 or 2xx")),
+        AssertionKind::HeaderExists => (Some("Header name"), None),
+        AssertionKind::HeaderMatches => (Some("Header name"), Some("Regular expression")),
+        AssertionKind::JsonPathEquals => (Some("$.data.id"), Some("Expected JSON value")),
+        AssertionKind::JsonPathContains => (Some("$.items[*].name"), Some("Value")),
+        AssertionKind::MatchesSchema => (Some("/path/to/schema.json"), None),
+        AssertionKind::TimeBelow => (None, Some("Milliseconds")),
+    };
+
+    for (entry, placeholder) in [(&row.target_entry, target), (&row.expected_entry, expected)] {
+        entry.set_visible(placeholder.is_some());
+        entry.set_placeholder_text(placeholder);
+    }
+}
@@ -0,0 +1,74 @@
+use adw::{Dialog, HeaderBar, ToolbarView, prelude::*};
+use gtk::{Button, DropDown, ScrolledWindow, StringList};
+use sourceview5::prelude::*;
+use sourceview5::{Buffer, LanguageManager, View};
+
+use crate::{
+    codegen::{self, Language},
+    config,
+    models::Request,
+    ui::helpers::set_editor_scheme,
+};
+
+pub fn present(parent: &impl IsA<gtk::Widget>, request: Request) {
+    let labels: Vec<&str> = Language::ALL.iter().map(|l| l.label()).collect();
+    let language_dropdown = DropDown::new(Some(StringList::new(&labels)), gtk::Expression::NONE);
+
+    let copy_button = Button::builder()
+        .icon_name("edit-copy-symbolic")
+        .tooltip_text("Copy to Clipboard")
+        .build();
+
+    let header = HeaderBar::new();
+    header.pack_start(&language_dropdown);
+    header.pack_end(&copy_button);
+
+    let buffer = Buffer::new(None);
+    set_editor_scheme(&buffer);
+
+    let view = View::with_buffer(&buffer);
+    view.set_monospace(true);
+    view.set_editable(false);
+    view.set_top_margin(config::SPACING_MEDIUM);
+    view.set_bottom_margin(config::SPACING_MEDIUM);
+    view.set_left_margin(config::SPACING_MEDIUM);
+
+    let scrolled_window = ScrolledWindow::builder()
+        .hscrollbar_policy(gtk::PolicyType::Automatic)
+        .vscrollbar_policy(gtk::PolicyType::Automatic)
+        .child(&view)
+        .vexpand(true)
+        .build();
+
+    let toolbar_view = ToolbarView::new();
+    toolbar_view.add_top_bar(&header);
+    toolbar_view.set_content(Some(&scrolled_window));
+
+    let dialog = Dialog::builder()
+        .title("Generate Code")
+        .content_width(config::DIALOG_DEFAULT_WIDTH)
+        .content_height(config::DIALOG_DEFAULT_HEIGHT)
+        .child(&toolbar_view)
+        .build();
+
+    render(&buffer, Language::ALL[0], &request);
+
+    language_dropdown.connect_selected_notify(glib::clone!(@strong buffer => move |dropdown| {
+        if let Some(language) = Language::ALL.get(dropdown.selected() as usize) {
+            render(&buffer, *language, &request);
+        }
+    }));
+
+    copy_button.connect_clicked(glib::clone!(@strong buffer => move |button| {
+        let (start, end) = buffer.bounds();
+        button.clipboard().set_text(&buffer.text(&start, &end, false));
+    }));
+
+    dialog.present(Some(parent));
+}
+
+fn render(buffer: &Buffer, language: Language, request: &Request) {
+    let lang = LanguageManager::default().language(language.syntax_id());
+    buffer.set_language(lang.as_ref());
+    buffer.set_text(&codegen::generate(language, request));
+}
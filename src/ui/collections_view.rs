@@ -0,0 +1,217 @@
+use gtk::{
+    Box, GestureClick, Image, Label, ListBox, ListBoxRow, MenuButton, Orientation, PopoverMenu,
+    ScrolledWindow, gdk, gio, glib, prelude::*,
+};
+use std::collections::HashMap;
+
+use crate::{
+    config,
+    models::{Collection, CollectionItem, ItemKind},
+    ui::helpers::add_box_margins,
+};
+
+// Row names tell activation handlers what kind of row was clicked
+const COLLECTION_ROW_PREFIX: &str = "collection:";
+const ITEM_ROW_PREFIX: &str = "item:";
+
+pub enum RowTarget {
+    /// A collection's header, whose actions carry its id themselves
+    Collection,
+    Item(i64),
+}
+
+pub fn build() -> (Box, ListBox) {
+    let container = Box::new(Orientation::Vertical, config::SPACING_NONE);
+
+    let toolbar = Box::new(Orientation::Horizontal, config::SPACING_SMALL);
+    add_box_margins(&toolbar, config::SPACING_MEDIUM);
+
+    let label = Label::new(Some("Collections"));
+    label.add_css_class("heading");
+    label.set_hexpand(true);
+    label.set_xalign(0.0);
+
+    let menu = gio::Menu::new();
+    menu.append(
+        Some("Import Postman Collection…"),
+        Some("collections.import-postman"),
+    );
+    menu.append(
+        Some("Import OpenAPI Specification…"),
+        Some("collections.import-openapi"),
+    );
+    menu.append(Some("Open .http File…"), Some("collections.open-http"));
+
+    let menu_btn = MenuButton::builder()
+        .icon_name("view-more-symbolic")
+        .css_classes(vec!["flat".to_string()])
+        .tooltip_text("Collections Menu")
+        .menu_model(&menu)
+        .build();
+
+    toolbar.append(&label);
+    toolbar.append(&menu_btn);
+
+    let list = ListBox::new();
+    list.add_css_class("navigation-sidebar");
+
+    let scrolled = ScrolledWindow::builder()
+        .hscrollbar_policy(gtk::PolicyType::Never)
+        .child(&list)
+        .vexpand(true)
+        .build();
+
+    container.append(&toolbar);
+    container.append(&scrolled);
+
+    (container, list)
+}
+
+pub fn row_target(row: &ListBoxRow) -> Option<RowTarget> {
+    let name = row.widget_name();
+    if name.starts_with(COLLECTION_ROW_PREFIX) {
+        Some(RowTarget::Collection)
+    } else if let Some(id) = name.strip_prefix(ITEM_ROW_PREFIX) {
+        id.parse().ok().map(RowTarget::Item)
+    } else {
+        None
+    }
+}
+
+pub fn clear(list: &ListBox) {
+    while let Some(row) = list.first_child() {
+        list.remove(&row);
+    }
+}
+
+/// Appends a collection header followed by its items, indented by depth.
+pub fn add_collection(list: &ListBox, collection: &Collection, items: &[CollectionItem]) {
+    let row = ListBoxRow::new();
+    let row_box = Box::new(Orientation::Horizontal, config::SPACING_MEDIUM);
+    add_box_margins(&row_box, config::SPACING_MEDIUM);
+
+    let name_label = Label::new(Some(collection.name.as_str()));
+    name_label.add_css_class("heading");
+    name_label.set_ellipsize(gtk::pango::EllipsizeMode::End);
+    name_label.set_xalign(0.0);
+
+    row_box.append(&Image::from_icon_name("folder-symbolic"));
+    row_box.append(&name_label);
+    row.set_child(Some(&row_box));
+    row.set_widget_name(&format!("{}{}", COLLECTION_ROW_PREFIX, collection.id));
+
+    attach_context_menu(&row, collection_menu(collection.id));
+    list.append(&row);
+
+    // Items arrive depth first, so a parent's depth is known before its children
+    let mut depths: HashMap<i64, i32> = HashMap::new();
+    for item in items {
+        let depth = item
+            .parent_id
+            .and_then(|parent| depths.get(&parent))
+            .map_or(1, |depth| depth + 1);
+        depths.insert(item.id, depth);
+        add_item_row(list, item, depth);
+    }
+}
+
+fn add_item_row(list: &ListBox, item: &CollectionItem, depth: i32) {
+    let row = ListBoxRow::new();
+    let row_box = Box::new(Orientation::Horizontal, config::SPACING_MEDIUM);
+    add_box_margins(&row_box, config::SPACING_MEDIUM);
+    row_box.set_margin_start(config::SPACING_MEDIUM * (depth + 1));
+
+    match item.kind {
+        ItemKind::Folder => {
+            row_box.append(&Image::from_icon_name("folder-symbolic"));
+            // Folders only group requests
+            row.set_activatable(false);
+            attach_context_menu(&row, folder_menu(item.id));
+        }
+        ItemKind::Request => {
+            let method = item.request.method.as_str();
+            let method_label = Label::new(Some(method));
+            method_label.add_css_class(config::get_badge_class(method));
+            row_box.append(&method_label);
+            attach_context_menu(&row, request_menu(item.id));
+        }
+    }
+
+    let name_label = Label::new(Some(item.name.as_str()));
+    name_label.set_ellipsize(gtk::pango::EllipsizeMode::End);
+    name_label.set_xalign(0.0);
+    row_box.append(&name_label);
+
+    row.set_child(Some(&row_box));
+    row.set_widget_name(&format!("{}{}", ITEM_ROW_PREFIX, item.id));
+    if !item.request.url.is_empty() {
+        row.set_tooltip_text(Some(item.request.url.as_str()));
+    }
+
+    list.append(&row);
+}
+
+fn attach_context_menu(row: &ListBoxRow, menu: gio::Menu) {
+    let gesture = GestureClick::new();
+    gesture.set_button(gdk::BUTTON_SECONDARY);
+    gesture.connect_pressed(move |gesture, _, x, y| {
+        let Some(row) = gesture.widget() else {
+            return;
+        };
+
+        let popover = PopoverMenu::from_model(Some(&menu));
+        popover.set_parent(&row);
+        popover.set_has_arrow(false);
+        popover.set_pointing_to(Some(&gdk::Rectangle::new(x as i32, y as i32, 1, 1)));
+        popover.connect_closed(|popover| {
+            let popover = popover.clone();
+            glib::idle_add_local_once(move || popover.unparent());
+        });
+        popover.popup();
+    });
+    row.add_controller(gesture);
+}
+
+// Items target the `collections` action group installed on the window
+fn collection_menu(id: i64) -> gio::Menu {
+    let menu = gio::Menu::new();
+
+    let run = gio::MenuItem::new(Some("Run…"), None);
+    run.set_action_and_target_value(Some("collections.run"), Some(&id.to_variant()));
+    menu.append_item(&run);
+
+    let export = gio::MenuItem::new(Some("Export as Postman…"), None);
+    export.set_action_and_target_value(Some("collections.export-postman"), Some(&id.to_variant()));
+    menu.append_item(&export);
+
+    let export_http = gio::MenuItem::new(Some("Export as .http…"), None);
+    export_http
+        .set_action_and_target_value(Some("collections.export-http"), Some(&id.to_variant()));
+    menu.append_item(&export_http);
+
+    let delete = gio::MenuItem::new(Some("Delete"), None);
+    delete.set_action_and_target_value(Some("collections.delete"), Some(&id.to_variant()));
+    menu.append_item(&delete);
+
+    menu
+}
+
+fn folder_menu(id: i64) -> gio::Menu {
+    let menu = gio::Menu::new();
+
+    let run = gio::MenuItem::new(Some("Run Folder…"), None);
+    run.set_action_and_target_value(Some("collections.run-item"), Some(&id.to_variant()));
+    menu.append_item(&run);
+
+    menu
+}
+
+fn request_menu(id: i64) -> gio::Menu {
+    let menu = gio::Menu::new();
+
+    let run = gio::MenuItem::new(Some("Run…"), None);
+    run.set_action_and_target_value(Some("collections.run-item"), Some(&id.to_variant()));
+    menu.append_item(&run);
+
+    menu
+}
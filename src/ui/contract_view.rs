@@ -0,0 +1,185 @@
+use adw::{AlertDialog, prelude::*};
+use gtk::{
+    Box, DropDown, Label, ListBox, MenuButton, Orientation, ScrolledWindow, StringList, gio,
+};
+use std::{cell::RefCell, rc::Rc};
+
+use crate::{config, models::ContractLink, schema::Violation, ui::helpers::add_box_margins};
+
+/// The "Contract" response tab: which contract the request is linked to,
+/// and what the last response violated.
+#[derive(Clone)]
+pub struct ContractView {
+    pub container: Box,
+    link_label: Label,
+    summary_label: Label,
+    violations_list: ListBox,
+    link: Rc<RefCell<Option<ContractLink>>>,
+}
+
+impl ContractView {
+    pub fn new() -> Self {
+        let container = Box::new(Orientation::Vertical, config::SPACING_NONE);
+
+        let toolbar = Box::new(Orientation::Horizontal, config::SPACING_SMALL);
+        add_box_margins(&toolbar, config::SPACING_MEDIUM);
+
+        let link_label = Label::new(None);
+        link_label.set_hexpand(true);
+        link_label.set_xalign(0.0);
+        link_label.set_ellipsize(gtk::pango::EllipsizeMode::Middle);
+
+        // Items target the `contract` action group installed on the window
+        let menu = gio::Menu::new();
+        menu.append(
+            Some("Link OpenAPI Operation…"),
+            Some("contract.link-openapi"),
+        );
+        menu.append(Some("Link JSON Schema…"), Some("contract.link-schema"));
+        menu.append(Some("Remove Link"), Some("contract.unlink"));
+
+        let menu_btn = MenuButton::builder()
+            .icon_name("view-more-symbolic")
+            .css_classes(vec!["flat".to_string()])
+            .tooltip_text("Contract Menu")
+            .menu_model(&menu)
+            .build();
+
+        toolbar.append(&link_label);
+        toolbar.append(&menu_btn);
+
+        let summary_label = Label::new(None);
+        summary_label.set_xalign(0.0);
+        summary_label.set_wrap(true);
+        summary_label.set_margin_start(config::SPACING_MEDIUM);
+        summary_label.set_margin_end(config::SPACING_MEDIUM);
+
+        let violations_list = ListBox::new();
+        violations_list.set_selection_mode(gtk::SelectionMode::None);
+        violations_list.set_margin_start(config::SPACING_MEDIUM);
+        violations_list.set_margin_end(config::SPACING_MEDIUM);
+        violations_list.set_margin_bottom(config::SPACING_MEDIUM);
+
+        let scrolled = ScrolledWindow::builder()
+            .hscrollbar_policy(gtk::PolicyType::Never)
+            .child(&violations_list)
+            .vexpand(true)
+            .build();
+
+        container.append(&toolbar);
+        container.append(&summary_label);
+        container.append(&scrolled);
+
+        let view = ContractView {
+            container,
+            link_label,
+            summary_label,
+            violations_list,
+            link: Rc::new(RefCell::new(None)),
+        };
+        view.set_link(None);
+        view
+    }
+
+    pub fn get_link(&self) -> Option<ContractLink> {
+        self.link.borrow().clone()
+    }
+
+    pub fn set_link(&self, link: Option<ContractLink>) {
+        let text = match &link {
+            Some(ContractLink::OpenApi { spec, operation }) => {
+                format!("{} in {}", operation, file_name(spec))
+            }
+            Some(ContractLink::JsonSchema { path }) => format!("Schema {}", file_name(path)),
+            None => "No contract linked".to_string(),
+        };
+        self.link_label.set_text(&text);
+        *self.link.borrow_mut() = link;
+        self.clear_result();
+    }
+
+    pub fn clear_result(&self) {
+        self.summary_label.set_text("");
+        self.summary_label.remove_css_class(config::CLASS_SUCCESS);
+        self.summary_label.remove_css_class(config::CLASS_ERROR);
+        while let Some(row) = self.violations_list.first_child() {
+            self.violations_list.remove(&row);
+        }
+    }
+
+    pub fn show_result(&self, result: &Result<Vec<Violation>, String>) {
+        self.clear_result();
+
+        match result {
+            Ok(violations) if violations.is_empty() => {
+                self.summary_label.set_text("Response matches the contract");
+                self.summary_label.add_css_class(config::CLASS_SUCCESS);
+            }
+            Ok(violations) => {
+                self.summary_label
+                    .set_text(&format!("{} contract violations", violations.len()));
+                self.summary_label.add_css_class(config::CLASS_ERROR);
+
+                for violation in violations {
+                    let row = Box::new(Orientation::Horizontal, config::SPACING_MEDIUM);
+                    add_box_margins(&row, config::SPACING_EXTRA_SMALL);
+
+                    let pointer = Label::new(Some(violation.pointer.as_str()));
+                    pointer.add_css_class("monospace");
+                    pointer.set_xalign(0.0);
+                    pointer.set_selectable(true);
+
+                    let message = Label::new(Some(violation.message.as_str()));
+                    message.set_xalign(0.0);
+                    message.set_wrap(true);
+                    message.set_hexpand(true);
+
+                    row.append(&pointer);
+                    row.append(&message);
+                    self.violations_list.append(&row);
+                }
+            }
+            Err(e) => {
+                self.summary_label
+                    .set_text(&format!("Could not check the contract: {}", e));
+                self.summary_label.add_css_class(config::CLASS_ERROR);
+            }
+        }
+    }
+}
+
+/// Asks which operation of a spec to link to.
+pub fn choose_operation(
+    parent: &impl IsA<gtk::Widget>,
+    operations: Vec<String>,
+    on_chosen: impl Fn(String) + 'static,
+) {
+    let labels: Vec<&str> = operations.iter().map(String::as_str).collect();
+    let dropdown = DropDown::new(Some(StringList::new(&labels)), gtk::Expression::NONE);
+
+    let dialog = AlertDialog::builder()
+        .heading("Link OpenAPI Operation")
+        .body("Responses to this request will be checked against the operation.")
+        .extra_child(&dropdown)
+        .build();
+    dialog.add_response("cancel", "Cancel");
+    dialog.add_response("link", "Link");
+    dialog.set_response_appearance("link", adw::ResponseAppearance::Suggested);
+    dialog.set_default_response(Some("link"));
+
+    dialog.connect_response(None, move |_, response| {
+        if response == "link"
+            && let Some(operation) = operations.get(dropdown.selected() as usize)
+        {
+            on_chosen(operation.clone());
+        }
+    });
+
+    dialog.present(Some(parent));
+}
+
+fn file_name(path: &std::path::Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.display().to_string())
+}
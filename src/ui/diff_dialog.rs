@@ -0,0 +1,224 @@
+use adw::{
+    ActionRow, Bin, Dialog, HeaderBar, PreferencesGroup, PreferencesPage, StatusPage, ToolbarView,
+    ViewStack, ViewSwitcher, prelude::*,
+};
+use gtk::{Box, Entry, Orientation, ScrolledWindow, TextBuffer, TextTag, TextView};
+
+use crate::{
+    config,
+    diff::{self, DiffSide, JsonChange, LineTag, ResponseDiff},
+    ui::helpers::add_box_margins,
+};
+
+/// Compares `before` with `after`. The ignored paths and headers can be
+/// edited in the dialog; `on_close` gets them as left.
+pub fn present(
+    parent: &impl IsA<gtk::Widget>,
+    before: DiffSide,
+    after: DiffSide,
+    ignored: Vec<String>,
+    on_close: impl Fn(Vec<String>) + 'static,
+) {
+    let ignore_entry = Entry::builder()
+        .text(ignored.join(", "))
+        .placeholder_text("Ignore, e.g. $..updated_at, date")
+        .tooltip_text("JSONPath expressions and header names, separated by commas")
+        .hexpand(true)
+        .build();
+    let ignore_bar = Box::new(Orientation::Horizontal, config::SPACING_SMALL);
+    add_box_margins(&ignore_bar, config::SPACING_SMALL);
+    ignore_bar.append(&ignore_entry);
+
+    let summary = Bin::new();
+    let body = Bin::new();
+    let lines = TextBuffer::new(None);
+    for (name, color) in [
+        ("removed", config::DIFF_REMOVED_COLOR),
+        ("added", config::DIFF_ADDED_COLOR),
+    ] {
+        let tag = TextTag::builder()
+            .name(name)
+            .paragraph_background(color)
+            .build();
+        lines.tag_table().add(&tag);
+    }
+    let lines_view = TextView::builder()
+        .buffer(&lines)
+        .monospace(true)
+        .editable(false)
+        .top_margin(config::SPACING_MEDIUM)
+        .bottom_margin(config::SPACING_MEDIUM)
+        .left_margin(config::SPACING_MEDIUM)
+        .build();
+    let lines_scrolled = ScrolledWindow::builder()
+        .child(&lines_view)
+        .vexpand(true)
+        .build();
+
+    let stack = ViewStack::new();
+    stack
+        .add_titled(&summary, Some("summary"), "Summary")
+        .set_icon_name(Some("view-list-symbolic"));
+    stack
+        .add_titled(&body, Some("body"), "JSON")
+        .set_icon_name(Some("text-x-generic-symbolic"));
+    stack
+        .add_titled(&lines_scrolled, Some("lines"), "Lines")
+        .set_icon_name(Some("format-justify-left-symbolic"));
+
+    let switcher = ViewSwitcher::builder()
+        .stack(&stack)
+        .policy(adw::ViewSwitcherPolicy::Wide)
+        .build();
+    let header = HeaderBar::builder().title_widget(&switcher).build();
+
+    let toolbar_view = ToolbarView::new();
+    toolbar_view.add_top_bar(&header);
+    toolbar_view.add_top_bar(&ignore_bar);
+    toolbar_view.set_content(Some(&stack));
+
+    let dialog = Dialog::builder()
+        .title("Compare Responses")
+        .content_width(config::DIALOG_DEFAULT_WIDTH)
+        .content_height(config::DIALOG_DEFAULT_HEIGHT)
+        .child(&toolbar_view)
+        .build();
+
+    // Takes the entry rather than holding it, which would keep both alive
+    let refresh = move |entry: &Entry| {
+        let result = diff::compare(&before, &after, &ignored_list(entry));
+        summary.set_child(Some(&summary_page(&before, &after, &result)));
+        body.set_child(Some(&json_page(&result)));
+        show_lines(&lines, &result);
+    };
+    refresh(&ignore_entry);
+    ignore_entry.connect_changed(refresh);
+
+    dialog.connect_closed(move |_| on_close(ignored_list(&ignore_entry)));
+
+    dialog.present(Some(parent));
+}
+
+fn ignored_list(entry: &Entry) -> Vec<String> {
+    entry
+        .text()
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+fn row(title: &str, subtitle: &str) -> ActionRow {
+    ActionRow::builder()
+        .title(title)
+        .subtitle(subtitle)
+        .use_markup(false)
+        .subtitle_selectable(true)
+        .build()
+}
+
+fn summary_page(before: &DiffSide, after: &DiffSide, diff: &ResponseDiff) -> PreferencesPage {
+    let response_group = PreferencesGroup::builder()
+        .title("Response")
+        .description(format!("{} → {}", before.title, after.title))
+        .build();
+
+    let status = match &diff.status {
+        Some((old, new)) => format!("{} → {}", old, new),
+        None => format!("{} (same)", after.status),
+    };
+    response_group.add(&row("Status", &status));
+
+    let time = match diff.time_ms {
+        Some(delta) => format!("{} → {} ({:+.0} ms)", before.time, after.time, delta),
+        None => format!("{} → {}", before.time, after.time),
+    };
+    response_group.add(&row("Time", &time));
+
+    let size = match diff.size_bytes {
+        Some(delta) => format!("{} → {} ({:+} bytes)", before.size, after.size, delta),
+        None => format!("{} → {}", before.size, after.size),
+    };
+    response_group.add(&row("Size", &size));
+
+    let headers_group = PreferencesGroup::builder().title("Headers").build();
+    if diff.headers.is_empty() {
+        headers_group.set_description(Some("No differences"));
+    }
+    for change in &diff.headers {
+        let subtitle = format!(
+            "{} → {}",
+            change.before.as_deref().unwrap_or("(none)"),
+            change.after.as_deref().unwrap_or("(none)")
+        );
+        headers_group.add(&row(&change.name, &subtitle));
+    }
+
+    let page = PreferencesPage::new();
+    page.add(&response_group);
+    page.add(&headers_group);
+    page
+}
+
+fn json_page(diff: &ResponseDiff) -> gtk::Widget {
+    let Some(changes) = &diff.json else {
+        return StatusPage::builder()
+            .icon_name("text-x-generic-symbolic")
+            .title("Not JSON")
+            .description("Both bodies must be JSON; the Lines tab compares them as text")
+            .build()
+            .upcast();
+    };
+    if changes.is_empty() {
+        return StatusPage::builder()
+            .icon_name("object-select-symbolic")
+            .title("No Differences")
+            .build()
+            .upcast();
+    }
+
+    let group = PreferencesGroup::builder()
+        .description("Key order is ignored")
+        .build();
+    for change in changes {
+        let subtitle = match change {
+            JsonChange::Added(_, value) => format!("added {}", short_json(value)),
+            JsonChange::Removed(_, value) => format!("removed {}", short_json(value)),
+            JsonChange::Changed(_, old, new) => {
+                format!("{} → {}", short_json(old), short_json(new))
+            }
+        };
+        group.add(&row(change.path(), &subtitle));
+    }
+
+    let page = PreferencesPage::new();
+    page.add(&group);
+    page.upcast()
+}
+
+/// One line of compact JSON, cut short when long.
+fn short_json(value: &serde_json::Value) -> String {
+    let text = value.to_string();
+    match text.char_indices().nth(config::DIFF_VALUE_MAX_CHARS) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text,
+    }
+}
+
+fn show_lines(buffer: &TextBuffer, diff: &ResponseDiff) {
+    buffer.set_text("");
+    for (tag, line) in &diff.lines {
+        let (prefix, tag_name) = match tag {
+            LineTag::Same => ("  ", None),
+            LineTag::Removed => ("- ", Some("removed")),
+            LineTag::Added => ("+ ", Some("added")),
+        };
+        let text = format!("{}{}\n", prefix, line);
+        let mut end = buffer.end_iter();
+        match tag_name {
+            Some(name) => buffer.insert_with_tags_by_name(&mut end, &text, &[name]),
+            None => buffer.insert(&mut end, &text),
+        }
+    }
+}
@@ -0,0 +1,55 @@
+use crate::config;
+use crate::models::Environment;
+use gtk::prelude::*;
+use gtk::{Box, DropDown, MenuButton, Orientation, StringList, gio};
+
+pub const NO_ENVIRONMENT: &str = "No Environment";
+
+pub fn build() -> (Box, DropDown) {
+    let container = Box::new(Orientation::Horizontal, config::SPACING_EXTRA_SMALL);
+
+    let environment_dropdown = DropDown::new(
+        Some(StringList::new(&[NO_ENVIRONMENT])),
+        gtk::Expression::NONE,
+    );
+    environment_dropdown.set_tooltip_text(Some("Active Environment"));
+
+    // Items target the `environments` action group installed on the window
+    let menu = gio::Menu::new();
+    menu.append(Some("New Environment"), Some("environments.new"));
+    menu.append(Some("Edit Environment…"), Some("environments.edit"));
+    menu.append(Some("Unlock Secrets…"), Some("environments.unlock"));
+    menu.append(
+        Some("Import Postman Environment…"),
+        Some("environments.import-postman"),
+    );
+    menu.append(
+        Some("Export Postman Environment…"),
+        Some("environments.export-postman"),
+    );
+    menu.append(Some("Delete Environment"), Some("environments.delete"));
+
+    let menu_btn = MenuButton::builder()
+        .icon_name("view-more-symbolic")
+        .css_classes(vec!["flat".to_string()])
+        .tooltip_text("Environments Menu")
+        .menu_model(&menu)
+        .build();
+
+    container.append(&environment_dropdown);
+    container.append(&menu_btn);
+
+    (container, environment_dropdown)
+}
+
+/// Fills the dropdown and selects `active`; index 0 is always "No Environment".
+pub fn set_environments(dropdown: &DropDown, environments: &[Environment], active: Option<i64>) {
+    let mut names = vec![NO_ENVIRONMENT];
+    names.extend(environments.iter().map(|e| e.name.as_str()));
+    dropdown.set_model(Some(&StringList::new(&names)));
+
+    let selected = active
+        .and_then(|id| environments.iter().position(|e| e.id == id))
+        .map_or(0, |index| index as u32 + 1);
+    dropdown.set_selected(selected);
+}
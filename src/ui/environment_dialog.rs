@@ -0,0 +1,170 @@
+use adw::{Dialog, HeaderBar, ToolbarView, prelude::*};
+use gtk::{Box, Button, CheckButton, Entry, Orientation, ScrolledWindow, ToggleButton};
+use std::{cell::RefCell, rc::Rc};
+
+use crate::{
+    config,
+    models::{Environment, Variable},
+    secrets,
+    ui::helpers::add_box_margins,
+};
+
+#[derive(Clone)]
+struct VariableRow {
+    container: Box,
+    enabled_check: CheckButton,
+    key_entry: Entry,
+    value_entry: Entry,
+    secret_toggle: ToggleButton,
+    /// Encrypted value of a locked secret, kept as it is on save
+    locked_value: Option<String>,
+}
+
+/// Edits an environment's name and variables. Secret values are masked, and
+/// stay read-only while secrets are locked.
+pub fn present(
+    parent: &impl IsA<gtk::Widget>,
+    environment: Environment,
+    on_save: impl Fn(Environment) + 'static,
+) {
+    let name_entry = Entry::builder()
+        .placeholder_text("Environment Name")
+        .text(&environment.name)
+        .build();
+
+    let rows_box = Box::new(Orientation::Vertical, config::SPACING_EXTRA_SMALL);
+    let rows = Rc::new(RefCell::new(Vec::<VariableRow>::new()));
+    for variable in &environment.variables {
+        add_row(&rows_box, &rows, variable);
+    }
+
+    let add_btn = Button::with_label("Add Variable");
+    add_btn.connect_clicked(glib::clone!(@strong rows_box, @strong rows => move |_| {
+        let variable = Variable {
+            key: String::new(),
+            value: String::new(),
+            enabled: true,
+            secret: false,
+        };
+        add_row(&rows_box, &rows, &variable);
+    }));
+
+    let content = Box::new(Orientation::Vertical, config::SPACING_SMALL);
+    add_box_margins(&content, config::SPACING_MEDIUM);
+    content.append(&name_entry);
+    content.append(&rows_box);
+    content.append(&add_btn);
+
+    let scrolled_window = ScrolledWindow::builder()
+        .hscrollbar_policy(gtk::PolicyType::Never)
+        .child(&content)
+        .vexpand(true)
+        .build();
+
+    let save_btn = Button::with_label("Save");
+    save_btn.add_css_class("suggested-action");
+
+    let header = HeaderBar::new();
+    header.pack_end(&save_btn);
+
+    let toolbar_view = ToolbarView::new();
+    toolbar_view.add_top_bar(&header);
+    toolbar_view.set_content(Some(&scrolled_window));
+
+    let dialog = Dialog::builder()
+        .title("Edit Environment")
+        .content_width(config::DIALOG_DEFAULT_WIDTH)
+        .content_height(config::DIALOG_DEFAULT_HEIGHT)
+        .child(&toolbar_view)
+        .build();
+
+    let dialog_ref = dialog.downgrade();
+    save_btn.connect_clicked(move |_| {
+        let Some(dialog) = dialog_ref.upgrade() else {
+            return;
+        };
+        let variables = rows
+            .borrow()
+            .iter()
+            .filter(|row| !row.key_entry.text().is_empty())
+            .map(|row| Variable {
+                key: row.key_entry.text().to_string(),
+                value: row
+                    .locked_value
+                    .clone()
+                    .unwrap_or_else(|| row.value_entry.text().to_string()),
+                enabled: row.enabled_check.is_active(),
+                secret: row.secret_toggle.is_active(),
+            })
+            .collect();
+        on_save(Environment {
+            id: environment.id,
+            name: name_entry.text().to_string(),
+            variables,
+        });
+        dialog.close();
+    });
+
+    dialog.present(Some(parent));
+}
+
+fn add_row(rows_box: &Box, rows: &Rc<RefCell<Vec<VariableRow>>>, variable: &Variable) {
+    let container = Box::new(Orientation::Horizontal, config::SPACING_EXTRA_SMALL);
+
+    let enabled_check = CheckButton::builder()
+        .active(variable.enabled)
+        .tooltip_text("Enabled")
+        .build();
+
+    let key_entry = Entry::builder()
+        .placeholder_text("Key")
+        .hexpand(true)
+        .text(&variable.key)
+        .build();
+
+    let locked = secrets::is_locked(variable);
+    let value_entry = Entry::builder()
+        .placeholder_text(if locked { "Locked" } else { "Value" })
+        .hexpand(true)
+        .text(if locked { "" } else { variable.value.as_str() })
+        .visibility(!variable.secret)
+        .sensitive(!locked)
+        .build();
+
+    let secret_toggle = ToggleButton::builder()
+        .icon_name("dialog-password-symbolic")
+        .tooltip_text("Secret")
+        .active(variable.secret)
+        .sensitive(!locked)
+        .css_classes(vec!["flat".to_string()])
+        .build();
+    secret_toggle.connect_toggled(glib::clone!(@strong value_entry => move |toggle| {
+        value_entry.set_visibility(!toggle.is_active());
+    }));
+
+    let del_btn = Button::builder()
+        .icon_name("user-trash-symbolic")
+        .css_classes(vec!["flat".to_string()])
+        .build();
+
+    container.append(&enabled_check);
+    container.append(&key_entry);
+    container.append(&value_entry);
+    container.append(&secret_toggle);
+    container.append(&del_btn);
+    rows_box.append(&container);
+
+    rows.borrow_mut().push(VariableRow {
+        container: container.clone(),
+        enabled_check,
+        key_entry,
+        value_entry,
+        secret_toggle,
+        locked_value: locked.then(|| variable.value.clone()),
+    });
+
+    del_btn.connect_clicked(glib::clone!(@strong rows_box, @strong rows => move |_| {
+        rows_box.remove(&container);
+        rows.borrow_mut().retain(|row| row.container != container);
+    }));
+}
@@ -0,0 +1,161 @@
+use gtk::{Box, Button, DropDown, Entry, Label, ScrolledWindow, StringList, prelude::*};
+use std::{cell::RefCell, rc::Rc};
+
+use crate::{
+    config,
+    models::{Extractor, ExtractorSource},
+    ui::helpers::add_box_margins,
+};
+
+#[derive(Clone)]
+pub struct ExtractorRow {
+    pub container: Box,
+    pub source_dropdown: DropDown,
+    pub expression_entry: Entry,
+    pub variable_entry: Entry,
+}
+
+/// The request "Capture" tab: values taken from each response into variables.
+#[derive(Clone)]
+pub struct ExtractorEditor {
+    pub container: Box,
+    pub rows_box: Box,
+
+    pub rows: Rc<RefCell<Vec<ExtractorRow>>>,
+}
+
+impl ExtractorEditor {
+    pub fn new() -> Self {
+        let container = Box::new(gtk::Orientation::Vertical, config::SPACING_NONE);
+
+        let rows_box = Box::new(gtk::Orientation::Vertical, config::SPACING_EXTRA_SMALL);
+        add_box_margins(&rows_box, config::SPACING_MEDIUM);
+
+        let scrolled = ScrolledWindow::builder()
+            .hscrollbar_policy(gtk::PolicyType::Never)
+            .child(&rows_box)
+            .vexpand(true)
+            .build();
+
+        container.append(&scrolled);
+
+        let add_btn = Button::with_label("Add Capture");
+        add_btn.add_css_class("suggested-action");
+        add_btn.set_margin_bottom(config::SPACING_MEDIUM);
+        add_btn.set_margin_start(config::SPACING_MEDIUM);
+        add_btn.set_margin_end(config::SPACING_MEDIUM);
+
+        container.append(&add_btn);
+
+        let editor = ExtractorEditor {
+            container,
+            rows_box,
+            rows: Rc::new(RefCell::new(Vec::new())),
+        };
+
+        let editor_clone = editor.clone();
+        add_btn.connect_clicked(move |_| editor_clone.add_row(&Extractor::default()));
+
+        editor
+    }
+
+    pub fn add_row(&self, extractor: &Extractor) {
+        let row_container = Box::new(gtk::Orientation::Horizontal, config::SPACING_EXTRA_SMALL);
+
+        let labels: Vec<&str> = ExtractorSource::ALL.iter().map(|s| s.label()).collect();
+        let source_dropdown = DropDown::new(Some(StringList::new(&labels)), gtk::Expression::NONE);
+        source_dropdown.set_selected(extractor.source.to_index());
+
+        let expression_entry = Entry::builder()
+            .hexpand(true)
+            .text(extractor.expression.as_str())
+            .build();
+
+        let arrow = Label::new(Some("→"));
+        arrow.add_css_class("dim-label");
+
+        let variable_entry = Entry::builder()
+            .placeholder_text("Variable")
+            .text(extractor.variable.as_str())
+            .build();
+
+        let del_btn = Button::builder()
+            .icon_name("user-trash-symbolic")
+            .css_classes(vec!["flat".to_string()])
+            .build();
+
+        row_container.append(&source_dropdown);
+        row_container.append(&expression_entry);
+        row_container.append(&arrow);
+        row_container.append(&variable_entry);
+        row_container.append(&del_btn);
+
+        self.rows_box.append(&row_container);
+
+        let row_data = ExtractorRow {
+            container: row_container.clone(),
+            source_dropdown: source_dropdown.clone(),
+            expression_entry,
+            variable_entry,
+        };
+        update_placeholder(&row_data);
+
+        let row_ref = row_data.clone();
+        source_dropdown.connect_selected_notify(move |_| update_placeholder(&row_ref));
+
+        self.rows.borrow_mut().push(row_data);
+
+        let rows_ref = self.rows.clone();
+        let rows_box_ref = self.rows_box.clone();
+        let container_ref = row_container.clone();
+
+        del_btn.connect_clicked(move |_| {
+            rows_box_ref.remove(&container_ref);
+
+            rows_ref
+                .borrow_mut()
+                .retain(|row| row.container != container_ref)
+        });
+    }
+
+    pub fn get_data(&self) -> Vec<Extractor> {
+        self.rows
+            .borrow()
+            .iter()
+            .map(|row| Extractor {
+                source: ExtractorSource::from_index(row.source_dropdown.selected()),
+                expression: row.expression_entry.text().to_string(),
+                variable: row.variable_entry.text().to_string(),
+            })
+            .collect()
+    }
+
+    pub fn set_data(&self, data: Vec<Extractor>) {
+        let mut rows = self.rows.borrow_mut();
+
+        for row in rows.iter() {
+            self.rows_box.remove(&row.container);
+        }
+
+        rows.clear();
+        drop(rows);
+
+        for extractor in &data {
+            self.add_row(extractor);
+        }
+    }
+
+    pub fn clear(&self) {
+        self.set_data(Vec::new());
+    }
+}
+
+fn update_placeholder(row: &ExtractorRow) {
+    let placeholder = match ExtractorSource::from_index(row.source_dropdown.selected()) {
+        ExtractorSource::JsonPath => "$.access_token",
+        ExtractorSource::Regex => "Pattern; the first group is captured",
+        ExtractorSource::Header => "Header name",
+        ExtractorSource::Cookie => "Cookie name",
+    };
+    row.expression_entry.set_placeholder_text(Some(placeholder));
+}
@@ -0,0 +1,56 @@
+use adw::ApplicationWindow;
+use gtk::{FileDialog, FileFilter, gio, prelude::*};
+use std::path::PathBuf;
+
+/// A `(name, glob patterns)` pair shown in the file type selector
+pub type Filter<'a> = (&'a str, &'a [&'a str]);
+
+fn filters_model(filters: &[Filter]) -> gio::ListStore {
+    let model = gio::ListStore::new::<FileFilter>();
+    for (name, patterns) in filters {
+        let filter = FileFilter::new();
+        filter.set_name(Some(name));
+        for pattern in patterns.iter() {
+            filter.add_pattern(pattern);
+        }
+        model.append(&filter);
+    }
+    model
+}
+
+pub fn open(
+    window: &ApplicationWindow,
+    title: &str,
+    filters: &[Filter],
+    on_chosen: impl FnOnce(PathBuf) + 'static,
+) {
+    let dialog = FileDialog::builder().title(title).modal(true).build();
+    if !filters.is_empty() {
+        dialog.set_filters(Some(&filters_model(filters)));
+    }
+
+    dialog.open(Some(window), gio::Cancellable::NONE, move |result| {
+        if let Some(path) = result.ok().and_then(|file| file.path()) {
+            on_chosen(path);
+        }
+    });
+}
+
+pub fn save(
+    window: &ApplicationWindow,
+    title: &str,
+    initial_name: &str,
+    on_chosen: impl FnOnce(PathBuf) + 'static,
+) {
+    let dialog = FileDialog::builder()
+        .title(title)
+        .initial_name(initial_name)
+        .modal(true)
+        .build();
+
+    dialog.save(Some(window), gio::Cancellable::NONE, move |result| {
+        if let Some(path) = result.ok().and_then(|file| file.path()) {
+            on_chosen(path);
+        }
+    });
+}
@@ -1,5 +1,10 @@
 use gtk::{Box, prelude::*};
-use sourceview5::{Buffer, LanguageManager, prelude::BufferExt};
+use sourceview5::{
+    Buffer, LanguageManager, StyleSchemeManager, View,
+    prelude::{BufferExt, ViewExt},
+};
+
+use crate::{config, ui::variable_completion::VariableProvider};
 
 pub fn add_box_margins(target_box: &Box, size: i32) {
     target_box.set_margin_top(size);
@@ -8,6 +13,26 @@ pub fn add_box_margins(target_box: &Box, size: i32) {
     target_box.set_margin_end(size);
 }
 
+/// Completes the names of built-in `{{$name}}` placeholders on Ctrl+Space.
+pub fn add_variable_completion(view: &View) {
+    let completion = view.completion();
+    completion.add_provider(&VariableProvider::default());
+    // Only on request, so typing a body does not keep opening proposals
+    completion.block_interactive();
+}
+
+pub fn set_editor_scheme(buffer: &Buffer) {
+    let style_manager = StyleSchemeManager::default();
+    let scheme = style_manager
+        .scheme(config::EDITOR_SCHEME_PREF_1)
+        .or_else(|| style_manager.scheme(config::EDITOR_SCHEME_PREF_2))
+        .or_else(|| style_manager.scheme(config::EDITOR_SCHEME_PREF_3));
+
+    if let Some(s) = scheme {
+        buffer.set_style_scheme(Some(&s));
+    }
+}
+
 pub fn set_syntax_highlighting(buffer: &Buffer, headers_or_type: &str) {
     let lm = LanguageManager::default();
     let text = headers_or_type.to_lowercase();
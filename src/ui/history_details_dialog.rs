@@ -0,0 +1,88 @@
+use adw::{AlertDialog, prelude::*};
+use gtk::{Box, Entry, Frame, Orientation, TextView};
+
+use crate::config;
+
+/// Asks for a name shown instead of the entry's URL; clearing it shows the
+/// URL again.
+pub fn rename(parent: &impl IsA<gtk::Widget>, label: &str, on_rename: impl Fn(String) + 'static) {
+    let entry = Entry::builder()
+        .text(label)
+        .placeholder_text("Name")
+        .activates_default(true)
+        .build();
+
+    let dialog = AlertDialog::builder()
+        .heading("Rename Entry")
+        .body("Leave empty to show the URL")
+        .extra_child(&entry)
+        .build();
+    dialog.add_response("cancel", "Cancel");
+    dialog.add_response("rename", "Rename");
+    dialog.set_response_appearance("rename", adw::ResponseAppearance::Suggested);
+    dialog.set_default_response(Some("rename"));
+    dialog.set_close_response("cancel");
+
+    dialog.connect_response(None, move |_, response| {
+        if response == "rename" {
+            on_rename(entry.text().to_string());
+        }
+    });
+
+    dialog.present(Some(parent));
+}
+
+/// Edits the free-form note and the comma-separated tags of an entry.
+pub fn annotate(
+    parent: &impl IsA<gtk::Widget>,
+    note: &str,
+    tags: &[String],
+    on_save: impl Fn(String, Vec<String>) + 'static,
+) {
+    let tags_entry = Entry::builder()
+        .text(tags.join(", "))
+        .placeholder_text("Tags, separated by commas")
+        .build();
+
+    let note_view = TextView::builder()
+        .wrap_mode(gtk::WrapMode::WordChar)
+        .height_request(config::NOTE_MIN_HEIGHT)
+        .top_margin(config::SPACING_SMALL)
+        .bottom_margin(config::SPACING_SMALL)
+        .left_margin(config::SPACING_SMALL)
+        .right_margin(config::SPACING_SMALL)
+        .build();
+    note_view.buffer().set_text(note);
+    let note_frame = Frame::builder().child(&note_view).build();
+
+    let fields = Box::new(Orientation::Vertical, config::SPACING_SMALL);
+    fields.append(&tags_entry);
+    fields.append(&note_frame);
+
+    let dialog = AlertDialog::builder()
+        .heading("Note and Tags")
+        .extra_child(&fields)
+        .build();
+    dialog.add_response("cancel", "Cancel");
+    dialog.add_response("save", "Save");
+    dialog.set_response_appearance("save", adw::ResponseAppearance::Suggested);
+    dialog.set_close_response("cancel");
+
+    dialog.connect_response(None, move |_, response| {
+        if response != "save" {
+            return;
+        }
+        let buffer = note_view.buffer();
+        let (start, end) = buffer.bounds();
+        let tags = tags_entry
+            .text()
+            .split(',')
+            .map(str::trim)
+            .filter(|tag| !tag.is_empty())
+            .map(String::from)
+            .collect();
+        on_save(buffer.text(&start, &end, false).trim().to_string(), tags);
+    });
+
+    dialog.present(Some(parent));
+}
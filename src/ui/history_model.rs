@@ -0,0 +1,236 @@
+use gtk::{gio, glib, prelude::*, subclass::prelude::*};
+use std::rc::Rc;
+
+use crate::{
+    database::{Database, HistoryFilter, HistoryGrouping, HistoryItem},
+    models::AssertionResult,
+};
+
+/// What a row in the history list shows, held by each model item.
+pub struct HistoryRow {
+    pub item: HistoryItem,
+    /// Title of the section the entry is listed under
+    pub section: String,
+    /// Matched text with highlight markers, when searching for words
+    pub snippet: Option<String>,
+    pub results: Vec<AssertionResult>,
+}
+
+mod imp {
+    use gtk::{gio, glib, prelude::*, subclass::prelude::*};
+    use std::cell::{Cell, OnceCell, RefCell};
+    use std::collections::HashMap;
+    use std::rc::Rc;
+
+    use super::HistoryRow;
+    use crate::{
+        config,
+        database::{Database, HistoryFilter, HistoryGrouping, HistorySection},
+    };
+
+    #[derive(Default)]
+    pub struct HistoryModel {
+        pub db: OnceCell<Rc<Database>>,
+        pub filter: RefCell<HistoryFilter>,
+        pub grouping: Cell<HistoryGrouping>,
+        pub sections: RefCell<Vec<HistorySection>>,
+        pub count: Cell<u32>,
+        /// Pinned entries come first, so new ones go after them
+        pub pinned: Cell<u32>,
+        /// Fetched rows by page number
+        pub pages: RefCell<HashMap<usize, Vec<glib::BoxedAnyObject>>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for HistoryModel {
+        const NAME: &'static str = "DispatchHistoryModel";
+        type Type = super::HistoryModel;
+        type Interfaces = (gio::ListModel, gtk::SectionModel);
+    }
+
+    impl ObjectImpl for HistoryModel {}
+
+    impl ListModelImpl for HistoryModel {
+        fn item_type(&self) -> glib::Type {
+            glib::BoxedAnyObject::static_type()
+        }
+
+        fn n_items(&self) -> u32 {
+            self.count.get()
+        }
+
+        fn item(&self, position: u32) -> Option<glib::Object> {
+            if position >= self.count.get() {
+                return None;
+            }
+            let page = position as usize / config::HISTORY_PAGE_SIZE;
+            let index = position as usize % config::HISTORY_PAGE_SIZE;
+            if !self.pages.borrow().contains_key(&page) {
+                let rows = self.fetch(page)?;
+                self.cache(page, rows);
+            }
+            self.pages
+                .borrow()
+                .get(&page)
+                .and_then(|rows| rows.get(index))
+                .map(|row| row.clone().upcast())
+        }
+    }
+
+    impl SectionModelImpl for HistoryModel {
+        fn section(&self, position: u32) -> (u32, u32) {
+            match self.section_at(position) {
+                Some((start, end, _)) => (start, end),
+                None => (self.count.get(), u32::MAX),
+            }
+        }
+    }
+
+    impl HistoryModel {
+        /// Where the section holding `position` starts and ends, and its title.
+        pub fn section_at(&self, position: u32) -> Option<(u32, u32, String)> {
+            let mut start = 0;
+            for section in self.sections.borrow().iter() {
+                let end = start + section.count as u32;
+                if position < end {
+                    return Some((start, end, section.title.clone()));
+                }
+                start = end;
+            }
+            None
+        }
+
+        fn fetch(&self, page: usize) -> Option<Vec<glib::BoxedAnyObject>> {
+            let db = self.db.get()?;
+            let matches = db
+                .search_history(
+                    &self.filter.borrow(),
+                    self.grouping.get(),
+                    page * config::HISTORY_PAGE_SIZE,
+                    config::HISTORY_PAGE_SIZE,
+                )
+                .ok()?;
+            let ids: Vec<i64> = matches.iter().map(|found| found.item.id).collect();
+            let mut results = db.get_assertion_results_for(&ids).unwrap_or_default();
+            let first = (page * config::HISTORY_PAGE_SIZE) as u32;
+            let rows = matches
+                .into_iter()
+                .zip(first..)
+                .map(|(found, position)| {
+                    let results = results.remove(&found.item.id).unwrap_or_default();
+                    let section = self
+                        .section_at(position)
+                        .map(|(_, _, title)| title)
+                        .unwrap_or_default();
+                    glib::BoxedAnyObject::new(HistoryRow {
+                        item: found.item,
+                        section,
+                        snippet: found.snippet,
+                        results,
+                    })
+                })
+                .collect();
+            Some(rows)
+        }
+
+        /// Keeps the page, dropping the one furthest from it when full.
+        fn cache(&self, page: usize, rows: Vec<glib::BoxedAnyObject>) {
+            let mut pages = self.pages.borrow_mut();
+            if pages.len() >= config::HISTORY_CACHED_PAGES {
+                let furthest = pages.keys().copied().max_by_key(|p| p.abs_diff(page));
+                if let Some(furthest) = furthest {
+                    pages.remove(&furthest);
+                }
+            }
+            pages.insert(page, rows);
+        }
+    }
+}
+
+glib::wrapper! {
+    /// History entries matching a filter in sections by date or host, newest
+    /// first, read from the database a page at a time as the list scrolls to
+    /// them.
+    pub struct HistoryModel(ObjectSubclass<imp::HistoryModel>)
+        @implements gio::ListModel, gtk::SectionModel;
+}
+
+impl Default for HistoryModel {
+    fn default() -> Self {
+        glib::Object::new()
+    }
+}
+
+impl HistoryModel {
+    /// Empty until given the database to read from.
+    pub fn set_database(&self, db: Rc<Database>) {
+        let _ = self.imp().db.set(db);
+    }
+
+    pub fn filter(&self) -> HistoryFilter {
+        self.imp().filter.borrow().clone()
+    }
+
+    pub fn grouping(&self) -> HistoryGrouping {
+        self.imp().grouping.get()
+    }
+
+    /// Shows what `filter` matches, split into sections by `grouping`.
+    pub fn set_filter(
+        &self,
+        filter: HistoryFilter,
+        grouping: HistoryGrouping,
+    ) -> rusqlite::Result<()> {
+        self.imp().filter.replace(filter);
+        self.imp().grouping.set(grouping);
+        self.reload()
+    }
+
+    /// Counts the entries again and drops every fetched row.
+    pub fn reload(&self) -> rusqlite::Result<()> {
+        let imp = self.imp();
+        self.load_sections()?;
+        let count = imp.sections.borrow().iter().map(|s| s.count as u32).sum();
+        let removed = imp.count.replace(count);
+        imp.pages.borrow_mut().clear();
+        self.items_changed(0, removed, count);
+        Ok(())
+    }
+
+    /// Shows a newly saved entry at the top, below pinned ones. With a filter
+    /// set, or grouped by host, the search runs again since the entry may not
+    /// match or may belong further down.
+    pub fn insert_newest(&self) -> rusqlite::Result<()> {
+        let imp = self.imp();
+        if !imp.filter.borrow().is_empty() || imp.grouping.get() != HistoryGrouping::Date {
+            return self.reload();
+        }
+        // A first entry today opens a new section
+        self.load_sections()?;
+        // Every fetched page is now one row off
+        imp.pages.borrow_mut().clear();
+        imp.count.set(imp.count.get() + 1);
+        self.items_changed(imp.pinned.get(), 0, 1);
+        self.sections_changed(0, imp.count.get());
+        Ok(())
+    }
+
+    fn load_sections(&self) -> rusqlite::Result<()> {
+        let imp = self.imp();
+        let sections = match imp.db.get() {
+            Some(db) => db.history_sections(&imp.filter.borrow(), imp.grouping.get())?,
+            None => Vec::new(),
+        };
+        // Pinned entries are always the first section
+        let pinned = sections.first().filter(|s| s.pinned).map_or(0, |s| s.count);
+        imp.pinned.set(pinned as u32);
+        imp.sections.replace(sections);
+        Ok(())
+    }
+
+    pub fn row_id(&self, position: u32) -> Option<i64> {
+        let object = self.item(position).and_downcast::<glib::BoxedAnyObject>()?;
+        let id = object.borrow::<HistoryRow>().item.id;
+        Some(id)
+    }
+}
@@ -0,0 +1,197 @@
+use gtk::{
+    Box, CheckButton, DropDown, Entry, Grid, Label, MenuButton, Orientation, Popover, SearchEntry,
+    StringList, ToggleButton, prelude::*,
+};
+
+use crate::{
+    config,
+    database::{HistoryFilter, HistoryGrouping},
+};
+
+const GROUPINGS: [(&str, HistoryGrouping); 2] = [
+    ("Date", HistoryGrouping::Date),
+    ("Host", HistoryGrouping::Host),
+];
+
+// Index 0 of each filter dropdown means "any"
+const METHODS: [&str; 6] = ["Any Method", "GET", "POST", "PUT", "PATCH", "DELETE"];
+const STATUS_CLASSES: [&str; 5] = ["Any Status", "2xx", "3xx", "4xx", "5xx"];
+
+/// Search entry and filters above the history list.
+#[derive(Clone)]
+pub struct HistorySearch {
+    pub container: Box,
+    search_entry: SearchEntry,
+    filter_button: MenuButton,
+    method_dropdown: DropDown,
+    status_dropdown: DropDown,
+    host_entry: Entry,
+    since_entry: Entry,
+    until_entry: Entry,
+    pinned_check: CheckButton,
+    grouping_dropdown: DropDown,
+    errors_button: ToggleButton,
+    host_button: ToggleButton,
+}
+
+impl HistorySearch {
+    pub fn new() -> Self {
+        let container = Box::new(Orientation::Horizontal, config::SPACING_EXTRA_SMALL);
+        container.set_margin_start(config::SPACING_MEDIUM);
+        container.set_margin_end(config::SPACING_MEDIUM);
+        container.set_margin_bottom(config::SPACING_SMALL);
+
+        let search_entry = SearchEntry::builder()
+            .placeholder_text("Search URLs, headers and bodies")
+            .hexpand(true)
+            .build();
+
+        let method_dropdown = DropDown::new(Some(StringList::new(&METHODS)), gtk::Expression::NONE);
+        let status_dropdown = DropDown::new(
+            Some(StringList::new(&STATUS_CLASSES)),
+            gtk::Expression::NONE,
+        );
+        let host_entry = Entry::builder().placeholder_text("api.example.com").build();
+        let since_entry = Entry::builder().placeholder_text("YYYY-MM-DD").build();
+        let until_entry = Entry::builder().placeholder_text("YYYY-MM-DD").build();
+        let grouping_names: Vec<&str> = GROUPINGS.iter().map(|(name, _)| *name).collect();
+        let grouping_dropdown = DropDown::new(
+            Some(StringList::new(&grouping_names)),
+            gtk::Expression::NONE,
+        );
+
+        let grid = Grid::builder()
+            .row_spacing(config::SPACING_SMALL)
+            .column_spacing(config::SPACING_MEDIUM)
+            .build();
+        let rows: [(&str, &gtk::Widget); 6] = [
+            ("Group By", grouping_dropdown.upcast_ref()),
+            ("Method", method_dropdown.upcast_ref()),
+            ("Status", status_dropdown.upcast_ref()),
+            ("Host", host_entry.upcast_ref()),
+            ("From", since_entry.upcast_ref()),
+            ("To", until_entry.upcast_ref()),
+        ];
+        for (row, (title, widget)) in rows.into_iter().enumerate() {
+            let label = Label::builder().label(title).xalign(0.0).build();
+            grid.attach(&label, 0, row as i32, 1, 1);
+            grid.attach(widget, 1, row as i32, 1, 1);
+        }
+        let pinned_check = CheckButton::with_label("Pinned Only");
+        grid.attach(&pinned_check, 1, rows.len() as i32, 1, 1);
+
+        let popover = Popover::builder().child(&grid).build();
+        let filter_button = MenuButton::builder()
+            .icon_name("funnel-symbolic")
+            .tooltip_text("Filter History")
+            .css_classes(vec!["flat".to_string()])
+            .popover(&popover)
+            .build();
+
+        // Quick filters, next to the search rather than in the popover
+        let errors_button = ToggleButton::builder()
+            .icon_name("dialog-warning-symbolic")
+            .tooltip_text("Errors Only")
+            .css_classes(vec!["flat".to_string()])
+            .build();
+        let host_button = ToggleButton::builder()
+            .icon_name("network-server-symbolic")
+            .tooltip_text("Only the Host in the URL Bar")
+            .css_classes(vec!["flat".to_string()])
+            .build();
+
+        container.append(&search_entry);
+        container.append(&errors_button);
+        container.append(&host_button);
+        container.append(&filter_button);
+
+        HistorySearch {
+            container,
+            search_entry,
+            filter_button,
+            method_dropdown,
+            status_dropdown,
+            host_entry,
+            since_entry,
+            until_entry,
+            pinned_check,
+            grouping_dropdown,
+            errors_button,
+            host_button,
+        }
+    }
+
+    pub fn grouping(&self) -> HistoryGrouping {
+        let index = self.grouping_dropdown.selected() as usize;
+        GROUPINGS
+            .get(index)
+            .map_or_else(Default::default, |(_, grouping)| *grouping)
+    }
+
+    /// Whether the list follows the host of the request being edited, in place
+    /// of the host filter.
+    pub fn current_host_only(&self) -> bool {
+        self.host_button.is_active()
+    }
+
+    pub fn filter(&self) -> HistoryFilter {
+        let method = self.method_dropdown.selected() as usize;
+        let status = self.status_dropdown.selected();
+        HistoryFilter {
+            text: self.search_entry.text().trim().to_string(),
+            method: (method > 0).then(|| METHODS[method].to_string()),
+            // "2xx" is at index 1
+            status_class: (status > 0).then(|| status as u8 + 1),
+            host: self.host_entry.text().trim().to_string(),
+            since: self.since_entry.text().trim().to_string(),
+            until: self.until_entry.text().trim().to_string(),
+            pinned_only: self.pinned_check.is_active(),
+            errors_only: self.errors_button.is_active(),
+        }
+    }
+
+    /// Calls `f` with the new filter and grouping whenever the search, a
+    /// filter or the grouping changes.
+    pub fn connect_changed(&self, f: impl Fn(HistoryFilter, HistoryGrouping) + 'static) {
+        let search = self.clone();
+        let changed = std::rc::Rc::new(move || {
+            let filter = search.filter();
+            // Highlighted while filters other than the search text apply
+            let filtered = !HistoryFilter {
+                text: String::new(),
+                errors_only: false,
+                ..filter.clone()
+            }
+            .is_empty();
+            if filtered {
+                search.filter_button.add_css_class("accent");
+            } else {
+                search.filter_button.remove_css_class("accent");
+            }
+            f(filter, search.grouping());
+        });
+
+        let on_change = changed.clone();
+        self.search_entry
+            .connect_search_changed(move |_| on_change());
+        let dropdowns = [
+            &self.method_dropdown,
+            &self.status_dropdown,
+            &self.grouping_dropdown,
+        ];
+        for dropdown in dropdowns {
+            let on_change = changed.clone();
+            dropdown.connect_selected_notify(move |_| on_change());
+        }
+        for entry in [&self.host_entry, &self.since_entry, &self.until_entry] {
+            let on_change = changed.clone();
+            entry.connect_changed(move |_| on_change());
+        }
+        let on_change = changed.clone();
+        self.pinned_check.connect_toggled(move |_| on_change());
+        for button in [&self.errors_button, &self.host_button] {
+            let on_change = changed.clone();
+            button.connect_toggled(move |_| on_change());
+        }
+    }
+}
@@ -0,0 +1,40 @@
+use adw::{AlertDialog, prelude::*};
+use gtk::{Label, ScrolledWindow};
+
+use crate::config;
+
+/// Lists what an import could not convert, one line per warning.
+pub fn present(parent: &impl IsA<gtk::Widget>, heading: &str, warnings: &[String]) {
+    let label = Label::builder()
+        .label(
+            warnings
+                .iter()
+                .map(|w| format!("• {}", w))
+                .collect::<Vec<_>>()
+                .join("\n"),
+        )
+        .xalign(0.0)
+        .wrap(true)
+        .selectable(true)
+        .build();
+
+    let scrolled = ScrolledWindow::builder()
+        .hscrollbar_policy(gtk::PolicyType::Never)
+        .min_content_height(config::REPORT_MIN_HEIGHT)
+        .max_content_height(config::DIALOG_DEFAULT_HEIGHT)
+        .propagate_natural_height(true)
+        .child(&label)
+        .build();
+
+    let dialog = AlertDialog::builder()
+        .heading(heading)
+        .body(format!(
+            "{} items could not be fully converted:",
+            warnings.len()
+        ))
+        .extra_child(&scrolled)
+        .build();
+    dialog.add_response("close", "Close");
+
+    dialog.present(Some(parent));
+}
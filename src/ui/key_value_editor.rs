@@ -2,7 +2,10 @@ use gtk::{Box, Entry, ScrolledWindow};
 use gtk::{Button, prelude::*};
 use std::{cell::RefCell, rc::Rc};
 
-use crate::{config, ui::helpers::add_box_margins};
+use crate::{
+    config,
+    ui::{helpers::add_box_margins, variable_completion},
+};
 
 #[derive(Clone)]
 pub struct KeyValueRow {
@@ -69,6 +72,7 @@ impl KeyValueEditor {
             .hexpand(true)
             .text(value)
             .build();
+        variable_completion::attach_to_entry(&value_entry);
 
         let del_btn = Button::builder()
             .icon_name("user-trash-symbolic")
@@ -1,11 +1,15 @@
 use adw::{
     prelude::*, Application, ApplicationWindow, Breakpoint, BreakpointCondition, OverlaySplitView,
+    ToastOverlay,
 };
-use gtk::{Box, HeaderBar, Orientation};
+use gtk::{Box, Button, HeaderBar, Orientation};
 
 use crate::{
     config,
-    ui::{request_bar, request_tabs, response_view, sidebar, status_bar, widgets::WindowWidgets},
+    ui::{
+        environment_bar, request_bar, request_tabs, response_view, runner_view::RunnerView,
+        sidebar, status_bar, widgets::WindowWidgets,
+    },
 };
 
 pub fn build_ui(app: &Application) -> (ApplicationWindow, WindowWidgets) {
@@ -16,14 +20,45 @@ pub fn build_ui(app: &Application) -> (ApplicationWindow, WindowWidgets) {
     let main_header = HeaderBar::new();
     main_content.append(&main_header);
 
+    let generate_code_btn = Button::builder()
+        .icon_name("utilities-terminal-symbolic")
+        .tooltip_text("Generate Code")
+        .build();
+    main_header.pack_end(&generate_code_btn);
+
+    let save_request_btn = Button::builder()
+        .icon_name("document-save-symbolic")
+        .tooltip_text("Save to Collection")
+        .build();
+    main_header.pack_end(&save_request_btn);
+
+    let (env_bar_container, environment_dropdown) = environment_bar::build();
+    main_header.pack_start(&env_bar_container);
+
     let (req_bar_container, url_entry, method_dropdown, send_button) = request_bar::build();
     main_content.append(&req_bar_container);
 
-    let (req_tabs_widget, request_body_buffer, headers_editor) = request_tabs::build();
+    let (
+        req_tabs_widget,
+        request_body_buffer,
+        headers_editor,
+        options_editor,
+        assertion_editor,
+        extractor_editor,
+        script_editor,
+    ) = request_tabs::build();
 
     let status_widget = status_bar::build();
 
-    let (resp_view_container, response_buffer, response_headers_buffer) = response_view::build();
+    let (
+        resp_view_container,
+        response_buffer,
+        response_headers_buffer,
+        test_results_view,
+        contract_view,
+        snapshot_view,
+        console_buffer,
+    ) = response_view::build();
 
     let response_area = Box::new(Orientation::Vertical, 0);
     response_area.append(&status_widget.container);
@@ -37,23 +72,6 @@ pub fn build_ui(app: &Application) -> (ApplicationWindow, WindowWidgets) {
 
     main_content.append(&paned);
 
-    let widgets = WindowWidgets {
-        url_entry,
-        method_dropdown,
-        request_body_buffer,
-        response_buffer,
-        response_headers_buffer,
-        status_label: status_widget.status_label,
-        time_label: status_widget.time_label,
-        size_label: status_widget.size_label,
-        spinner: status_widget.spinner,
-        headers_editor,
-        history_list: sidebar_widgets.history_list.clone(),
-        send_button,
-        new_request_btn: sidebar_widgets.new_request_btn,
-        clear_history_btn: sidebar_widgets.clear_history_btn,
-    };
-
     // ---  Window Creation---
     let split_view = OverlaySplitView::builder()
         .sidebar(&sidebar_content)
@@ -69,15 +87,54 @@ pub fn build_ui(app: &Application) -> (ApplicationWindow, WindowWidgets) {
     ));
     breakpoint.add_setter(&split_view, "collapsed", Some(&true.to_value()));
 
+    let toast_overlay = ToastOverlay::new();
+    toast_overlay.set_child(Some(&split_view));
+
     let window = ApplicationWindow::builder()
         .application(app)
         .title("Dispatch")
         .default_width(config::WINDOW_DEFAULT_WIDTH)
         .default_height(config::WINDOW_DEFAULT_HEIGHT)
-        .content(&split_view)
+        .content(&toast_overlay)
         .build();
 
     window.add_breakpoint(breakpoint);
 
+    let widgets = WindowWidgets {
+        window: window.clone(),
+        toast_overlay,
+        url_entry,
+        method_dropdown,
+        request_body_buffer,
+        response_buffer,
+        response_headers_buffer,
+        test_results_view,
+        contract_view,
+        snapshot_view,
+        console_buffer,
+        status_label: status_widget.status_label,
+        time_label: status_widget.time_label,
+        size_label: status_widget.size_label,
+        captured_label: status_widget.captured_label,
+        snapshot_label: status_widget.snapshot_label,
+        spinner: status_widget.spinner,
+        headers_editor,
+        options_editor,
+        assertion_editor,
+        extractor_editor,
+        script_editor,
+        runner_view: RunnerView::new(),
+        environment_dropdown,
+        history_list: sidebar_widgets.history_list.clone(),
+        history_model: sidebar_widgets.history_model,
+        history_search: sidebar_widgets.history_search,
+        collections_list: sidebar_widgets.collections_list,
+        send_button,
+        generate_code_btn,
+        save_request_btn,
+        new_request_btn: sidebar_widgets.new_request_btn,
+        clear_history_btn: sidebar_widgets.clear_history_btn,
+    };
+
     (window, widgets)
 }
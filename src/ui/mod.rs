@@ -1,12 +1,33 @@
+pub mod assertion_editor;
+pub mod codegen_dialog;
+pub mod collections_view;
+pub mod contract_view;
+pub mod diff_dialog;
+pub mod environment_bar;
+pub mod environment_dialog;
+pub mod extractor_editor;
+pub mod file_dialogs;
 pub mod helpers;
+pub mod history_details_dialog;
+pub mod history_model;
+pub mod history_search;
+pub mod import_report;
 pub mod key_value_editor;
 pub mod layout;
+pub mod options_editor;
+pub mod passphrase_dialog;
+pub mod preferences_dialog;
 pub mod reducer;
 pub mod request_bar;
 pub mod request_tabs;
 pub mod response_view;
+pub mod runner_view;
+pub mod script_editor;
 pub mod sidebar;
+pub mod snapshot_view;
 pub mod status_bar;
 pub mod styles;
+pub mod test_results_view;
+pub mod variable_completion;
 pub mod widgets;
 pub mod window;
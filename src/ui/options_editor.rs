@@ -0,0 +1,67 @@
+use adw::{PreferencesGroup, SwitchRow, prelude::*};
+use gtk::Box;
+
+use crate::{config, models::RequestOptions, ui::helpers::add_box_margins};
+
+#[derive(Clone)]
+pub struct OptionsEditor {
+    pub container: Box,
+    pub insecure_row: SwitchRow,
+    pub follow_redirects_row: SwitchRow,
+    pub compressed_row: SwitchRow,
+}
+
+impl OptionsEditor {
+    pub fn new() -> Self {
+        let container = Box::new(gtk::Orientation::Vertical, config::SPACING_NONE);
+        add_box_margins(&container, config::SPACING_MEDIUM);
+
+        let group = PreferencesGroup::new();
+
+        let insecure_row = SwitchRow::builder()
+            .title("Skip TLS Verification")
+            .subtitle("Accept invalid or self-signed certificates")
+            .build();
+
+        let follow_redirects_row = SwitchRow::builder().title("Follow Redirects").build();
+
+        let compressed_row = SwitchRow::builder()
+            .title("Compressed Response")
+            .subtitle("Request gzip, deflate or brotli and decompress the result")
+            .build();
+
+        group.add(&insecure_row);
+        group.add(&follow_redirects_row);
+        group.add(&compressed_row);
+        container.append(&group);
+
+        let editor = OptionsEditor {
+            container,
+            insecure_row,
+            follow_redirects_row,
+            compressed_row,
+        };
+
+        editor.clear();
+        editor
+    }
+
+    pub fn get_data(&self) -> RequestOptions {
+        RequestOptions {
+            insecure: self.insecure_row.is_active(),
+            follow_redirects: self.follow_redirects_row.is_active(),
+            compressed: self.compressed_row.is_active(),
+        }
+    }
+
+    pub fn set_data(&self, options: RequestOptions) {
+        self.insecure_row.set_active(options.insecure);
+        self.follow_redirects_row
+            .set_active(options.follow_redirects);
+        self.compressed_row.set_active(options.compressed);
+    }
+
+    pub fn clear(&self) {
+        self.set_data(RequestOptions::default());
+    }
+}
@@ -0,0 +1,66 @@
+use adw::{AlertDialog, prelude::*};
+use gtk::{Box, Orientation, PasswordEntry};
+
+use crate::config;
+
+/// Asks for the passphrase that unlocks secret variables. The first time,
+/// it is typed twice since it becomes the passphrase from then on.
+pub fn present(
+    parent: &impl IsA<gtk::Widget>,
+    first_time: bool,
+    on_unlock: impl Fn(String) + 'static,
+) {
+    let passphrase_entry = PasswordEntry::builder()
+        .placeholder_text("Passphrase")
+        .show_peek_icon(true)
+        .activates_default(true)
+        .build();
+    let confirm_entry = PasswordEntry::builder()
+        .placeholder_text("Confirm Passphrase")
+        .show_peek_icon(true)
+        .activates_default(true)
+        .visible(first_time)
+        .build();
+
+    let entries = Box::new(Orientation::Vertical, config::SPACING_EXTRA_SMALL);
+    entries.append(&passphrase_entry);
+    entries.append(&confirm_entry);
+
+    let body = if first_time {
+        "Choose a passphrase to encrypt secret variables. It cannot be recovered, so keep it somewhere safe."
+    } else {
+        "Enter your passphrase to use secret variables for this session."
+    };
+    let dialog = AlertDialog::builder()
+        .heading("Unlock Secrets")
+        .body(body)
+        .extra_child(&entries)
+        .build();
+    dialog.add_response("cancel", "Cancel");
+    dialog.add_response("unlock", "Unlock");
+    dialog.set_response_appearance("unlock", adw::ResponseAppearance::Suggested);
+    dialog.set_default_response(Some("unlock"));
+    dialog.set_close_response("cancel");
+    dialog.set_response_enabled("unlock", false);
+
+    let dialog_ref = dialog.downgrade();
+    let update = glib::clone!(@strong passphrase_entry, @strong confirm_entry => move || {
+        let Some(dialog) = dialog_ref.upgrade() else {
+            return;
+        };
+        let passphrase = passphrase_entry.text();
+        let valid = !passphrase.is_empty() && (!first_time || passphrase == confirm_entry.text());
+        dialog.set_response_enabled("unlock", valid);
+    });
+    let on_change = update.clone();
+    passphrase_entry.connect_changed(move |_| on_change());
+    confirm_entry.connect_changed(move |_| update());
+
+    dialog.connect_response(None, move |_, response| {
+        if response == "unlock" {
+            on_unlock(passphrase_entry.text().to_string());
+        }
+    });
+
+    dialog.present(Some(parent));
+}
@@ -0,0 +1,228 @@
+use adw::{
+    ActionRow, AlertDialog, EntryRow, PreferencesDialog, PreferencesGroup, PreferencesPage,
+    SpinRow, SwitchRow, prelude::*,
+};
+use gtk::{Button, Frame, TextView};
+use std::rc::Rc;
+
+use crate::{
+    config,
+    database::{RetentionPolicy, StorageUsage},
+    redaction::RedactionPolicy,
+};
+
+const MEGABYTE: u64 = 1024 * 1024;
+
+/// History preferences: the redaction and retention policies, saved when the
+/// dialog closes, and a button to scrub entries saved before the redaction
+/// policy with the policy as edited.
+pub fn present(
+    parent: &impl IsA<gtk::Widget>,
+    policy: RedactionPolicy,
+    retention: RetentionPolicy,
+    usage: StorageUsage,
+    on_save: impl Fn(RedactionPolicy) + 'static,
+    on_save_retention: impl Fn(RetentionPolicy) + 'static,
+    on_scrub: impl Fn(RedactionPolicy) + 'static,
+) {
+    let headers_row = EntryRow::builder().title("Headers").build();
+    headers_row.set_text(&policy.headers.join(", "));
+    let json_keys_row = EntryRow::builder()
+        .title("JSON Keys and Query Parameters")
+        .build();
+    json_keys_row.set_text(&policy.json_keys.join(", "));
+
+    let redaction_group = PreferencesGroup::builder()
+        .title("Redaction")
+        .description("Values masked in history before it is saved and when it is exported, separated by commas")
+        .build();
+    redaction_group.add(&headers_row);
+    redaction_group.add(&json_keys_row);
+
+    let patterns_view = TextView::builder()
+        .monospace(true)
+        .top_margin(config::SPACING_SMALL)
+        .bottom_margin(config::SPACING_SMALL)
+        .left_margin(config::SPACING_SMALL)
+        .right_margin(config::SPACING_SMALL)
+        .build();
+    patterns_view.buffer().set_text(&policy.patterns.join("\n"));
+    let patterns_frame = Frame::builder().child(&patterns_view).build();
+
+    let patterns_group = PreferencesGroup::builder()
+        .title("Patterns")
+        .description("Regular expressions, one per line; matching text is masked anywhere")
+        .build();
+    patterns_group.add(&patterns_frame);
+
+    let keep_session_row = SwitchRow::builder()
+        .title("Keep Full Values This Session")
+        .subtitle("Entries sent before Dispatch closes still open unredacted")
+        .active(policy.keep_session)
+        .build();
+
+    let session_group = PreferencesGroup::new();
+    session_group.add(&keep_session_row);
+
+    let scrub_button = Button::builder()
+        .label("Scrub")
+        .valign(gtk::Align::Center)
+        .css_classes(vec!["destructive-action".to_string()])
+        .build();
+    let scrub_row = ActionRow::builder()
+        .title("Scrub History")
+        .subtitle("Apply the policy above to entries recorded before it")
+        .build();
+    scrub_row.add_suffix(&scrub_button);
+
+    let existing_group = PreferencesGroup::new();
+    existing_group.add(&scrub_row);
+
+    let page = PreferencesPage::builder()
+        .title("History")
+        .icon_name("document-open-recent-symbolic")
+        .build();
+    page.add(&redaction_group);
+    page.add(&patterns_group);
+    page.add(&session_group);
+    page.add(&existing_group);
+
+    let max_entries_row = limit_row(
+        "Keep at Most",
+        "Entries besides pinned ones, oldest removed first; 0 keeps all",
+        config::RETENTION_MAX_ENTRIES,
+        100.0,
+        retention.max_entries.map(|n| n as f64),
+    );
+    let max_age_row = limit_row(
+        "Remove After",
+        "Days, unless pinned; 0 keeps entries however old",
+        config::RETENTION_MAX_DAYS,
+        1.0,
+        retention.max_age_days.map(f64::from),
+    );
+    let max_body_row = limit_row(
+        "Body Storage Limit",
+        "Megabytes of request and response bodies; 0 is unlimited",
+        config::RETENTION_MAX_MEGABYTES,
+        10.0,
+        retention.max_body_bytes.map(|b| (b / MEGABYTE) as f64),
+    );
+
+    let retention_group = PreferencesGroup::builder()
+        .title("Retention")
+        .description("Applied in the background when Dispatch starts and when these change")
+        .build();
+    retention_group.add(&max_entries_row);
+    retention_group.add(&max_age_row);
+    retention_group.add(&max_body_row);
+
+    let usage_group = PreferencesGroup::builder().title("Usage").build();
+    let usage_rows = [
+        (
+            "Entries",
+            format!("{} ({} pinned)", usage.entries, usage.pinned),
+        ),
+        ("Bodies", glib::format_size(usage.body_bytes).to_string()),
+        (
+            "Database File",
+            glib::format_size(usage.file_bytes).to_string(),
+        ),
+    ];
+    for (title, value) in usage_rows {
+        let row = ActionRow::builder().title(title).build();
+        row.add_suffix(
+            &gtk::Label::builder()
+                .label(value)
+                .css_classes(vec!["dim-label".to_string()])
+                .build(),
+        );
+        usage_group.add(&row);
+    }
+
+    let storage_page = PreferencesPage::builder()
+        .title("Storage")
+        .icon_name("drive-harddisk-symbolic")
+        .build();
+    storage_page.add(&retention_group);
+    storage_page.add(&usage_group);
+
+    let dialog = PreferencesDialog::new();
+    dialog.add(&page);
+    dialog.add(&storage_page);
+
+    let edited_policy = Rc::new(move || {
+        let buffer = patterns_view.buffer();
+        let (start, end) = buffer.bounds();
+        RedactionPolicy {
+            headers: split_list(&headers_row.text()),
+            json_keys: split_list(&json_keys_row.text()),
+            patterns: buffer
+                .text(&start, &end, false)
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .map(String::from)
+                .collect(),
+            keep_session: keep_session_row.is_active(),
+        }
+    });
+
+    let on_scrub = Rc::new(on_scrub);
+    let dialog_ref = dialog.downgrade();
+    scrub_button.connect_clicked(glib::clone!(@strong edited_policy => move |_| {
+        let Some(dialog) = dialog_ref.upgrade() else {
+            return;
+        };
+        let confirm = AlertDialog::builder()
+            .heading("Scrub History?")
+            .body("Values the policy masks are removed from every saved entry and cannot be restored.")
+            .build();
+        confirm.add_response("cancel", "Cancel");
+        confirm.add_response("scrub", "Scrub");
+        confirm.set_response_appearance("scrub", adw::ResponseAppearance::Destructive);
+        confirm.set_close_response("cancel");
+
+        let on_scrub = on_scrub.clone();
+        let edited_policy = edited_policy.clone();
+        confirm.connect_response(None, move |_, response| {
+            if response == "scrub" {
+                on_scrub(edited_policy());
+            }
+        });
+        confirm.present(Some(&dialog));
+    }));
+
+    dialog.connect_closed(move |_| {
+        on_save(edited_policy());
+        on_save_retention(RetentionPolicy {
+            max_entries: limit(&max_entries_row).map(|n| n as usize),
+            max_age_days: limit(&max_age_row).map(|n| n as u32),
+            max_body_bytes: limit(&max_body_row).map(|n| n * MEGABYTE),
+        });
+    });
+
+    dialog.present(Some(parent));
+}
+
+/// A number row where 0 stands for no limit.
+fn limit_row(title: &str, subtitle: &str, max: f64, step: f64, value: Option<f64>) -> SpinRow {
+    let row = SpinRow::with_range(0.0, max, step);
+    row.set_title(title);
+    row.set_subtitle(subtitle);
+    row.set_value(value.unwrap_or(0.0));
+    row
+}
+
+fn limit(row: &SpinRow) -> Option<u64> {
+    let value = row.value() as u64;
+    (value > 0).then_some(value)
+}
+
+fn split_list(text: &str) -> Vec<String> {
+    text.split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(String::from)
+        .collect()
+}
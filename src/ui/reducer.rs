@@ -1,20 +1,49 @@
 use gtk::prelude::*;
-use std::{rc::Rc, thread};
+use std::{
+    cell::RefCell,
+    fs,
+    path::Path,
+    rc::Rc,
+    sync::{Arc, atomic::AtomicBool, atomic::Ordering},
+    thread,
+};
 
 use glib::Sender;
 
 use crate::{
-    api, config,
-    database::Database,
-    models::Method,
-    state::Action,
-    ui::{helpers::set_syntax_highlighting, sidebar, widgets::WindowWidgets},
+    api, config, contract,
+    database::{self, Database, HistoryItem},
+    diff::DiffSide,
+    extractors,
+    formats::{ImportError, curl, data::DataSet, har, http, openapi, postman},
+    models::{
+        CollectionItem, ContractLink, Extraction, ItemKind, Method, Request, SourcedRequest,
+        Variable,
+    },
+    runner,
+    scripting::ScriptOutput,
+    secrets::{self, SecretsError},
+    snapshot::{Masking, Snapshot, SnapshotStore},
+    state::{Action, AppState},
+    ui::{
+        codegen_dialog, collections_view, contract_view, diff_dialog, environment_bar,
+        environment_dialog, file_dialogs, helpers::set_syntax_highlighting, history_details_dialog,
+        import_report, passphrase_dialog, preferences_dialog, sidebar, widgets::WindowWidgets,
+    },
+    variables::{self, Scope},
 };
 
+const ACTIVE_ENVIRONMENT_SETTING: &str = "active_environment";
+// JSON list of the paths and headers the diff view leaves out
+const DIFF_IGNORED_SETTING: &str = "diff_ignored";
+// Requests saved without a collection open go here
+const DEFAULT_COLLECTION_NAME: &str = "My Requests";
+
 pub fn handle_action(
     action: Action,
     w: &WindowWidgets,
     db: &Rc<Database>,
+    state: &Rc<RefCell<AppState>>,
     sender: &Sender<Action>,
 ) {
     match action {
@@ -22,28 +51,225 @@ pub fn handle_action(
         Action::UpdateMethod(method) => w.method_dropdown.set_selected(method.to_index()),
         Action::UpdateBody(body) => w.request_body_buffer.set_text(&body),
         Action::UpdateHeaders(headers) => w.headers_editor.set_data(headers),
+        Action::UpdateOptions(options) => w.options_editor.set_data(options),
+        Action::UpdateContract(link) => w.contract_view.set_link(link),
+        Action::UpdateAssertions(assertions) => w.assertion_editor.set_data(assertions),
+        Action::UpdateExtractors(extractors) => w.extractor_editor.set_data(extractors),
+        Action::UpdateScripts(scripts) => w.script_editor.set_data(scripts),
+
+        Action::ImportCurl(command) => match curl::parse(&command) {
+            Ok(request) => {
+                sender.send(Action::UpdateMethod(request.method)).unwrap();
+                sender.send(Action::UpdateUrl(request.url)).unwrap();
+                sender.send(Action::UpdateHeaders(request.headers)).unwrap();
+                sender.send(Action::UpdateBody(request.body)).unwrap();
+                sender.send(Action::UpdateOptions(request.options)).unwrap();
+            }
+            Err(e) => {
+                w.status_label.set_text("Invalid curl command");
+                w.status_label.set_tooltip_text(Some(&e.to_string()));
+                w.status_label.add_css_class(config::CLASS_ERROR);
+                w.status_label.remove_css_class(config::CLASS_SUCCESS);
+            }
+        },
+
+        Action::SelectEnvironment(id) => {
+            state.borrow_mut().active_environment = id;
+            let value = id.map(|id| id.to_string());
+            let _ = db.set_setting(ACTIVE_ENVIRONMENT_SETTING, value.as_deref());
+        }
 
         Action::NewRequest => {
+            let mut s = state.borrow_mut();
+            s.active_collection = None;
+            s.active_item = None;
+            drop(s);
+            w.contract_view.set_link(None);
             w.url_entry.set_text("");
             w.method_dropdown.set_selected(0);
             w.request_body_buffer.set_text("");
             w.headers_editor.clear();
+            w.options_editor.clear();
+            w.assertion_editor.clear();
+            w.extractor_editor.clear();
+            w.script_editor.clear();
+            w.test_results_view.clear();
+            w.captured_label.set_visible(false);
+            w.console_buffer.set_text("");
             w.response_buffer.set_text("");
             w.response_headers_buffer.set_text("");
             w.status_label.set_text("-");
             w.status_label.remove_css_class(config::CLASS_SUCCESS);
             w.status_label.remove_css_class(config::CLASS_ERROR);
+            state.borrow_mut().shown_response = None;
+            show_snapshot(w, db, state, sender);
+        }
+
+        Action::ClearHistory => trash_history(w, db, sender, None),
+
+        Action::DeleteHistoryItem(id) => {
+            let selected = sidebar::selected_history_ids(&w.history_list, &w.history_model);
+            let ids = if selected.contains(&id) {
+                selected
+            } else {
+                vec![id]
+            };
+            trash_history(w, db, sender, Some(&ids));
+        }
+
+        Action::DeleteSelectedHistory => {
+            let selected = sidebar::selected_history_ids(&w.history_list, &w.history_model);
+            if !selected.is_empty() {
+                trash_history(w, db, sender, Some(&selected));
+            }
+        }
+
+        Action::CompareWithResponse(id) => {
+            let Some(shown) = state.borrow().shown_response.clone() else {
+                let message = "No response to compare with".to_string();
+                sender.send(Action::ShowToast(message)).unwrap();
+                return;
+            };
+            match db.get_request_by_id(id) {
+                Ok(item) => compare_responses(w, db, sender, DiffSide::from_history(&item), shown),
+                Err(e) => {
+                    let message = format!("Entry not found: {}", e);
+                    sender.send(Action::ShowToast(message)).unwrap();
+                }
+            }
+        }
+
+        Action::CompareSelectedHistory => {
+            let mut selected = sidebar::selected_history_ids(&w.history_list, &w.history_model);
+            let [older, newer] = selected.as_mut_slice() else {
+                let message = "Select two entries to compare".to_string();
+                sender.send(Action::ShowToast(message)).unwrap();
+                return;
+            };
+            if older > newer {
+                std::mem::swap(older, newer);
+            }
+            match (db.get_request_by_id(*older), db.get_request_by_id(*newer)) {
+                (Ok(before), Ok(after)) => compare_responses(
+                    w,
+                    db,
+                    sender,
+                    DiffSide::from_history(&before),
+                    DiffSide::from_history(&after),
+                ),
+                (Err(e), _) | (_, Err(e)) => {
+                    let message = format!("Entry not found: {}", e);
+                    sender.send(Action::ShowToast(message)).unwrap();
+                }
+            }
+        }
+
+        Action::SaveDiffIgnored(ignored) => {
+            let json = serde_json::to_string(&ignored).unwrap_or_default();
+            if let Err(e) = db.set_setting(DIFF_IGNORED_SETTING, Some(&json)) {
+                let message = format!("Ignored paths not saved: {}", e);
+                sender.send(Action::ShowToast(message)).unwrap();
+            }
+        }
+
+        Action::RestoreHistory(batch) => {
+            if let Err(e) = db.restore_history(batch) {
+                let message = format!("History not restored: {}", e);
+                sender.send(Action::ShowToast(message)).unwrap();
+            }
+            reload_history(w);
+        }
+
+        Action::PurgeHistory(batch) => {
+            let _ = db.purge_history(Some(batch));
+        }
+
+        Action::TogglePinHistoryItem(id) => {
+            let result = db
+                .get_request_by_id(id)
+                .and_then(|item| db.set_history_pinned(id, !item.pinned));
+            match result {
+                Ok(()) => reload_history(w),
+                Err(e) => {
+                    let message = format!("Entry not pinned: {}", e);
+                    sender.send(Action::ShowToast(message)).unwrap();
+                }
+            }
+        }
+
+        Action::RenameHistoryItem(id) => {
+            if let Ok(item) = db.get_request_by_id(id) {
+                let on_rename = glib::clone!(@strong sender => move |label| {
+                    sender.send(Action::SetHistoryLabel(id, label)).unwrap();
+                });
+                history_details_dialog::rename(&w.window, &item.label, on_rename);
+            }
         }
 
-        Action::ClearHistory => {
-            let _ = db.clear_history();
-            while let Some(row) = w.history_list.first_child() {
-                w.history_list.remove(&row);
+        Action::SetHistoryLabel(id, label) => match db.set_history_label(id, &label) {
+            Ok(()) => reload_history(w),
+            Err(e) => {
+                let message = format!("Entry not renamed: {}", e);
+                sender.send(Action::ShowToast(message)).unwrap();
+            }
+        },
+
+        Action::AnnotateHistoryItem(id) => {
+            if let Ok(item) = db.get_request_by_id(id) {
+                let on_save = glib::clone!(@strong sender => move |note, tags| {
+                    sender.send(Action::SetHistoryNotes(id, note, tags)).unwrap();
+                });
+                history_details_dialog::annotate(&w.window, &item.note, &item.tags, on_save);
+            }
+        }
+
+        Action::SetHistoryNotes(id, note, tags) => match db.set_history_notes(id, &note, &tags) {
+            Ok(()) => reload_history(w),
+            Err(e) => {
+                let message = format!("Note not saved: {}", e);
+                sender.send(Action::ShowToast(message)).unwrap();
+            }
+        },
+
+        Action::CopyHistoryUrl(id) => {
+            if let Ok(item) = db.get_request_by_id(id) {
+                w.window.clipboard().set_text(&item.url);
+                sender
+                    .send(Action::ShowToast("URL copied".to_string()))
+                    .unwrap();
+            }
+        }
+
+        // The request only, as if typed into a new one
+        Action::DuplicateHistoryItem(id) => {
+            if let Ok(item) = db.get_request_by_id(id) {
+                let request = item.to_request();
+                sender.send(Action::NewRequest).unwrap();
+                sender.send(Action::UpdateMethod(request.method)).unwrap();
+                sender.send(Action::UpdateUrl(request.url)).unwrap();
+                sender.send(Action::UpdateHeaders(request.headers)).unwrap();
+                sender.send(Action::UpdateBody(request.body)).unwrap();
             }
         }
 
         Action::LoadHistoryItem(id) => {
             if let Ok(item) = db.get_request_by_id(id) {
+                let mut s = state.borrow_mut();
+                s.active_collection = None;
+                s.active_item = None;
+                drop(s);
+                // History does not keep contract links, assertions, captures or scripts, only results
+                sender.send(Action::UpdateContract(None)).unwrap();
+                sender.send(Action::UpdateAssertions(Vec::new())).unwrap();
+                sender.send(Action::UpdateExtractors(Vec::new())).unwrap();
+                sender
+                    .send(Action::UpdateScripts(Default::default()))
+                    .unwrap();
+                w.captured_label.set_visible(false);
+                w.console_buffer.set_text("");
+                w.test_results_view
+                    .show(&db.get_assertion_results(id).unwrap_or_default());
+                state.borrow_mut().shown_response = Some(DiffSide::from_history(&item));
                 sender.send(Action::UpdateUrl(item.url)).unwrap();
                 if let Ok(m) = item.method.parse::<Method>() {
                     sender.send(Action::UpdateMethod(m)).unwrap();
@@ -54,6 +280,7 @@ pub fn handle_action(
                     sender.send(Action::UpdateHeaders(h)).unwrap();
                 }
 
+                show_snapshot(w, db, state, sender);
                 w.response_buffer.set_text(&item.response_body);
                 w.response_headers_buffer.set_text(&item.response_headers);
                 w.status_label.set_text(&item.status);
@@ -72,29 +299,1166 @@ pub fn handle_action(
             }
         }
 
+        Action::LoadCollectionItem(id) => {
+            let Ok(item) = db.get_collection_item(id) else {
+                return;
+            };
+            if item.kind != ItemKind::Request {
+                return;
+            }
+
+            let mut s = state.borrow_mut();
+            s.active_collection = Some(item.collection_id);
+            s.active_item = Some(item.id);
+            drop(s);
+
+            let request = item.request;
+            sender.send(Action::UpdateMethod(request.method)).unwrap();
+            sender.send(Action::UpdateUrl(request.url)).unwrap();
+            sender.send(Action::UpdateHeaders(request.headers)).unwrap();
+            sender.send(Action::UpdateBody(request.body)).unwrap();
+            sender.send(Action::UpdateOptions(request.options)).unwrap();
+            sender
+                .send(Action::UpdateContract(request.contract))
+                .unwrap();
+            sender
+                .send(Action::UpdateAssertions(request.assertions))
+                .unwrap();
+            sender
+                .send(Action::UpdateExtractors(request.extractors))
+                .unwrap();
+            sender.send(Action::UpdateScripts(request.scripts)).unwrap();
+
+            w.test_results_view.clear();
+            w.captured_label.set_visible(false);
+            w.console_buffer.set_text("");
+            w.response_buffer.set_text("");
+            w.response_headers_buffer.set_text("");
+            w.status_label.set_text("-");
+            w.status_label.remove_css_class(config::CLASS_SUCCESS);
+            w.status_label.remove_css_class(config::CLASS_ERROR);
+            state.borrow_mut().shown_response = None;
+            show_snapshot(w, db, state, sender);
+        }
+
+        Action::SaveRequest => {
+            let request = w.current_request();
+            let active_item = state.borrow().active_item;
+
+            let saved = match active_item {
+                Some(id) => db
+                    .get_collection_item(id)
+                    .map_err(|e| e.to_string())
+                    .and_then(|item| {
+                        // Requests from a `.http` file are saved as the file now reads
+                        let request = write_back_http_file(db, &item, &request)?.unwrap_or(request);
+                        db.update_collection_item_request(id, &request)
+                            .map_err(|e| e.to_string())?;
+                        Ok(item.name)
+                    }),
+                None => {
+                    let name = format!("{} {}", request.method.as_str(), request.url);
+                    let collection_id = match db
+                        .get_collections()
+                        .ok()
+                        .and_then(|c| c.into_iter().find(|c| c.name == DEFAULT_COLLECTION_NAME))
+                    {
+                        Some(collection) => Ok(collection.id),
+                        None => db.create_collection(DEFAULT_COLLECTION_NAME, &[]),
+                    };
+                    collection_id
+                        .and_then(|collection_id| {
+                            let id = db.insert_collection_item(
+                                collection_id,
+                                None,
+                                ItemKind::Request,
+                                &name,
+                                &request,
+                            )?;
+                            let mut s = state.borrow_mut();
+                            s.active_collection = Some(collection_id);
+                            s.active_item = Some(id);
+                            Ok(())
+                        })
+                        .map(|_| name)
+                        .map_err(|e| e.to_string())
+                }
+            };
+
+            let message = match saved {
+                Ok(name) => {
+                    reload_collections(w, db);
+                    show_snapshot(w, db, state, sender);
+                    format!("Saved “{}”", name)
+                }
+                Err(e) => format!("Save failed: {}", e),
+            };
+            sender.send(Action::ShowToast(message)).unwrap();
+        }
+
+        Action::GenerateCode => {
+            let secret_values = db.secret_values().unwrap_or_default();
+            let request = secrets::redact_request(&w.current_request(), &secret_values);
+            codegen_dialog::present(&w.window, request);
+        }
+
+        Action::GenerateCodeForHistory(id) => {
+            if let Ok(item) = db.get_request_by_id(id) {
+                let secret_values = db.secret_values().unwrap_or_default();
+                let request = secrets::redact_request(&item.to_request(), &secret_values);
+                codegen_dialog::present(&w.window, request);
+            }
+        }
+
+        Action::ExportHar(id) => {
+            let tx = sender.clone();
+            file_dialogs::save(&w.window, "Export HAR", "dispatch.har", move |path| {
+                tx.send(Action::ExportHarTo(id, path)).unwrap();
+            });
+        }
+
+        Action::ExportHarTo(id, path) => {
+            let items = match id {
+                Some(id) => db.get_request_by_id(id).map(|item| vec![item]),
+                None => db.get_all_history(),
+            };
+            // Entries saved before a variable was marked secret, or before the
+            // redaction policy changed, may still hold sensitive values
+            let items = items.and_then(|items| {
+                let redact = runner::history_redaction(db)?;
+                Ok(items.iter().map(redact).collect::<Vec<_>>())
+            });
+
+            let message = match items {
+                Ok(items) => match fs::write(&path, har::export(&items)) {
+                    Ok(()) => format!("Exported {} entries to {}", items.len(), path.display()),
+                    Err(e) => format!("Export failed: {}", e),
+                },
+                Err(e) => format!("Export failed: {}", e),
+            };
+            sender.send(Action::ShowToast(message)).unwrap();
+        }
+
+        Action::ImportHar => {
+            let tx = sender.clone();
+            file_dialogs::open(
+                &w.window,
+                "Import HAR",
+                &[("HAR files", &["*.har", "*.json"])],
+                move |path| {
+                    tx.send(Action::ImportHarFrom(path)).unwrap();
+                },
+            );
+        }
+
+        Action::ImportHarFrom(path) => {
+            let result = fs::read_to_string(&path)
+                .map_err(ImportError::from)
+                .and_then(|json| har::import(&json))
+                .map_err(|e| e.to_string())
+                .and_then(|import| {
+                    // Masked like entries sent from here before they are stored
+                    let redact = runner::history_redaction(db).map_err(|e| e.to_string())?;
+                    let items: Vec<HistoryItem> = import.items.iter().map(redact).collect();
+                    let imported = db.import_exchanges(&items).map_err(|e| e.to_string())?;
+                    Ok((imported, import.skipped))
+                });
+
+            let message = match result {
+                Ok((imported, skipped)) => {
+                    reload_history(w);
+
+                    if skipped > 0 {
+                        format!(
+                            "Imported {} entries, skipped {} with unsupported methods or timings",
+                            imported, skipped
+                        )
+                    } else {
+                        format!("Imported {} entries", imported)
+                    }
+                }
+                Err(e) => format!("Import failed: {}", e),
+            };
+            sender.send(Action::ShowToast(message)).unwrap();
+        }
+
+        Action::EditHistoryPreferences => {
+            let policy = db.redaction_policy().unwrap_or_default();
+            let retention = db.retention_policy().unwrap_or_default();
+            let usage = db.storage_usage().unwrap_or_default();
+            let on_save = glib::clone!(@strong sender => move |policy| {
+                sender.send(Action::SaveRedactionPolicy(policy)).unwrap();
+            });
+            let on_save_retention = glib::clone!(@strong sender => move |policy| {
+                sender.send(Action::SaveRetentionPolicy(policy)).unwrap();
+            });
+            let on_scrub = glib::clone!(@strong sender => move |policy| {
+                sender.send(Action::ScrubHistory(policy)).unwrap();
+            });
+            preferences_dialog::present(
+                &w.window,
+                policy,
+                retention,
+                usage,
+                on_save,
+                on_save_retention,
+                on_scrub,
+            );
+        }
+
+        Action::SaveRedactionPolicy(policy) => {
+            let result = policy
+                .validate()
+                .and_then(|()| db.set_redaction_policy(&policy).map_err(|e| e.to_string()));
+            if let Err(e) = result {
+                let message = format!("Redaction policy not saved: {}", e);
+                sender.send(Action::ShowToast(message)).unwrap();
+            }
+        }
+
+        Action::SearchHistory(mut filter, grouping) => {
+            if w.history_search.current_host_only() {
+                filter.host = url_host(&w.url_entry.text()).to_string();
+            }
+            // Typing in the URL bar asks again on every key
+            let model = &w.history_model;
+            if model.filter() == filter && model.grouping() == grouping {
+                return;
+            }
+            if let Err(e) = model.set_filter(filter, grouping) {
+                let message = format!("History search failed: {}", e);
+                sender.send(Action::ShowToast(message)).unwrap();
+            }
+        }
+
+        Action::SaveRetentionPolicy(policy) => {
+            if db.retention_policy().ok().as_ref() == Some(&policy) {
+                return;
+            }
+            match db.set_retention_policy(&policy) {
+                Ok(()) => sender.send(Action::RunMaintenance).unwrap(),
+                Err(e) => {
+                    let message = format!("Retention policy not saved: {}", e);
+                    sender.send(Action::ShowToast(message)).unwrap();
+                }
+            }
+        }
+
+        Action::RunMaintenance => {
+            let policy = match db.retention_policy() {
+                Ok(policy) => policy,
+                Err(e) => {
+                    let message = format!("History cleanup failed: {}", e);
+                    sender.send(Action::ShowToast(message)).unwrap();
+                    return;
+                }
+            };
+            let mut s = state.borrow_mut();
+            if s.maintaining {
+                // The policy may have changed since that pass started
+                s.maintain_again = true;
+                return;
+            }
+            s.maintaining = true;
+
+            let path = db.path().to_path_buf();
+            let tx = sender.clone();
+            thread::spawn(move || {
+                let result = database::maintain(&path, &policy).map_err(|e| e.to_string());
+                tx.send(Action::MaintenanceFinished(result)).unwrap();
+            });
+        }
+
+        Action::MaintenanceFinished(result) => {
+            let mut s = state.borrow_mut();
+            s.maintaining = false;
+            if s.maintain_again {
+                s.maintain_again = false;
+                sender.send(Action::RunMaintenance).unwrap();
+            }
+            drop(s);
+
+            match result {
+                Ok(report) if report.removed > 0 => {
+                    reload_history(w);
+                    let message = format!("Removed {} old history entries", report.removed);
+                    sender.send(Action::ShowToast(message)).unwrap();
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    let message = format!("History cleanup failed: {}", e);
+                    sender.send(Action::ShowToast(message)).unwrap();
+                }
+            }
+        }
+
+        Action::ScrubHistory(policy) => {
+            let result = policy
+                .validate()
+                .and_then(|()| db.set_redaction_policy(&policy).map_err(|e| e.to_string()))
+                .and_then(|()| {
+                    let redact = runner::history_redaction(db).map_err(|e| e.to_string())?;
+                    db.scrub_history(redact).map_err(|e| e.to_string())
+                });
+            let message = match result {
+                Ok(scrubbed) => {
+                    reload_history(w);
+                    format!("Scrubbed {} entries", scrubbed)
+                }
+                Err(e) => format!("Scrub failed: {}", e),
+            };
+            sender.send(Action::ShowToast(message)).unwrap();
+        }
+
+        Action::ImportPostmanCollection => {
+            let tx = sender.clone();
+            file_dialogs::open(
+                &w.window,
+                "Import Postman Collection",
+                &[("Postman collections", &["*.json"])],
+                move |path| {
+                    tx.send(Action::ImportPostmanCollectionFrom(path)).unwrap();
+                },
+            );
+        }
+
+        Action::ImportPostmanCollectionFrom(path) => {
+            let result = fs::read_to_string(&path)
+                .map_err(ImportError::from)
+                .and_then(|json| postman::import_collection(&json));
+
+            if let Ok(import) = &result
+                && needs_passphrase(db, &import.value.variables)
+            {
+                prompt_unlock(
+                    w,
+                    db,
+                    state,
+                    sender,
+                    Some(Action::ImportPostmanCollectionFrom(path)),
+                );
+                return;
+            }
+
+            let message = match result {
+                Ok(import) => match db.import_collection(&import.value) {
+                    Ok(_) => {
+                        reload_collections(w, db);
+                        if !import.warnings.is_empty() {
+                            import_report::present(
+                                &w.window,
+                                &format!("Imported “{}”", import.value.name),
+                                &import.warnings,
+                            );
+                        }
+                        format!("Imported collection “{}”", import.value.name)
+                    }
+                    Err(e) => format!("Import failed: {}", e),
+                },
+                Err(e) => format!("Import failed: {}", e),
+            };
+            sender.send(Action::ShowToast(message)).unwrap();
+        }
+
+        Action::ExportPostmanCollection(id) => {
+            let name = db
+                .get_collection(id)
+                .map(|c| c.name)
+                .unwrap_or_else(|_| "collection".to_string());
+            let tx = sender.clone();
+            file_dialogs::save(
+                &w.window,
+                "Export Postman Collection",
+                &format!("{}.postman_collection.json", name),
+                move |path| {
+                    tx.send(Action::ExportPostmanCollectionTo(id, path))
+                        .unwrap();
+                },
+            );
+        }
+
+        Action::ExportPostmanCollectionTo(id, path) => {
+            let secret_values = db.secret_values().unwrap_or_default();
+            let message = match db.get_collection_tree(id) {
+                Ok(tree) => match fs::write(
+                    &path,
+                    postman::export_collection(&secrets::redact_tree(&tree, &secret_values)),
+                ) {
+                    Ok(()) => format!("Exported “{}” to {}", tree.name, path.display()),
+                    Err(e) => format!("Export failed: {}", e),
+                },
+                Err(e) => format!("Export failed: {}", e),
+            };
+            sender.send(Action::ShowToast(message)).unwrap();
+        }
+
+        Action::DeleteCollection(id) => {
+            let _ = db.delete_collection(id);
+            let mut state = state.borrow_mut();
+            if state.active_collection == Some(id) {
+                state.active_collection = None;
+                state.active_item = None;
+            }
+            drop(state);
+            reload_collections(w, db);
+        }
+
+        Action::ImportOpenApi => {
+            let tx = sender.clone();
+            file_dialogs::open(
+                &w.window,
+                "Import OpenAPI Specification",
+                &[("OpenAPI and Swagger specs", &["*.yaml", "*.yml", "*.json"])],
+                move |path| {
+                    tx.send(Action::ImportOpenApiFrom(path)).unwrap();
+                },
+            );
+        }
+
+        // Importing the same spec again merges into the collection it created
+        Action::ImportOpenApiFrom(path) => {
+            let result = fs::read_to_string(&path)
+                .map_err(ImportError::from)
+                .and_then(|text| openapi::import(&text));
+
+            let message = match result {
+                Ok(mut import) => {
+                    // Generated requests are checked against the operation they came from
+                    for sourced in &mut import.requests {
+                        sourced.request.contract = Some(ContractLink::OpenApi {
+                            spec: path.clone(),
+                            operation: sourced.key.clone(),
+                        });
+                    }
+                    match db.sync_collection(
+                        &openapi::collection_source(&path),
+                        &import.name,
+                        &import.variables,
+                        &import.requests,
+                    ) {
+                        Ok(summary) => {
+                            reload_collections(w, db);
+                            for name in &summary.kept {
+                                import
+                                    .warnings
+                                    .push(format!("{}: kept your local changes", name));
+                            }
+                            if !import.warnings.is_empty() {
+                                import_report::present(
+                                    &w.window,
+                                    &format!("Imported “{}”", import.name),
+                                    &import.warnings,
+                                );
+                            }
+                            format!(
+                                "“{}”: {} added, {} updated, {} removed",
+                                import.name, summary.added, summary.updated, summary.removed
+                            )
+                        }
+                        Err(e) => format!("Import failed: {}", e),
+                    }
+                }
+                Err(e) => format!("Import failed: {}", e),
+            };
+            sender.send(Action::ShowToast(message)).unwrap();
+        }
+
+        Action::OpenHttpFile => {
+            let tx = sender.clone();
+            file_dialogs::open(
+                &w.window,
+                "Open .http File",
+                &[("HTTP request files", &["*.http", "*.rest"])],
+                move |path| {
+                    tx.send(Action::OpenHttpFileFrom(path)).unwrap();
+                },
+            );
+        }
+
+        // The file stays the source of truth: opening it again picks up its changes
+        Action::OpenHttpFileFrom(path) => {
+            let path = fs::canonicalize(&path).unwrap_or(path);
+            let base_dir = path.parent().unwrap_or(Path::new("."));
+            let result = fs::read_to_string(&path)
+                .map_err(ImportError::from)
+                .and_then(|text| http::parse(&text, base_dir));
+
+            let message = match result {
+                Ok(mut file) => {
+                    let name = path
+                        .file_stem()
+                        .map(|stem| stem.to_string_lossy().into_owned())
+                        .unwrap_or_else(|| path.display().to_string());
+                    let requests: Vec<SourcedRequest> = file
+                        .requests
+                        .iter()
+                        .map(|r| SourcedRequest {
+                            key: r.name.clone(),
+                            folder: None,
+                            name: r.name.clone(),
+                            request: r.request.clone(),
+                        })
+                        .collect();
+                    let source = format!("{}{}", http::COLLECTION_SOURCE, path.display());
+
+                    match db.sync_collection(&source, &name, &file.variables, &requests) {
+                        Ok(summary) => {
+                            reload_collections(w, db);
+                            for kept in &summary.kept {
+                                file.warnings
+                                    .push(format!("{}: kept your local changes", kept));
+                            }
+                            if !file.warnings.is_empty() {
+                                import_report::present(
+                                    &w.window,
+                                    &format!("Opened “{}”", name),
+                                    &file.warnings,
+                                );
+                            }
+                            format!(
+                                "“{}”: {} added, {} updated, {} removed",
+                                name, summary.added, summary.updated, summary.removed
+                            )
+                        }
+                        Err(e) => format!("Open failed: {}", e),
+                    }
+                }
+                Err(e) => format!("Open failed: {}", e),
+            };
+            sender.send(Action::ShowToast(message)).unwrap();
+        }
+
+        Action::ExportHttpCollection(id) => {
+            let name = db
+                .get_collection(id)
+                .map(|c| c.name)
+                .unwrap_or_else(|_| "collection".to_string());
+            let tx = sender.clone();
+            file_dialogs::save(
+                &w.window,
+                "Export as .http",
+                &format!("{}.http", name),
+                move |path| {
+                    tx.send(Action::ExportHttpCollectionTo(id, path)).unwrap();
+                },
+            );
+        }
+
+        Action::ExportHttpCollectionTo(id, path) => {
+            let secret_values = db.secret_values().unwrap_or_default();
+            let message = match db.get_collection_tree(id) {
+                Ok(tree) => match fs::write(
+                    &path,
+                    http::export_collection(&secrets::redact_tree(&tree, &secret_values)),
+                ) {
+                    Ok(()) => format!("Exported “{}” to {}", tree.name, path.display()),
+                    Err(e) => format!("Export failed: {}", e),
+                },
+                Err(e) => format!("Export failed: {}", e),
+            };
+            sender.send(Action::ShowToast(message)).unwrap();
+        }
+
+        Action::LinkOpenApiContract => {
+            let tx = sender.clone();
+            file_dialogs::open(
+                &w.window,
+                "Link OpenAPI Operation",
+                &[("OpenAPI and Swagger specs", &["*.yaml", "*.yml", "*.json"])],
+                move |path| {
+                    tx.send(Action::LinkOpenApiContractFrom(path)).unwrap();
+                },
+            );
+        }
+
+        Action::LinkOpenApiContractFrom(path) => match contract::operations(&path) {
+            Ok(operations) => {
+                let tx = sender.clone();
+                contract_view::choose_operation(&w.window, operations, move |operation| {
+                    let link = ContractLink::OpenApi {
+                        spec: path.clone(),
+                        operation,
+                    };
+                    tx.send(Action::UpdateContract(Some(link))).unwrap();
+                });
+            }
+            Err(e) => sender.send(Action::ShowToast(e)).unwrap(),
+        },
+
+        Action::LinkJsonSchemaContract => {
+            let tx = sender.clone();
+            file_dialogs::open(
+                &w.window,
+                "Link JSON Schema",
+                &[("JSON Schema", &["*.json", "*.yaml", "*.yml"])],
+                move |path| {
+                    tx.send(Action::UpdateContract(Some(ContractLink::JsonSchema {
+                        path,
+                    })))
+                    .unwrap();
+                },
+            );
+        }
+
+        Action::RecordSnapshot => {
+            let s = state.borrow();
+            let Some(store) = snapshot_store(db, &s) else {
+                drop(s);
+                let message = "Save the request to record a snapshot".to_string();
+                sender.send(Action::ShowToast(message)).unwrap();
+                return;
+            };
+            let Some(shown) = s.shown_response.clone() else {
+                drop(s);
+                let message = "Send the request to record its response".to_string();
+                sender.send(Action::ShowToast(message)).unwrap();
+                return;
+            };
+            drop(s);
+
+            let ignored = store
+                .load(db)
+                .ok()
+                .flatten()
+                .map(|snapshot| snapshot.ignored)
+                .unwrap_or_default();
+            let saved = Masking::load(db).and_then(|masking| {
+                let snapshot = Snapshot::record(&shown.status, &shown.body, ignored, &masking);
+                store.save(db, Some(&snapshot))
+            });
+            let message = match saved {
+                Ok(()) => "Snapshot recorded".to_string(),
+                Err(e) => format!("Snapshot not saved: {}", e),
+            };
+            show_snapshot(w, db, state, sender);
+            sender.send(Action::ShowToast(message)).unwrap();
+        }
+
+        Action::DeleteSnapshot => {
+            let Some(store) = snapshot_store(db, &state.borrow()) else {
+                return;
+            };
+            if let Err(e) = store.save(db, None) {
+                let message = format!("Snapshot not deleted: {}", e);
+                sender.send(Action::ShowToast(message)).unwrap();
+            }
+            show_snapshot(w, db, state, sender);
+        }
+
+        Action::CompareWithSnapshot => {
+            let s = state.borrow();
+            let Some(Ok(Some(snapshot))) = snapshot_store(db, &s).map(|store| store.load(db))
+            else {
+                drop(s);
+                let message = "No snapshot recorded for this request".to_string();
+                sender.send(Action::ShowToast(message)).unwrap();
+                return;
+            };
+            let Some(shown) = s.shown_response.clone() else {
+                drop(s);
+                let message = "Send the request to compare its response".to_string();
+                sender.send(Action::ShowToast(message)).unwrap();
+                return;
+            };
+            drop(s);
+            let masking = match Masking::load(db) {
+                Ok(masking) => masking,
+                Err(e) => {
+                    sender.send(Action::ShowToast(e)).unwrap();
+                    return;
+                }
+            };
+            // Compared as it would be recorded
+            let shown = DiffSide {
+                body: masking.body(&shown.body),
+                ..shown
+            };
+
+            // Snapshots leave headers out, so those are taken as unchanged
+            let before = DiffSide {
+                title: "Snapshot".to_string(),
+                status: snapshot.status.clone(),
+                headers: shown.headers.clone(),
+                body: snapshot.body.clone(),
+                ..Default::default()
+            };
+            let saved = snapshot.ignored.clone();
+            diff_dialog::present(
+                &w.window,
+                before,
+                shown,
+                snapshot.ignored,
+                glib::clone!(@strong sender => move |ignored| {
+                    if ignored != saved {
+                        sender.send(Action::SetSnapshotIgnored(ignored)).unwrap();
+                    }
+                }),
+            );
+        }
+
+        Action::SetSnapshotIgnored(ignored) => {
+            let Some(store) = snapshot_store(db, &state.borrow()) else {
+                return;
+            };
+            let Ok(Some(mut snapshot)) = store.load(db) else {
+                return;
+            };
+            snapshot.ignored = ignored;
+            if let Err(e) = store.save(db, Some(&snapshot)) {
+                let message = format!("Snapshot not saved: {}", e);
+                sender.send(Action::ShowToast(message)).unwrap();
+            }
+            show_snapshot(w, db, state, sender);
+        }
+
+        Action::RunCollection(id) => open_runner(w, db, state, sender, id, None),
+
+        Action::RunItem(id) => match db.get_collection_item(id) {
+            Ok(item) => open_runner(w, db, state, sender, item.collection_id, Some(id)),
+            Err(e) => sender.send(Action::ShowToast(e.to_string())).unwrap(),
+        },
+
+        Action::ChooseRunData => {
+            let tx = sender.clone();
+            file_dialogs::open(
+                &w.window,
+                "Choose Data File",
+                &[("CSV or JSON", &["*.csv", "*.json"]), ("All Files", &["*"])],
+                move |path| {
+                    tx.send(Action::LoadRunData(path)).unwrap();
+                },
+            );
+        }
+
+        Action::LoadRunData(path) => match DataSet::load(&path) {
+            Ok(data) => {
+                let name = path
+                    .file_name()
+                    .map(|n| n.to_string_lossy().into_owned())
+                    .unwrap_or_default();
+                w.runner_view.set_data(Some((&name, data.rows.len())));
+                state.borrow_mut().run_data = Some(data);
+            }
+            Err(e) => {
+                let message = format!("Could not read {}: {}", path.display(), e);
+                sender.send(Action::ShowToast(message)).unwrap();
+            }
+        },
+
+        Action::ClearRunData => {
+            state.borrow_mut().run_data = None;
+            w.runner_view.set_data(None);
+        }
+
+        Action::StartRun => {
+            let Some((collection_id, folder_id)) = state.borrow().run_target else {
+                return;
+            };
+            if state.borrow().run_cancel.is_some() {
+                return;
+            }
+            // Loaded again so edits made while the dialog was open are included
+            let target = match runner::collection_target(db, collection_id, folder_id) {
+                Ok(target) => target,
+                Err(e) => {
+                    sender.send(Action::ShowToast(e)).unwrap();
+                    return;
+                }
+            };
+
+            let s = state.borrow();
+            let environment = s
+                .active_environment
+                .and_then(|id| s.environments.iter().find(|e| e.id == id))
+                .map(|e| e.variables.clone())
+                .unwrap_or_default();
+            let data = s.run_data.clone();
+            drop(s);
+
+            let variables = [target.variables.as_slice(), &environment].concat();
+            if target
+                .requests
+                .iter()
+                .any(|(_, request)| secrets::uses_locked(request, &variables))
+            {
+                prompt_unlock(w, db, state, sender, Some(Action::StartRun));
+                return;
+            }
+
+            let options = w.runner_view.options();
+            let passes =
+                options.iterations.max(1) as usize * data.as_ref().map_or(1, |d| d.rows.len());
+            let total = target.requests.len() * passes;
+            let cancel = Arc::new(AtomicBool::new(false));
+            {
+                let mut s = state.borrow_mut();
+                s.run_cancel = Some(cancel.clone());
+                s.run_total = total;
+                s.run_done = 0;
+                s.last_report = None;
+            }
+            w.runner_view.run_started(total);
+
+            let tx = sender.clone();
+            thread::spawn(move || {
+                let report = runner::run(
+                    &target,
+                    &environment,
+                    data.as_ref(),
+                    options,
+                    &cancel,
+                    |step| tx.send(Action::RunStepCompleted(step.clone())).unwrap(),
+                );
+                tx.send(Action::RunFinished(report)).unwrap();
+            });
+        }
+
+        Action::StopRun => {
+            if let Some(cancel) = &state.borrow().run_cancel {
+                cancel.store(true, Ordering::Relaxed);
+                w.runner_view.run_stopping();
+            }
+        }
+
+        Action::RunStepCompleted(step) => {
+            let (done, total, data_row) = {
+                let mut s = state.borrow_mut();
+                s.run_done += 1;
+                let data_row = s
+                    .run_data
+                    .as_ref()
+                    .zip(step.row)
+                    .map(|(data, row)| format!("Row {}: {}", row + 1, data.describe(row)));
+                (s.run_done, s.run_total, data_row)
+            };
+            w.runner_view.add_step(&step, data_row, done, total);
+
+            if let Ok(response) = &step.response {
+                let action = match runner::record(db, &step.request, response, &step.assertions) {
+                    Ok(_) => Action::HistorySaved,
+                    Err(e) => Action::ShowToast(format!("Not saved to history: {}", e)),
+                };
+                sender.send(action).unwrap();
+            }
+        }
+
+        Action::RunFinished(report) => {
+            w.runner_view.run_finished(&report);
+            let mut s = state.borrow_mut();
+            s.run_cancel = None;
+            s.last_report = Some(report);
+        }
+
+        Action::ExportRunReport(format) => {
+            let Some(name) = state.borrow().last_report.as_ref().map(|r| r.name.clone()) else {
+                return;
+            };
+            let tx = sender.clone();
+            file_dialogs::save(
+                &w.window,
+                "Export Run Report",
+                &format!("{}.{}", name.replace(" / ", "-"), format.extension()),
+                move |path| {
+                    tx.send(Action::ExportRunReportTo(format, path)).unwrap();
+                },
+            );
+        }
+
+        Action::ExportRunReportTo(format, path) => {
+            let s = state.borrow();
+            let Some(report) = &s.last_report else {
+                return;
+            };
+            // Masked the way history is, since reports get passed around
+            let result = runner::redact_report(db, report)
+                .map_err(|e| e.to_string())
+                .and_then(|report| {
+                    fs::write(&path, format.render(&report)).map_err(|e| e.to_string())
+                });
+            drop(s);
+            let message = match result {
+                Ok(()) => format!("Exported run report to {}", path.display()),
+                Err(e) => format!("Export failed: {}", e),
+            };
+            sender.send(Action::ShowToast(message)).unwrap();
+        }
+
+        Action::ExportFailedRows => {
+            let s = state.borrow();
+            let Some(report) = &s.last_report else {
+                return;
+            };
+            if report.data.is_none() || report.failed_rows().is_empty() {
+                drop(s);
+                let message = "No data rows failed in the last run".to_string();
+                sender.send(Action::ShowToast(message)).unwrap();
+                return;
+            }
+            let name = format!("{} failures.csv", report.name.replace(" / ", "-"));
+            drop(s);
+
+            let tx = sender.clone();
+            file_dialogs::save(&w.window, "Export Failing Rows", &name, move |path| {
+                tx.send(Action::ExportFailedRowsTo(path)).unwrap();
+            });
+        }
+
+        Action::ExportFailedRowsTo(path) => {
+            let s = state.borrow();
+            let Some((report, data)) = s
+                .last_report
+                .as_ref()
+                .and_then(|r| r.data.as_ref().map(|d| (r, d)))
+            else {
+                return;
+            };
+            let rows = report.failed_rows();
+            let text = data.to_csv(&rows);
+            drop(s);
+
+            let message = match fs::write(&path, text) {
+                Ok(()) => format!("Exported {} failing rows to {}", rows.len(), path.display()),
+                Err(e) => format!("Export failed: {}", e),
+            };
+            sender.send(Action::ShowToast(message)).unwrap();
+        }
+
+        Action::AcceptRunSnapshots => {
+            let s = state.borrow();
+            let (Some(report), Some((collection_id, folder_id))) = (&s.last_report, s.run_target)
+            else {
+                return;
+            };
+            let updated = runner::collection_target(db, collection_id, folder_id)
+                .and_then(|target| runner::update_snapshots(db, &target, report));
+            drop(s);
+
+            let message = match updated {
+                Ok(count) => {
+                    w.runner_view.accept_button.set_visible(false);
+                    format!("Updated {} snapshots", count)
+                }
+                Err(e) => format!("Snapshots not updated: {}", e),
+            };
+            sender.send(Action::ShowToast(message)).unwrap();
+            show_snapshot(w, db, state, sender);
+        }
+
+        Action::ImportPostmanEnvironment => {
+            let tx = sender.clone();
+            file_dialogs::open(
+                &w.window,
+                "Import Postman Environment",
+                &[("Postman environments", &["*.json"])],
+                move |path| {
+                    tx.send(Action::ImportPostmanEnvironmentFrom(path)).unwrap();
+                },
+            );
+        }
+
+        Action::ImportPostmanEnvironmentFrom(path) => {
+            let result = fs::read_to_string(&path)
+                .map_err(ImportError::from)
+                .and_then(|json| postman::import_environment(&json));
+
+            if let Ok(import) = &result
+                && needs_passphrase(db, &import.value.1)
+            {
+                prompt_unlock(
+                    w,
+                    db,
+                    state,
+                    sender,
+                    Some(Action::ImportPostmanEnvironmentFrom(path)),
+                );
+                return;
+            }
+
+            let message = match result {
+                Ok(import) => {
+                    let (name, variables) = import.value;
+                    match db.create_environment(&name, &variables) {
+                        Ok(id) => {
+                            state.borrow_mut().active_environment = Some(id);
+                            reload_environments(w, db, state);
+                            format!("Imported environment “{}”", name)
+                        }
+                        Err(e) => format!("Import failed: {}", e),
+                    }
+                }
+                Err(e) => format!("Import failed: {}", e),
+            };
+            sender.send(Action::ShowToast(message)).unwrap();
+        }
+
+        Action::ExportPostmanEnvironment => {
+            let s = state.borrow();
+            let Some(environment) = s
+                .active_environment
+                .and_then(|id| s.environments.iter().find(|e| e.id == id))
+                .cloned()
+            else {
+                let message = "Select an environment to export".to_string();
+                sender.send(Action::ShowToast(message)).unwrap();
+                return;
+            };
+            drop(s);
+
+            let tx = sender.clone();
+            file_dialogs::save(
+                &w.window,
+                "Export Postman Environment",
+                &format!("{}.postman_environment.json", environment.name),
+                move |path| {
+                    tx.send(Action::ExportPostmanEnvironmentTo(environment.id, path))
+                        .unwrap();
+                },
+            );
+        }
+
+        Action::ExportPostmanEnvironmentTo(id, path) => {
+            let message = match db.get_environment(id) {
+                Ok(environment) => match fs::write(
+                    &path,
+                    postman::export_environment(&environment.name, &environment.variables),
+                ) {
+                    Ok(()) => format!("Exported “{}” to {}", environment.name, path.display()),
+                    Err(e) => format!("Export failed: {}", e),
+                },
+                Err(e) => format!("Export failed: {}", e),
+            };
+            sender.send(Action::ShowToast(message)).unwrap();
+        }
+
+        Action::DeleteEnvironment => {
+            let active = state.borrow_mut().active_environment.take();
+            if let Some(id) = active {
+                let _ = db.delete_environment(id);
+                reload_environments(w, db, state);
+            }
+        }
+
+        Action::NewEnvironment => match db.create_environment("New Environment", &[]) {
+            Ok(id) => {
+                state.borrow_mut().active_environment = Some(id);
+                reload_environments(w, db, state);
+                sender.send(Action::EditEnvironment).unwrap();
+            }
+            Err(e) => sender.send(Action::ShowToast(e.to_string())).unwrap(),
+        },
+
+        Action::EditEnvironment => {
+            let s = state.borrow();
+            let Some(environment) = s
+                .active_environment
+                .and_then(|id| s.environments.iter().find(|e| e.id == id))
+                .cloned()
+            else {
+                let message = "Select an environment to edit".to_string();
+                sender.send(Action::ShowToast(message)).unwrap();
+                return;
+            };
+            drop(s);
+
+            let tx = sender.clone();
+            environment_dialog::present(&w.window, environment, move |environment| {
+                tx.send(Action::SaveEnvironment(environment)).unwrap();
+            });
+        }
+
+        Action::SaveEnvironment(environment) => {
+            if needs_passphrase(db, &environment.variables) {
+                prompt_unlock(
+                    w,
+                    db,
+                    state,
+                    sender,
+                    Some(Action::SaveEnvironment(environment)),
+                );
+                return;
+            }
+            match db.update_environment(&environment) {
+                Ok(()) => reload_environments(w, db, state),
+                Err(e) => {
+                    let message = format!("Save failed: {}", e);
+                    sender.send(Action::ShowToast(message)).unwrap();
+                }
+            }
+        }
+
+        Action::PromptUnlockSecrets => prompt_unlock(w, db, state, sender, None),
+
+        Action::UnlockSecrets(passphrase) => {
+            let after_unlock = state.borrow_mut().after_unlock.take();
+            match db.unlock_secrets(&passphrase) {
+                Ok(()) => {
+                    reload_environments(w, db, state);
+                    sender
+                        .send(Action::ShowToast("Secrets unlocked".to_string()))
+                        .unwrap();
+                    if let Some(action) = after_unlock {
+                        sender.send(action).unwrap();
+                    }
+                }
+                Err(SecretsError::WrongPassphrase) => {
+                    // Asked again, still meaning to do what needed the secrets
+                    prompt_unlock(w, db, state, sender, after_unlock);
+                    let message = "Wrong passphrase".to_string();
+                    sender.send(Action::ShowToast(message)).unwrap();
+                }
+                Err(e) => {
+                    let message = format!("Could not unlock secrets: {}", e);
+                    sender.send(Action::ShowToast(message)).unwrap();
+                }
+            }
+        }
+
+        Action::ShowToast(message) => w.toast_overlay.add_toast(adw::Toast::new(&message)),
+
         Action::SendRequest => {
-            let url = w.url_entry.text().to_string();
-            if url.is_empty() {
+            let request = w.current_request();
+            if request.url.is_empty() {
+                return;
+            }
+            if secrets::uses_locked(&request, &active_variables(db, &state.borrow())) {
+                prompt_unlock(w, db, state, sender, Some(Action::SendRequest));
                 return;
             }
 
-            let method = Method::from_index(w.method_dropdown.selected());
-            let (start, end) = w.request_body_buffer.bounds();
-            let body = w.request_body_buffer.text(&start, &end, true).to_string();
-            let headers = w.headers_editor.get_data();
+            let mut scope = request_scope(db, &state.borrow());
+            let mut script = ScriptOutput::default();
+            let prepared = runner::prepare(&request, &mut scope, &mut script);
+            w.console_buffer.set_text(&script.logs.join("\n"));
+            let request = match prepared {
+                Ok(request) => request,
+                Err(e) => {
+                    w.test_results_view.clear();
+                    w.captured_label.set_visible(false);
+                    w.status_label.set_text("Script Error");
+                    w.status_label.set_tooltip_text(Some(&e));
+                    w.status_label.add_css_class(config::CLASS_ERROR);
+                    w.status_label.remove_css_class(config::CLASS_SUCCESS);
+                    w.response_buffer.set_text(&e);
+                    state.borrow_mut().shown_response = None;
+                    show_snapshot(w, db, state, sender);
+                    return;
+                }
+            };
+            store_captured(w, db, state, sender, &script.variables);
+
+            let ignored = api::ignored_options(&request.url, request.options);
+            if !ignored.is_empty() {
+                let message = format!("Not applied to unix: sockets: {}", ignored.join(", "));
+                sender.send(Action::ShowToast(message)).unwrap();
+            }
 
             sender.send(Action::RequestStarted).unwrap();
 
             let tx = sender.clone();
-            let method_clone = method.clone();
-            let url_clone = url.clone();
-            let body_clone = body.clone();
-            let headers_clone = headers.clone();
 
             thread::spawn(move || {
-                let result =
-                    api::perform_request(method_clone, &url_clone, &body_clone, headers_clone);
-                tx.send(Action::RequestCompleted(result)).unwrap();
+                let result = runner::send(&request);
+                tx.send(Action::RequestCompleted(request, result)).unwrap();
             });
         }
 
@@ -102,16 +1466,19 @@ pub fn handle_action(
             w.spinner.set_visible(true);
             w.spinner.start();
             w.status_label.set_text("Sending...");
+            w.status_label.set_tooltip_text(None);
             w.status_label.remove_css_class(config::CLASS_ERROR);
             w.status_label.remove_css_class(config::CLASS_SUCCESS);
         }
 
-        Action::RequestCompleted(result) => {
+        Action::RequestCompleted(request, result) => {
             w.spinner.stop();
             w.spinner.set_visible(false);
 
             match result {
                 Ok(res) => {
+                    let shown = DiffSide::from_response("Latest Response", &res);
+                    state.borrow_mut().shown_response = Some(shown);
                     w.response_buffer.set_text(&res.body);
                     w.response_headers_buffer.set_text(&res.headers);
                     w.status_label.set_text(&res.status);
@@ -126,40 +1493,462 @@ pub fn handle_action(
                         w.status_label.remove_css_class(config::CLASS_SUCCESS);
                     }
 
-                    let url = w.url_entry.text().to_string();
-                    let method = Method::from_index(w.method_dropdown.selected());
-                    let (start, end) = w.request_body_buffer.bounds();
-                    let body = w.request_body_buffer.text(&start, &end, true).to_string();
-                    let headers = w.headers_editor.get_data();
-                    let headers_json = serde_json::to_string(&headers).unwrap_or_default();
-
-                    if let Ok(id) = db.save_exchange(
-                        method.as_str(),
-                        &url,
-                        &body,
-                        &headers_json,
-                        &res.body,
-                        &res.headers,
-                        &res.status,
-                        &res.time,
-                        &res.size,
-                    ) {
-                        sender.send(Action::HistorySaved(id)).unwrap();
+                    match &request.contract {
+                        Some(link) => w.contract_view.show_result(&contract::validate(link, &res)),
+                        None => w.contract_view.clear_result(),
+                    }
+
+                    // Scripts see the variables as they are now, after the pre-request script
+                    let mut scope = request_scope(db, &state.borrow());
+                    let mut script = ScriptOutput::default();
+                    let (mut results, extracted) =
+                        runner::check(&request, &res, &mut scope, &mut script);
+                    let store = snapshot_store(db, &state.borrow());
+                    if let Some(Ok(Some(snapshot))) = store.map(|store| store.load(db))
+                        && let Ok(masking) = Masking::load(db)
+                    {
+                        results.push(snapshot.check(&res, &masking));
                     }
+                    w.test_results_view.show(&results);
+                    append_console(w, &script.logs);
+                    store_captured(w, db, state, sender, &extracted);
+
+                    show_snapshot(w, db, state, sender);
+
+                    // History records what was sent, with variables resolved
+                    let action = match runner::record(db, &request, &res, &results) {
+                        Ok(_) => Action::HistorySaved,
+                        Err(e) => Action::ShowToast(format!("Not saved to history: {}", e)),
+                    };
+                    sender.send(action).unwrap();
                 }
                 Err(api::ApiError::RequestFailed(msg)) => {
+                    w.contract_view.clear_result();
+                    w.test_results_view.clear();
+                    w.captured_label.set_visible(false);
                     w.status_label.set_text("Error");
                     w.status_label.add_css_class(config::CLASS_ERROR);
                     w.response_buffer.set_text(&msg);
+                    state.borrow_mut().shown_response = None;
+                    show_snapshot(w, db, state, sender);
                 }
             }
         }
 
-        Action::HistorySaved(id) => {
-            // Add to sidebar
-            let url = w.url_entry.text().to_string();
-            let method = Method::from_index(w.method_dropdown.selected());
-            sidebar::add_history_row(&w.history_list, method.as_str(), &url, id);
+        Action::HistorySaved => {
+            // Newest first, so it goes at the top
+            if let Err(e) = w.history_model.insert_newest() {
+                let message = format!("History not updated: {}", e);
+                sender.send(Action::ShowToast(message)).unwrap();
+            }
+        }
+    }
+}
+
+/// Takes entries, or all of them with `None`, out of the list with a toast
+/// to bring them back; they are deleted for good once it goes away.
+fn trash_history(w: &WindowWidgets, db: &Database, sender: &Sender<Action>, ids: Option<&[i64]>) {
+    let batch = match db.trash_history(ids) {
+        Ok(batch) => batch,
+        Err(e) => {
+            let message = format!("History not deleted: {}", e);
+            sender.send(Action::ShowToast(message)).unwrap();
+            return;
+        }
+    };
+    reload_history(w);
+
+    let title = match ids {
+        Some([_]) => "Entry deleted".to_string(),
+        Some(ids) => format!("{} entries deleted", ids.len()),
+        None => "History cleared".to_string(),
+    };
+    let toast = adw::Toast::builder()
+        .title(title)
+        .button_label("Undo")
+        .build();
+    toast.connect_button_clicked(glib::clone!(@strong sender => move |_| {
+        sender.send(Action::RestoreHistory(batch)).unwrap();
+    }));
+    // Also emitted after Undo, when there is nothing left to purge
+    toast.connect_dismissed(glib::clone!(@strong sender => move |_| {
+        sender.send(Action::PurgeHistory(batch)).unwrap();
+    }));
+    w.toast_overlay.add_toast(toast);
+}
+
+/// Opens the diff view, remembering the paths and headers left ignored.
+/// Both sides get the history redaction: entries are stored masked, so a
+/// value masked on one side only would show up as a change.
+fn compare_responses(
+    w: &WindowWidgets,
+    db: &Database,
+    sender: &Sender<Action>,
+    before: DiffSide,
+    after: DiffSide,
+) {
+    let redact = match runner::history_redaction(db) {
+        Ok(redact) => redact,
+        Err(e) => {
+            let message = format!("Compare failed: {}", e);
+            sender.send(Action::ShowToast(message)).unwrap();
+            return;
+        }
+    };
+    let redact_side = |side: DiffSide| {
+        let item = redact(&HistoryItem {
+            response_headers: side.headers.clone(),
+            response_body: side.body.clone(),
+            ..Default::default()
+        });
+        DiffSide {
+            headers: item.response_headers,
+            body: item.response_body,
+            ..side
+        }
+    };
+    let (before, after) = (redact_side(before), redact_side(after));
+
+    let ignored: Vec<String> = db
+        .get_setting(DIFF_IGNORED_SETTING)
+        .ok()
+        .flatten()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default();
+    let saved = ignored.clone();
+    diff_dialog::present(
+        &w.window,
+        before,
+        after,
+        ignored,
+        glib::clone!(@strong sender => move |ignored| {
+            if ignored != saved {
+                sender.send(Action::SaveDiffIgnored(ignored)).unwrap();
+            }
+        }),
+    );
+}
+
+/// Where the saved request being edited keeps its snapshot.
+fn snapshot_store(db: &Database, state: &AppState) -> Option<SnapshotStore> {
+    state
+        .active_item
+        .and_then(|id| SnapshotStore::for_item(db, id).ok())
+}
+
+/// Shows the saved request's snapshot in the Snapshot tab, and how the shown
+/// response compares with it there and in the status bar.
+fn show_snapshot(
+    w: &WindowWidgets,
+    db: &Database,
+    state: &RefCell<AppState>,
+    sender: &Sender<Action>,
+) {
+    w.snapshot_label.set_visible(false);
+    let s = state.borrow();
+    let Some(store) = snapshot_store(db, &s) else {
+        w.snapshot_view.set_snapshot(None, false);
+        return;
+    };
+    let snapshot = store.load(db).unwrap_or_else(|e| {
+        let message = format!("Snapshot not loaded: {}", e);
+        sender.send(Action::ShowToast(message)).unwrap();
+        None
+    });
+    w.snapshot_view.set_snapshot(snapshot.as_ref(), true);
+    let (Some(snapshot), Some(shown)) = (snapshot, &s.shown_response) else {
+        return;
+    };
+
+    let masking = match Masking::load(db) {
+        Ok(masking) => masking,
+        Err(e) => {
+            sender.send(Action::ShowToast(e)).unwrap();
+            return;
+        }
+    };
+    let differences = snapshot.differences(&shown.status, &shown.body, &masking);
+    w.snapshot_view.show_result(&differences);
+    w.snapshot_label.set_visible(true);
+    w.snapshot_label.remove_css_class(config::CLASS_SUCCESS);
+    w.snapshot_label.remove_css_class(config::CLASS_ERROR);
+    if differences.is_empty() {
+        w.snapshot_label.set_text("Matches snapshot");
+        w.snapshot_label.set_tooltip_text(None);
+        w.snapshot_label.add_css_class(config::CLASS_SUCCESS);
+    } else {
+        w.snapshot_label.set_text("Differs from snapshot");
+        w.snapshot_label
+            .set_tooltip_text(Some(&differences.join("\n")));
+        w.snapshot_label.add_css_class(config::CLASS_ERROR);
+    }
+}
+
+/// Points the runner dialog at a collection or folder and shows it. While a run
+/// is in progress the dialog is shown as it is.
+fn open_runner(
+    w: &WindowWidgets,
+    db: &Database,
+    state: &RefCell<AppState>,
+    sender: &Sender<Action>,
+    collection_id: i64,
+    folder_id: Option<i64>,
+) {
+    if state.borrow().run_cancel.is_none() {
+        match runner::collection_target(db, collection_id, folder_id) {
+            Ok(target) => {
+                w.runner_view.prepare(&target.name, target.requests.len());
+                state.borrow_mut().run_target = Some((collection_id, folder_id));
+            }
+            Err(e) => {
+                sender.send(Action::ShowToast(e)).unwrap();
+                return;
+            }
+        }
+    }
+    w.runner_view.present(&w.window);
+}
+
+pub fn reload_history(w: &WindowWidgets) {
+    if let Err(e) = w.history_model.reload() {
+        let message = format!("History could not be loaded: {}", e);
+        w.toast_overlay.add_toast(adw::Toast::new(&message));
+    }
+}
+
+/// The `host[:port]` part of a URL, empty when it has no scheme.
+fn url_host(url: &str) -> &str {
+    let rest = url.trim().split_once("://").map_or("", |(_, rest)| rest);
+    rest.split(['/', '?', '#']).next().unwrap_or_default()
+}
+
+pub fn reload_collections(w: &WindowWidgets, db: &Database) {
+    collections_view::clear(&w.collections_list);
+    if let Ok(collections) = db.get_collections() {
+        for collection in &collections {
+            let items = db.get_collection_items(collection.id).unwrap_or_default();
+            collections_view::add_collection(&w.collections_list, collection, &items);
         }
     }
 }
+
+pub fn reload_environments(w: &WindowWidgets, db: &Database, state: &RefCell<AppState>) {
+    let environments = db.get_environments().unwrap_or_default();
+    let active = state
+        .borrow()
+        .active_environment
+        .filter(|id| environments.iter().any(|e| e.id == *id));
+
+    let value = active.map(|id| id.to_string());
+    let _ = db.set_setting(ACTIVE_ENVIRONMENT_SETTING, value.as_deref());
+
+    {
+        let mut state = state.borrow_mut();
+        state.environments = environments.clone();
+        state.active_environment = active;
+    }
+
+    // Not borrowed here: changing the selection notifies synchronously
+    environment_bar::set_environments(&w.environment_dropdown, &environments, active);
+}
+
+pub fn load_active_environment(db: &Database, state: &RefCell<AppState>) {
+    state.borrow_mut().active_environment = db
+        .get_setting(ACTIVE_ENVIRONMENT_SETTING)
+        .ok()
+        .flatten()
+        .and_then(|id| id.parse().ok());
+}
+
+/// The active collection's variables followed by the active environment's,
+/// so the environment overrides the collection.
+fn active_variables(db: &Database, state: &AppState) -> Vec<Variable> {
+    let collection_variables = state
+        .active_collection
+        .and_then(|id| db.get_collection(id).ok())
+        .map(|c| c.variables)
+        .unwrap_or_default();
+    let environment_variables = state
+        .active_environment
+        .and_then(|id| state.environments.iter().find(|e| e.id == id))
+        .map(|e| e.variables.as_slice())
+        .unwrap_or_default();
+
+    [collection_variables.as_slice(), environment_variables].concat()
+}
+
+/// Variables a request from the editor is resolved with: the active
+/// collection's, overridden by the active environment's.
+fn request_scope(db: &Database, state: &AppState) -> Scope {
+    variables::scope(&[&active_variables(db, state)])
+}
+
+/// Whether storing `variables` needs the passphrase first: they hold new
+/// secret values and secrets are locked.
+fn needs_passphrase(db: &Database, variables: &[Variable]) -> bool {
+    !db.secrets_unlocked()
+        && variables
+            .iter()
+            .any(|v| v.secret && !secrets::is_encrypted(&v.value))
+}
+
+/// Asks for the passphrase, then sends `then` once secrets are unlocked.
+fn prompt_unlock(
+    w: &WindowWidgets,
+    db: &Database,
+    state: &RefCell<AppState>,
+    sender: &Sender<Action>,
+    then: Option<Action>,
+) {
+    state.borrow_mut().after_unlock = then;
+    let first_time = !db.has_secrets_passphrase().unwrap_or(false);
+    let tx = sender.clone();
+    passphrase_dialog::present(&w.window, first_time, move |passphrase| {
+        tx.send(Action::UnlockSecrets(passphrase)).unwrap();
+    });
+}
+
+fn append_console(w: &WindowWidgets, logs: &[String]) {
+    if logs.is_empty() {
+        return;
+    }
+    let mut end = w.console_buffer.end_iter();
+    let text = if w.console_buffer.char_count() > 0 {
+        format!("\n{}", logs.join("\n"))
+    } else {
+        logs.join("\n")
+    };
+    w.console_buffer.insert(&mut end, &text);
+}
+
+/// Saves captured values into the active environment, or into the request's
+/// collection when no environment is selected, and lists them in the status bar.
+fn store_captured(
+    w: &WindowWidgets,
+    db: &Database,
+    state: &RefCell<AppState>,
+    sender: &Sender<Action>,
+    extracted: &[Extraction],
+) {
+    if extracted.is_empty() {
+        w.captured_label.set_visible(false);
+        return;
+    }
+
+    let mut s = state.borrow_mut();
+    let active_environment = s.active_environment;
+    let active_collection = s.active_collection;
+    let saved = if let Some(environment) = s
+        .environments
+        .iter_mut()
+        .find(|e| Some(e.id) == active_environment)
+    {
+        if extractors::apply(extracted, &mut environment.variables) {
+            db.update_environment(environment)
+                .map_err(|e| format!("Captured variables not saved: {}", e))
+        } else {
+            Ok(())
+        }
+    } else if let Some(mut collection) = active_collection.and_then(|id| db.get_collection(id).ok())
+    {
+        if extractors::apply(extracted, &mut collection.variables) {
+            db.update_collection_variables(collection.id, &collection.variables)
+                .map_err(|e| format!("Captured variables not saved: {}", e))
+        } else {
+            Ok(())
+        }
+    } else {
+        Err("Select an environment to keep captured variables".to_string())
+    };
+    drop(s);
+
+    let captured: Vec<&str> = extracted
+        .iter()
+        .filter(|e| e.value.is_ok())
+        .map(|e| e.variable.as_str())
+        .collect();
+    let secret_values = db.secret_values().unwrap_or_default();
+    let tooltip: Vec<String> = extracted
+        .iter()
+        .map(|e| match &e.value {
+            Ok(_) if secret_values.iter().any(|(name, _)| *name == e.variable) => {
+                format!("{} = ••••••••", e.variable)
+            }
+            Ok(value) => format!("{} = {}", e.variable, value),
+            Err(reason) => format!("{}: {}", e.variable, reason),
+        })
+        .collect();
+
+    w.captured_label.set_visible(true);
+    w.captured_label.set_tooltip_text(Some(&tooltip.join("\n")));
+    w.captured_label.remove_css_class(config::CLASS_SUCCESS);
+    w.captured_label.remove_css_class(config::CLASS_ERROR);
+    if captured.len() == extracted.len() {
+        w.captured_label
+            .set_text(&format!("Captured: {}", captured.join(", ")));
+        w.captured_label.add_css_class(config::CLASS_SUCCESS);
+    } else {
+        w.captured_label.set_text(&format!(
+            "Captured {} of {}",
+            captured.len(),
+            extracted.len()
+        ));
+        w.captured_label.add_css_class(config::CLASS_ERROR);
+    }
+
+    if !captured.is_empty()
+        && let Err(message) = saved
+    {
+        sender.send(Action::ShowToast(message)).unwrap();
+    }
+}
+
+/// Writes a request back into the `.http` file its collection was opened
+/// from. Returns the request with what the file now reads, or `None` when
+/// the item did not come from a file.
+fn write_back_http_file(
+    db: &Database,
+    item: &CollectionItem,
+    request: &Request,
+) -> Result<Option<Request>, String> {
+    let source = db
+        .get_collection_source(item.collection_id)
+        .map_err(|e| e.to_string())?;
+    let Some(path) = source
+        .as_deref()
+        .and_then(|source| source.strip_prefix(http::COLLECTION_SOURCE))
+    else {
+        return Ok(None);
+    };
+    let Some(key) = db.get_item_source_key(item.id).map_err(|e| e.to_string())? else {
+        return Ok(None);
+    };
+
+    let path = Path::new(path);
+    let base_dir = path.parent().unwrap_or(Path::new("."));
+    let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let (updated, key) =
+        http::replace_request(&text, base_dir, &key, request).map_err(|e| e.to_string())?;
+    fs::write(path, &updated).map_err(|e| format!("{}: {}", path.display(), e))?;
+
+    let Some(generated) = http::parse(&updated, base_dir)
+        .ok()
+        .and_then(|file| file.requests.into_iter().find(|r| r.name == key))
+        .map(|r| r.request)
+    else {
+        return Ok(Some(request.clone()));
+    };
+    db.set_item_source(item.id, &key, &generated)
+        .map_err(|e| e.to_string())?;
+
+    // The file holds no contract, assertions, extractors or scripts, so
+    // those are kept as edited
+    Ok(Some(Request {
+        method: generated.method,
+        url: generated.url,
+        headers: generated.headers,
+        body: generated.body,
+        options: generated.options,
+        ..request.clone()
+    }))
+}
@@ -1,5 +1,6 @@
 use crate::config;
 use crate::ui::helpers::add_box_margins;
+use crate::ui::variable_completion;
 use gtk::prelude::*;
 use gtk::{Box, Button, DropDown, Entry, Orientation, StringList};
 
@@ -14,6 +15,7 @@ pub fn build() -> (Box, Entry, DropDown, Button) {
     let url_entry = Entry::new();
     url_entry.set_placeholder_text(Some("https://api.example.com/endpoint"));
     url_entry.set_hexpand(true);
+    variable_completion::attach_to_entry(&url_entry);
 
     let send_button = Button::with_label("Send");
     send_button.add_css_class("suggested-action");
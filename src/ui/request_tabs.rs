@@ -1,23 +1,30 @@
-use crate::{config, ui::helpers, ui::key_value_editor::KeyValueEditor};
+use crate::{
+    config,
+    ui::helpers,
+    ui::{
+        assertion_editor::AssertionEditor, extractor_editor::ExtractorEditor,
+        key_value_editor::KeyValueEditor, options_editor::OptionsEditor,
+        script_editor::ScriptEditor,
+    },
+};
 use gtk::{prelude::*, Box, Label, Notebook, Orientation, ScrolledWindow};
 use sourceview5::prelude::*;
 
-pub fn build() -> (Notebook, sourceview5::Buffer, KeyValueEditor) {
+pub fn build() -> (
+    Notebook,
+    sourceview5::Buffer,
+    KeyValueEditor,
+    OptionsEditor,
+    AssertionEditor,
+    ExtractorEditor,
+    ScriptEditor,
+) {
     let notebook = Notebook::new();
     notebook.set_vexpand(true);
 
     fn create_json_view() -> (ScrolledWindow, sourceview5::Buffer) {
         let buffer = sourceview5::Buffer::new(None);
-
-        let style_manager = sourceview5::StyleSchemeManager::default();
-        let scheme = style_manager
-            .scheme(config::EDITOR_SCHEME_PREF_1)
-            .or_else(|| style_manager.scheme(config::EDITOR_SCHEME_PREF_2))
-            .or_else(|| style_manager.scheme(config::EDITOR_SCHEME_PREF_3));
-
-        if let Some(s) = scheme {
-            buffer.set_style_scheme(Some(&s));
-        }
+        helpers::set_editor_scheme(&buffer);
 
         // Set JSON syntax highlighting
         helpers::set_syntax_highlighting(&buffer, "application/json");
@@ -25,6 +32,7 @@ pub fn build() -> (Notebook, sourceview5::Buffer, KeyValueEditor) {
         let view = sourceview5::View::with_buffer(&buffer);
         view.set_monospace(true);
         view.set_show_line_numbers(true);
+        helpers::add_variable_completion(&view);
         view.set_top_margin(config::SPACING_MEDIUM);
         view.set_bottom_margin(config::SPACING_MEDIUM);
         view.set_left_margin(config::SPACING_MEDIUM);
@@ -51,5 +59,33 @@ pub fn build() -> (Notebook, sourceview5::Buffer, KeyValueEditor) {
 
     notebook.append_page(&headers_editor.container, Some(&headers_label));
 
-    (notebook, buffer, headers_editor)
+    let options_editor = OptionsEditor::new();
+    let options_label = Label::new(Some("Options"));
+
+    notebook.append_page(&options_editor.container, Some(&options_label));
+
+    let assertion_editor = AssertionEditor::new();
+    let tests_label = Label::new(Some("Tests"));
+
+    notebook.append_page(&assertion_editor.container, Some(&tests_label));
+
+    let extractor_editor = ExtractorEditor::new();
+    let capture_label = Label::new(Some("Capture"));
+
+    notebook.append_page(&extractor_editor.container, Some(&capture_label));
+
+    let script_editor = ScriptEditor::new();
+    let scripts_label = Label::new(Some("Scripts"));
+
+    notebook.append_page(&script_editor.container, Some(&scripts_label));
+
+    (
+        notebook,
+        buffer,
+        headers_editor,
+        options_editor,
+        assertion_editor,
+        extractor_editor,
+        script_editor,
+    )
 }
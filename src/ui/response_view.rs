@@ -1,25 +1,28 @@
 use crate::config;
+use crate::ui::{
+    contract_view::ContractView, helpers::set_editor_scheme, snapshot_view::SnapshotView,
+    test_results_view::TestResultsView,
+};
 use gtk::{Label, Notebook};
 use gtk::{ScrolledWindow, prelude::*};
 use sourceview5::prelude::*;
-use sourceview5::{Buffer, StyleSchemeManager, View};
+use sourceview5::{Buffer, View};
 
-pub fn build() -> (Notebook, Buffer, Buffer) {
+pub fn build() -> (
+    Notebook,
+    Buffer,
+    Buffer,
+    TestResultsView,
+    ContractView,
+    SnapshotView,
+    Buffer,
+) {
     let notebook = Notebook::new();
     notebook.set_vexpand(true);
 
     fn create_view(lang_id: Option<&str>) -> (ScrolledWindow, Buffer) {
         let buffer = Buffer::new(None);
-
-        let style_manager = StyleSchemeManager::default();
-        let scheme = style_manager
-            .scheme(config::EDITOR_SCHEME_PREF_1)
-            .or_else(|| style_manager.scheme(config::EDITOR_SCHEME_PREF_2))
-            .or_else(|| style_manager.scheme(config::EDITOR_SCHEME_PREF_3));
-
-        if let Some(s) = scheme {
-            buffer.set_style_scheme(Some(&s));
-        };
+        set_editor_scheme(&buffer);
 
         if let Some(id) = lang_id {
             if let Some(lang) = sourceview5::LanguageManager::default().language(id) {
@@ -53,5 +56,29 @@ pub fn build() -> (Notebook, Buffer, Buffer) {
     let headers_label = Label::new(Some("Headers"));
     notebook.append_page(&headers_scroll, Some(&headers_label));
 
-    (notebook, body_buffer, headers_buffer)
+    let test_results_view = TestResultsView::new();
+    let tests_label = Label::new(Some("Tests"));
+    notebook.append_page(&test_results_view.container, Some(&tests_label));
+
+    let contract_view = ContractView::new();
+    let contract_label = Label::new(Some("Contract"));
+    notebook.append_page(&contract_view.container, Some(&contract_label));
+
+    let snapshot_view = SnapshotView::new();
+    let snapshot_label = Label::new(Some("Snapshot"));
+    notebook.append_page(&snapshot_view.container, Some(&snapshot_label));
+
+    let (console_scroll, console_buffer) = create_view(None);
+    let console_label = Label::new(Some("Console"));
+    notebook.append_page(&console_scroll, Some(&console_label));
+
+    (
+        notebook,
+        body_buffer,
+        headers_buffer,
+        test_results_view,
+        contract_view,
+        snapshot_view,
+        console_buffer,
+    )
 }
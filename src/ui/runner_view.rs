@@ -0,0 +1,372 @@
+use adw::{
+    ActionRow, Dialog, HeaderBar, PreferencesGroup, SpinRow, SwitchRow, ToolbarView, WindowTitle,
+    prelude::*,
+};
+use gtk::{
+    Box, Button, Image, Label, ListBox, MenuButton, Orientation, ProgressBar, ScrolledWindow, gio,
+};
+
+use crate::{
+    api::ApiError,
+    config,
+    runner::{RunOptions, RunReport, RunStep},
+    snapshot,
+    ui::helpers::add_box_margins,
+};
+
+/// The collection runner dialog: run options, per-request progress and the
+/// summary of the last run.
+#[derive(Clone)]
+pub struct RunnerView {
+    pub dialog: Dialog,
+    pub start_button: Button,
+    pub stop_button: Button,
+    pub data_button: Button,
+    pub data_clear_button: Button,
+    /// Shown when responses differed from their snapshots
+    pub accept_button: Button,
+    title: WindowTitle,
+    export_button: MenuButton,
+    options_group: PreferencesGroup,
+    stop_on_failure_row: SwitchRow,
+    delay_row: SpinRow,
+    iterations_row: SpinRow,
+    data_file_row: ActionRow,
+    progress_bar: ProgressBar,
+    steps_list: ListBox,
+    summary_label: Label,
+}
+
+impl RunnerView {
+    pub fn new() -> Self {
+        let title = WindowTitle::new("Run", "");
+
+        let start_button = Button::builder()
+            .label("Run")
+            .css_classes(vec!["suggested-action".to_string()])
+            .build();
+
+        let stop_button = Button::builder()
+            .label("Stop")
+            .css_classes(vec!["destructive-action".to_string()])
+            .visible(false)
+            .build();
+
+        let accept_button = Button::builder()
+            .label("Accept Snapshots")
+            .tooltip_text("Record the responses as the new snapshots")
+            .visible(false)
+            .build();
+
+        // Items target the `runner` action group installed on the dialog
+        let menu = gio::Menu::new();
+        menu.append(Some("Export JUnit XML…"), Some("runner.export-junit"));
+        menu.append(Some("Export JSON…"), Some("runner.export-json"));
+        menu.append(
+            Some("Export Failing Rows as CSV…"),
+            Some("runner.export-failed-rows"),
+        );
+
+        let export_button = MenuButton::builder()
+            .icon_name("document-save-symbolic")
+            .tooltip_text("Export Report")
+            .menu_model(&menu)
+            .sensitive(false)
+            .build();
+
+        let header = HeaderBar::new();
+        header.set_title_widget(Some(&title));
+        header.pack_start(&start_button);
+        header.pack_start(&stop_button);
+        header.pack_end(&export_button);
+        header.pack_end(&accept_button);
+
+        let stop_on_failure_row = SwitchRow::builder()
+            .title("Stop on Failure")
+            .subtitle("End the run at the first failing request")
+            .build();
+
+        let delay_row = SpinRow::with_range(0.0, config::RUN_MAX_DELAY_MS, 100.0);
+        delay_row.set_title("Delay");
+        delay_row.set_subtitle("Milliseconds between requests");
+
+        let iterations_row = SpinRow::with_range(1.0, config::RUN_MAX_ITERATIONS, 1.0);
+        iterations_row.set_title("Iterations");
+        iterations_row.set_subtitle("Times to run the whole sequence");
+
+        let data_button = Button::builder()
+            .icon_name("document-open-symbolic")
+            .tooltip_text("Choose CSV or JSON File")
+            .valign(gtk::Align::Center)
+            .css_classes(vec!["flat".to_string()])
+            .build();
+
+        let data_clear_button = Button::builder()
+            .icon_name("edit-clear-symbolic")
+            .tooltip_text("Run Without Data")
+            .valign(gtk::Align::Center)
+            .css_classes(vec!["flat".to_string()])
+            .visible(false)
+            .build();
+
+        let data_file_row = ActionRow::builder()
+            .title("Data File")
+            .use_markup(false)
+            .build();
+        data_file_row.add_suffix(&data_clear_button);
+        data_file_row.add_suffix(&data_button);
+
+        let options_group = PreferencesGroup::new();
+        options_group.add(&stop_on_failure_row);
+        options_group.add(&delay_row);
+        options_group.add(&iterations_row);
+        options_group.add(&data_file_row);
+
+        let progress_bar = ProgressBar::builder().show_text(true).build();
+
+        let steps_list = ListBox::new();
+        steps_list.set_selection_mode(gtk::SelectionMode::None);
+        steps_list.add_css_class("boxed-list");
+        steps_list.set_valign(gtk::Align::Start);
+
+        let scrolled = ScrolledWindow::builder()
+            .hscrollbar_policy(gtk::PolicyType::Never)
+            .child(&steps_list)
+            .vexpand(true)
+            .build();
+
+        let summary_label = Label::new(None);
+        summary_label.set_xalign(0.0);
+        summary_label.set_wrap(true);
+
+        let content = Box::new(Orientation::Vertical, config::SPACING_MEDIUM);
+        add_box_margins(&content, config::SPACING_MEDIUM);
+        content.append(&options_group);
+        content.append(&progress_bar);
+        content.append(&scrolled);
+        content.append(&summary_label);
+
+        let toolbar_view = ToolbarView::new();
+        toolbar_view.add_top_bar(&header);
+        toolbar_view.set_content(Some(&content));
+
+        let dialog = Dialog::builder()
+            .title("Collection Runner")
+            .content_width(config::DIALOG_DEFAULT_WIDTH)
+            .content_height(config::DIALOG_DEFAULT_HEIGHT)
+            .child(&toolbar_view)
+            .build();
+
+        let view = RunnerView {
+            dialog,
+            start_button,
+            stop_button,
+            data_button,
+            data_clear_button,
+            accept_button,
+            title,
+            export_button,
+            options_group,
+            stop_on_failure_row,
+            delay_row,
+            iterations_row,
+            data_file_row,
+            progress_bar,
+            steps_list,
+            summary_label,
+        };
+        view.set_data(None);
+        view
+    }
+
+    pub fn options(&self) -> RunOptions {
+        RunOptions {
+            stop_on_failure: self.stop_on_failure_row.is_active(),
+            delay_ms: self.delay_row.value() as u64,
+            iterations: self.iterations_row.value() as u32,
+            update_snapshots: false,
+        }
+    }
+
+    /// Shows the chosen data file as `(file name, row count)`.
+    pub fn set_data(&self, data: Option<(&str, usize)>) {
+        match data {
+            Some((name, rows)) => self
+                .data_file_row
+                .set_subtitle(&format!("{}, one pass per row ({} rows)", name, rows)),
+            None => self
+                .data_file_row
+                .set_subtitle("CSV or JSON rows that fill {{variables}}"),
+        }
+        self.data_clear_button.set_visible(data.is_some());
+    }
+
+    pub fn present(&self, parent: &impl IsA<gtk::Widget>) {
+        self.dialog.present(Some(parent));
+    }
+
+    /// Resets the dialog for a new target. Options are kept between targets.
+    pub fn prepare(&self, name: &str, request_count: usize) {
+        self.title.set_subtitle(name);
+        self.start_button.set_sensitive(request_count > 0);
+        self.export_button.set_sensitive(false);
+        self.accept_button.set_visible(false);
+        self.clear_steps();
+        self.progress_bar.set_fraction(0.0);
+        self.progress_bar
+            .set_text(Some(&format!("{} requests", request_count)));
+        self.set_summary("", None);
+    }
+
+    pub fn run_started(&self, total: usize) {
+        self.start_button.set_visible(false);
+        self.stop_button.set_visible(true);
+        self.stop_button.set_sensitive(true);
+        self.options_group.set_sensitive(false);
+        self.export_button.set_sensitive(false);
+        self.accept_button.set_visible(false);
+        self.clear_steps();
+        self.progress_bar.set_fraction(0.0);
+        self.progress_bar.set_text(Some(&format!("0 of {}", total)));
+        self.set_summary("Running…", None);
+    }
+
+    /// Stopping takes effect before the next request is sent.
+    pub fn run_stopping(&self) {
+        self.stop_button.set_sensitive(false);
+        self.set_summary("Stopping after the current request…", None);
+    }
+
+    /// `data_row` describes the data row the step used, if any.
+    pub fn add_step(&self, step: &RunStep, data_row: Option<String>, done: usize, total: usize) {
+        self.progress_bar
+            .set_fraction(done as f64 / total.max(1) as f64);
+        self.progress_bar
+            .set_text(Some(&format!("{} of {}", done, total)));
+
+        let passed = step.passed();
+        let row = Box::new(Orientation::Vertical, config::SPACING_NONE);
+        add_box_margins(&row, config::SPACING_SMALL);
+
+        let line = Box::new(Orientation::Horizontal, config::SPACING_MEDIUM);
+
+        let icon = Image::from_icon_name(if passed {
+            "emblem-ok-symbolic"
+        } else {
+            "dialog-error-symbolic"
+        });
+        icon.add_css_class(if passed {
+            config::CLASS_SUCCESS
+        } else {
+            config::CLASS_ERROR
+        });
+
+        let method = step.request.method.as_str();
+        let method_label = Label::new(Some(method));
+        method_label.add_css_class(config::get_badge_class(method));
+
+        let name = if self.iterations_row.value() > 1.0 || data_row.is_some() {
+            format!("#{} {}", step.iteration, step.name)
+        } else {
+            step.name.clone()
+        };
+        let name_label = Label::new(Some(name.as_str()));
+        name_label.set_xalign(0.0);
+        name_label.set_hexpand(true);
+        name_label.set_ellipsize(gtk::pango::EllipsizeMode::End);
+        name_label.set_tooltip_text(Some(step.request.url.as_str()));
+
+        let result = match &step.response {
+            Ok(response) => format!("{}  {}", response.status_code, response.time),
+            Err(_) => "Error".to_string(),
+        };
+        let result_label = Label::new(Some(result.as_str()));
+        result_label.add_css_class("dim-label");
+
+        line.append(&icon);
+        line.append(&method_label);
+        line.append(&name_label);
+        line.append(&result_label);
+        row.append(&line);
+
+        let mut details: Vec<String> = data_row.into_iter().collect();
+        match &step.response {
+            Err(ApiError::RequestFailed(msg)) => details.push(msg.clone()),
+            Ok(_) if !step.assertions.is_empty() => {
+                let passed = step.assertions.iter().filter(|a| a.passed).count();
+                details.push(format!(
+                    "{} of {} assertions passed",
+                    passed,
+                    step.assertions.len()
+                ));
+                details.extend(
+                    step.assertions
+                        .iter()
+                        .filter(|a| !a.passed)
+                        .map(|a| format!("✗ {}: {}", a.description, a.message)),
+                );
+            }
+            Ok(_) => {}
+        }
+        details.extend(step.extracted.iter().map(|e| match &e.value {
+            Ok(_) => format!("Captured {}", e.variable),
+            Err(reason) => format!("✗ capture {}: {}", e.variable, reason),
+        }));
+        for detail in details {
+            let label = Label::new(Some(detail.as_str()));
+            label.add_css_class("dim-label");
+            label.set_xalign(0.0);
+            label.set_wrap(true);
+            label.set_selectable(true);
+            label.set_margin_start(config::SPACING_MEDIUM * 2);
+            row.append(&label);
+        }
+
+        self.steps_list.append(&row);
+    }
+
+    pub fn run_finished(&self, report: &RunReport) {
+        self.start_button.set_visible(true);
+        self.start_button.set_sensitive(true);
+        self.stop_button.set_visible(false);
+        self.options_group.set_sensitive(true);
+        self.export_button.set_sensitive(!report.steps.is_empty());
+        let snapshots_differ = report.steps.iter().any(|step| {
+            step.assertions
+                .iter()
+                .any(|a| a.description == snapshot::CHECK && !a.passed)
+        });
+        self.accept_button.set_visible(snapshots_differ);
+
+        let mut summary = format!(
+            "{} passed, {} failed in {:.0} ms",
+            report.passed(),
+            report.failed(),
+            report.duration_ms
+        );
+        if report.stopped {
+            summary.push_str(" (stopped early)");
+        }
+        let class = if report.failed() == 0 {
+            config::CLASS_SUCCESS
+        } else {
+            config::CLASS_ERROR
+        };
+        self.set_summary(&summary, Some(class));
+    }
+
+    fn set_summary(&self, text: &str, class: Option<&str>) {
+        self.summary_label.set_text(text);
+        self.summary_label.remove_css_class(config::CLASS_SUCCESS);
+        self.summary_label.remove_css_class(config::CLASS_ERROR);
+        if let Some(class) = class {
+            self.summary_label.add_css_class(class);
+        }
+    }
+
+    fn clear_steps(&self) {
+        while let Some(row) = self.steps_list.first_child() {
+            self.steps_list.remove(&row);
+        }
+    }
+}
@@ -0,0 +1,91 @@
+use gtk::{Box, Orientation, ScrolledWindow, Stack, StackSwitcher, prelude::*};
+use sourceview5::{Buffer, View, prelude::*};
+
+use crate::{config, models::Scripts, ui::helpers::set_editor_scheme};
+
+/// The request "Scripts" tab: Rhai run before the request is sent and after
+/// its response arrives.
+#[derive(Clone)]
+pub struct ScriptEditor {
+    pub container: Box,
+    pub pre_request_buffer: Buffer,
+    pub post_response_buffer: Buffer,
+}
+
+impl ScriptEditor {
+    pub fn new() -> Self {
+        let container = Box::new(Orientation::Vertical, config::SPACING_NONE);
+
+        let stack = Stack::new();
+        stack.set_vexpand(true);
+
+        let (pre_request_view, pre_request_buffer) = create_script_view();
+        stack.add_titled(&pre_request_view, Some("pre-request"), "Pre-request");
+
+        let (post_response_view, post_response_buffer) = create_script_view();
+        stack.add_titled(&post_response_view, Some("post-response"), "Post-response");
+
+        let switcher = StackSwitcher::new();
+        switcher.set_stack(Some(&stack));
+        switcher.set_halign(gtk::Align::Center);
+        switcher.set_margin_top(config::SPACING_SMALL);
+        switcher.set_margin_bottom(config::SPACING_SMALL);
+
+        container.append(&switcher);
+        container.append(&stack);
+
+        ScriptEditor {
+            container,
+            pre_request_buffer,
+            post_response_buffer,
+        }
+    }
+
+    pub fn get_data(&self) -> Scripts {
+        Scripts {
+            pre_request: buffer_text(&self.pre_request_buffer),
+            post_response: buffer_text(&self.post_response_buffer),
+        }
+    }
+
+    pub fn set_data(&self, scripts: Scripts) {
+        self.pre_request_buffer.set_text(&scripts.pre_request);
+        self.post_response_buffer.set_text(&scripts.post_response);
+    }
+
+    pub fn clear(&self) {
+        self.set_data(Scripts::default());
+    }
+}
+
+fn create_script_view() -> (ScrolledWindow, Buffer) {
+    let buffer = Buffer::new(None);
+    set_editor_scheme(&buffer);
+
+    // Rhai reads close enough to Rust for its highlighting
+    if let Some(lang) = sourceview5::LanguageManager::default().language("rust") {
+        buffer.set_language(Some(&lang));
+    }
+
+    let view = View::with_buffer(&buffer);
+    view.set_monospace(true);
+    view.set_show_line_numbers(true);
+    view.set_auto_indent(true);
+    view.set_top_margin(config::SPACING_MEDIUM);
+    view.set_bottom_margin(config::SPACING_MEDIUM);
+    view.set_left_margin(config::SPACING_MEDIUM);
+
+    let scrolled_window = ScrolledWindow::builder()
+        .hscrollbar_policy(gtk::PolicyType::Automatic)
+        .vscrollbar_policy(gtk::PolicyType::Automatic)
+        .child(&view)
+        .vexpand(true)
+        .build();
+
+    (scrolled_window, buffer)
+}
+
+fn buffer_text(buffer: &Buffer) -> String {
+    let (start, end) = buffer.bounds();
+    buffer.text(&start, &end, true).to_string()
+}
@@ -1,9 +1,24 @@
-use gtk::{prelude::*, Box, Button, Label, ListBox, ListBoxRow, Orientation, ScrolledWindow};
+use gtk::{
+    gdk, gio, glib, prelude::*, Box, Button, GestureClick, Label, ListBox, ListItem, ListView,
+    MenuButton, Orientation, PopoverMenu, ScrolledWindow, SignalListItemFactory,
+};
 
-use crate::{config, ui::helpers::add_box_margins};
+use crate::{
+    config,
+    database::{HIGHLIGHT_END, HIGHLIGHT_START},
+    ui::{
+        collections_view,
+        helpers::add_box_margins,
+        history_model::{HistoryModel, HistoryRow},
+        history_search::HistorySearch,
+    },
+};
 
 pub struct SidebarWidgets {
-    pub history_list: ListBox,
+    pub history_list: ListView,
+    pub history_model: HistoryModel,
+    pub history_search: HistorySearch,
+    pub collections_list: ListBox,
     pub clear_history_btn: Button,
     pub new_request_btn: Button,
 }
@@ -32,12 +47,41 @@ pub fn build() -> (Box, SidebarWidgets) {
         .tooltip_text("Clear History")
         .build();
 
+    let history_menu = gio::Menu::new();
+    history_menu.append(Some("Import HAR…"), Some("history.import-har"));
+    history_menu.append(Some("Export All as HAR…"), Some("history.export-har"));
+    history_menu.append(Some("Preferences…"), Some("history.preferences"));
+
+    let history_menu_btn = MenuButton::builder()
+        .icon_name("view-more-symbolic")
+        .css_classes(vec!["flat".to_string()])
+        .tooltip_text("History Menu")
+        .menu_model(&history_menu)
+        .build();
+
     history_toolbar.append(&hist_label);
     history_toolbar.append(&new_request_btn);
     history_toolbar.append(&clear_history_btn);
+    history_toolbar.append(&history_menu_btn);
+
+    let history_search = HistorySearch::new();
 
-    let history_list = ListBox::new();
-    history_list.add_css_class("navigation-sidebar");
+    let history_model = HistoryModel::default();
+    // Ctrl and Shift click select several entries to delete at once
+    let selection = gtk::MultiSelection::new(Some(history_model.clone()));
+    let history_list = ListView::builder()
+        .model(&selection)
+        .factory(&history_row_factory())
+        .header_factory(&history_header_factory())
+        .css_classes(vec!["navigation-sidebar".to_string()])
+        .build();
+
+    let shortcuts = gtk::ShortcutController::new();
+    shortcuts.add_shortcut(gtk::Shortcut::new(
+        gtk::ShortcutTrigger::parse_string("Delete"),
+        Some(gtk::NamedAction::new("history.delete-selected")),
+    ));
+    history_list.add_controller(shortcuts);
 
     let history_scrolled = ScrolledWindow::builder()
         .hscrollbar_policy(gtk::PolicyType::Never)
@@ -46,39 +90,348 @@ pub fn build() -> (Box, SidebarWidgets) {
         .build();
 
     history_box.append(&history_toolbar);
+    history_box.append(&history_search.container);
     history_box.append(&history_scrolled);
 
-    container.append(&history_box);
+    let (collections_box, collections_list) = collections_view::build();
+
+    let stack = adw::ViewStack::new();
+    stack.set_vexpand(true);
+    stack
+        .add_titled(&history_box, Some("history"), "History")
+        .set_icon_name(Some("document-open-recent-symbolic"));
+    stack
+        .add_titled(&collections_box, Some("collections"), "Collections")
+        .set_icon_name(Some("folder-symbolic"));
+
+    let switcher = adw::ViewSwitcher::builder()
+        .stack(&stack)
+        .policy(adw::ViewSwitcherPolicy::Wide)
+        .margin_top(config::SPACING_SMALL)
+        .margin_start(config::SPACING_MEDIUM)
+        .margin_end(config::SPACING_MEDIUM)
+        .build();
+
+    container.append(&switcher);
+    container.append(&stack);
 
     (
         container,
         SidebarWidgets {
             history_list,
+            history_model,
+            history_search,
+            collections_list,
             clear_history_btn,
             new_request_btn,
         },
     )
 }
 
-pub fn add_history_row(list: &ListBox, method: &str, url: &str, id: i64) {
-    let row = ListBoxRow::new();
-    let row_box = Box::new(Orientation::Horizontal, config::SPACING_MEDIUM);
-    add_box_margins(&row_box, config::SPACING_MEDIUM);
+/// Ids of the entries selected in the history list.
+pub fn selected_history_ids(list: &ListView, model: &HistoryModel) -> Vec<i64> {
+    let Some(selection) = list.model() else {
+        return Vec::new();
+    };
+    let positions = selection.selection();
+    (0..positions.size())
+        .filter_map(|index| model.row_id(positions.nth(index as u32)))
+        .collect()
+}
+
+/// Rows are built once and refilled as the list scrolls.
+fn history_row_factory() -> SignalListItemFactory {
+    let factory = SignalListItemFactory::new();
+    factory.connect_setup(|_, object| {
+        let Some(list_item) = object.downcast_ref::<ListItem>() else {
+            return;
+        };
+
+        let row_box = Box::new(Orientation::Horizontal, config::SPACING_MEDIUM);
+        add_box_margins(&row_box, config::SPACING_MEDIUM);
+
+        let method_label = Label::new(None);
+
+        let pin_icon = gtk::Image::from_icon_name("view-pin-symbolic");
+        pin_icon.set_tooltip_text(Some("Pinned"));
+
+        // The name given to the entry, if any, otherwise its URL
+        let url_label = Label::new(None);
+        url_label.set_ellipsize(gtk::pango::EllipsizeMode::End);
+        url_label.set_xalign(0.0);
+
+        let tags_label = Label::builder()
+            .ellipsize(gtk::pango::EllipsizeMode::End)
+            .xalign(0.0)
+            .css_classes(vec!["dim-label".to_string(), "caption".to_string()])
+            .build();
+
+        // Status code and how long ago, under the name
+        let status_label = Label::builder()
+            .css_classes(vec!["caption".to_string()])
+            .build();
+        let time_label = Label::builder()
+            .css_classes(vec!["dim-label".to_string(), "caption".to_string()])
+            .build();
+        let meta_box = Box::new(Orientation::Horizontal, config::SPACING_SMALL);
+        meta_box.append(&status_label);
+        meta_box.append(&time_label);
+
+        // Search matches show under the URL
+        let snippet_label = Label::builder()
+            .use_markup(true)
+            .ellipsize(gtk::pango::EllipsizeMode::End)
+            .xalign(0.0)
+            .css_classes(vec!["dim-label".to_string(), "caption".to_string()])
+            .build();
+
+        let text_box = Box::new(Orientation::Vertical, config::SPACING_NONE);
+        text_box.set_hexpand(true);
+        text_box.append(&url_label);
+        text_box.append(&meta_box);
+        text_box.append(&tags_label);
+        text_box.append(&snippet_label);
+
+        // Failing assertions stay visible in the list
+        let tests_label = Label::new(None);
+        tests_label.set_xalign(1.0);
+        tests_label.set_tooltip_text(Some("Assertions passed"));
 
-    let method_label = Label::new(Some(method));
-    method_label.add_css_class(config::get_badge_class(method));
+        row_box.append(&method_label);
+        row_box.append(&pin_icon);
+        row_box.append(&text_box);
+        row_box.append(&tests_label);
 
-    let url_label = Label::new(Some(url));
-    url_label.set_ellipsize(gtk::pango::EllipsizeMode::End);
-    url_label.set_hexpand_set(true);
-    url_label.set_xalign(0.0);
+        // A plain click opens the entry; with Ctrl or Shift it only selects
+        let open_gesture = GestureClick::new();
+        open_gesture.set_button(gdk::BUTTON_PRIMARY);
+        let list_item_ref = list_item.downgrade();
+        open_gesture.connect_released(move |gesture, _, _, _| {
+            let selecting = gdk::ModifierType::CONTROL_MASK | gdk::ModifierType::SHIFT_MASK;
+            if gesture.current_event_state().intersects(selecting) {
+                return;
+            }
+            let (Some(list_item), Some(row)) = (list_item_ref.upgrade(), gesture.widget()) else {
+                return;
+            };
+            let position = list_item.position().to_variant();
+            let _ = row.activate_action("list.activate-item", Some(&position));
+        });
+        row_box.add_controller(open_gesture);
+
+        let gesture = GestureClick::new();
+        gesture.set_button(gdk::BUTTON_SECONDARY);
+        // The row outlives any one entry, so look up the one shown on press
+        let list_item_ref = list_item.downgrade();
+        gesture.connect_pressed(move |gesture, _, x, y| {
+            let Some(list_item) = list_item_ref.upgrade() else {
+                return;
+            };
+            let (Some(row), Some((id, pinned))) = (gesture.widget(), row_entry(&list_item)) else {
+                return;
+            };
+
+            let popover = PopoverMenu::from_model(Some(&history_row_menu(id, pinned)));
+            popover.set_parent(&row);
+            popover.set_has_arrow(false);
+            popover.set_pointing_to(Some(&gdk::Rectangle::new(x as i32, y as i32, 1, 1)));
+            // Unparent once the menu item (if any) has been activated
+            popover.connect_closed(|popover| {
+                let popover = popover.clone();
+                glib::idle_add_local_once(move || popover.unparent());
+            });
+            popover.popup();
+        });
+        row_box.add_controller(gesture);
+
+        let row_box_ref = row_box.clone();
+        list_item.connect_item_notify(move |list_item| {
+            let Some(object) = list_item.item().and_downcast::<glib::BoxedAnyObject>() else {
+                return;
+            };
+            let row = object.borrow::<HistoryRow>();
+
+            for class in method_label.css_classes() {
+                method_label.remove_css_class(&class);
+            }
+            method_label.set_text(&row.item.method);
+            method_label.add_css_class(config::get_badge_class(&row.item.method));
+            let item = &row.item;
+            if item.label.is_empty() {
+                url_label.set_text(&item.url);
+                url_label.set_tooltip_text(None);
+            } else {
+                url_label.set_text(&item.label);
+                url_label.set_tooltip_text(Some(&item.url));
+            }
+            pin_icon.set_visible(item.pinned);
+
+            let code = item.status.split(' ').next().unwrap_or_default();
+            status_label.set_text(code);
+            status_label.set_tooltip_text(Some(&item.status));
+            for class in [
+                config::CLASS_SUCCESS,
+                config::CLASS_WARNING,
+                config::CLASS_ERROR,
+            ] {
+                status_label.remove_css_class(class);
+            }
+            if let Some(class) = status_class(code) {
+                status_label.add_css_class(class);
+            }
+            status_label.set_visible(!code.is_empty());
+            let (relative, local) = relative_time(&item.timestamp);
+            time_label.set_text(&relative);
+            time_label.set_tooltip_text(Some(&local));
+
+            let tags: Vec<String> = item.tags.iter().map(|tag| format!("#{}", tag)).collect();
+            tags_label.set_text(&tags.join(" "));
+            tags_label.set_visible(!tags.is_empty());
+            // The note shows on hover
+            let note = (!item.note.is_empty()).then_some(item.note.as_str());
+            row_box_ref.set_tooltip_text(note);
+
+            match &row.snippet {
+                Some(snippet) => snippet_label.set_markup(&snippet_markup(snippet)),
+                None => snippet_label.set_text(""),
+            }
+            snippet_label.set_visible(row.snippet.is_some());
+
+            let results = &row.results;
+            let passed = results.iter().filter(|r| r.passed).count();
+            let all_passed = passed == results.len();
+            tests_label.set_text(&format!(
+                "{} {}/{}",
+                if all_passed { "✓" } else { "✗" },
+                passed,
+                results.len()
+            ));
+            tests_label.remove_css_class(config::CLASS_SUCCESS);
+            tests_label.remove_css_class(config::CLASS_ERROR);
+            tests_label.add_css_class(if all_passed {
+                config::CLASS_SUCCESS
+            } else {
+                config::CLASS_ERROR
+            });
+            tests_label.set_visible(!results.is_empty());
+        });
+
+        list_item.set_child(Some(&row_box));
+    });
+    factory
+}
+
+/// Section titles above the first row of each section.
+fn history_header_factory() -> SignalListItemFactory {
+    let factory = SignalListItemFactory::new();
+    factory.connect_setup(|_, object| {
+        let Some(header) = object.downcast_ref::<gtk::ListHeader>() else {
+            return;
+        };
+        let label = Label::builder()
+            .xalign(0.0)
+            .ellipsize(gtk::pango::EllipsizeMode::End)
+            .margin_top(config::SPACING_MEDIUM)
+            .margin_start(config::SPACING_MEDIUM)
+            .margin_end(config::SPACING_MEDIUM)
+            .css_classes(vec!["heading".to_string(), "dim-label".to_string()])
+            .build();
+        header.set_child(Some(&label));
+        header.connect_item_notify(move |header| {
+            let Some(object) = header.item().and_downcast::<glib::BoxedAnyObject>() else {
+                return;
+            };
+            label.set_text(&object.borrow::<HistoryRow>().section);
+        });
+    });
+    factory
+}
+
+/// Colors the status code by its class; 3xx and unknown codes stay plain.
+fn status_class(code: &str) -> Option<&'static str> {
+    match code.chars().next()? {
+        '2' => Some(config::CLASS_SUCCESS),
+        '4' => Some(config::CLASS_WARNING),
+        '5' => Some(config::CLASS_ERROR),
+        _ => None,
+    }
+}
+
+/// How long ago a UTC `YYYY-MM-DD HH:MM:SS` timestamp was, and the full
+/// local time for the tooltip.
+fn relative_time(timestamp: &str) -> (String, String) {
+    let Ok(time) = chrono::NaiveDateTime::parse_from_str(timestamp, "%Y-%m-%d %H:%M:%S") else {
+        return (timestamp.to_string(), timestamp.to_string());
+    };
+    let time = time.and_utc();
+    let local = time.with_timezone(&chrono::Local);
+    let elapsed = chrono::Utc::now() - time;
+
+    let relative = if elapsed.num_minutes() < 1 {
+        "just now".to_string()
+    } else if elapsed.num_hours() < 1 {
+        format!("{} min ago", elapsed.num_minutes())
+    } else if elapsed.num_days() < 1 {
+        format!("{} h ago", elapsed.num_hours())
+    } else if elapsed.num_days() < 7 {
+        format!("{} d ago", elapsed.num_days())
+    } else {
+        local.format("%Y-%m-%d").to_string()
+    };
+    (relative, local.format("%Y-%m-%d %H:%M:%S").to_string())
+}
+
+/// The id of the entry shown and whether it is pinned.
+fn row_entry(list_item: &ListItem) -> Option<(i64, bool)> {
+    let object = list_item.item().and_downcast::<glib::BoxedAnyObject>()?;
+    let row = object.borrow::<HistoryRow>();
+    Some((row.item.id, row.item.pinned))
+}
+
+/// One line with the matched terms in bold.
+fn snippet_markup(snippet: &str) -> String {
+    glib::markup_escape_text(&snippet.replace('\n', " "))
+        .replace(HIGHLIGHT_START, "<b>")
+        .replace(HIGHLIGHT_END, "</b>")
+}
 
-    row_box.append(&method_label);
-    row_box.append(&url_label);
+// Items target the `history` action group installed on the window
+fn history_row_menu(id: i64, pinned: bool) -> gio::Menu {
+    let section = |items: &[(&str, &str)]| {
+        let section = gio::Menu::new();
+        for (label, action) in items {
+            let item = gio::MenuItem::new(Some(label), None);
+            item.set_action_and_target_value(Some(action), Some(&id.to_variant()));
+            section.append_item(&item);
+        }
+        section
+    };
 
-    row.set_child(Some(&row_box));
+    let pin_label = if pinned { "Unpin" } else { "Pin to Top" };
 
-    row.set_widget_name(&id.to_string());
+    let menu = gio::Menu::new();
+    menu.append_section(
+        None,
+        &section(&[
+            (pin_label, "history.toggle-pin"),
+            ("Rename…", "history.rename"),
+            ("Note and Tags…", "history.annotate"),
+        ]),
+    );
+    let compare = section(&[("Compare with Response", "history.compare")]);
+    compare.append(Some("Compare Selected"), Some("history.compare-selected"));
+    menu.append_section(None, &compare);
+    menu.append_section(
+        None,
+        &section(&[
+            ("Copy URL", "history.copy-url"),
+            ("Duplicate as New Request", "history.duplicate"),
+            ("Generate Code", "history.generate-code"),
+            ("Export as HAR…", "history.export-entry-har"),
+        ]),
+    );
+    // Applies to every selected entry when this one is among them
+    menu.append_section(None, &section(&[("Delete", "history.delete")]));
 
-    list.append(&row);
+    menu
 }
@@ -0,0 +1,174 @@
+use adw::{EntryRow, prelude::*};
+use chrono::{DateTime, Local};
+use gtk::{Box, Button, Label, ListBox, MenuButton, Orientation, ScrolledWindow, gio};
+
+use crate::{config, snapshot::Snapshot, ui::helpers::add_box_margins};
+
+/// The "Snapshot" response tab: the response the saved request is expected
+/// to return, and how the shown one differs from it.
+#[derive(Clone)]
+pub struct SnapshotView {
+    pub container: Box,
+    /// Applied with its button, as JSONPath expressions separated by commas
+    pub ignored_row: EntryRow,
+    info_label: Label,
+    accept_button: Button,
+    summary_label: Label,
+    differences_list: ListBox,
+}
+
+impl SnapshotView {
+    pub fn new() -> Self {
+        let container = Box::new(Orientation::Vertical, config::SPACING_NONE);
+
+        let toolbar = Box::new(Orientation::Horizontal, config::SPACING_SMALL);
+        add_box_margins(&toolbar, config::SPACING_MEDIUM);
+
+        let info_label = Label::new(None);
+        info_label.set_hexpand(true);
+        info_label.set_xalign(0.0);
+        info_label.set_ellipsize(gtk::pango::EllipsizeMode::End);
+
+        // Both target the `snapshot` action group installed on the window
+        let accept_button = Button::builder()
+            .label("Accept New Response")
+            .action_name("snapshot.record")
+            .visible(false)
+            .build();
+
+        let menu = gio::Menu::new();
+        menu.append(Some("Record from Response"), Some("snapshot.record"));
+        menu.append(Some("Compare with Snapshot…"), Some("snapshot.compare"));
+        menu.append(Some("Delete Snapshot"), Some("snapshot.delete"));
+
+        let menu_btn = MenuButton::builder()
+            .icon_name("view-more-symbolic")
+            .css_classes(vec!["flat".to_string()])
+            .tooltip_text("Snapshot Menu")
+            .menu_model(&menu)
+            .build();
+
+        toolbar.append(&info_label);
+        toolbar.append(&accept_button);
+        toolbar.append(&menu_btn);
+
+        let ignored_row = EntryRow::builder()
+            .title("Ignored Paths")
+            .show_apply_button(true)
+            .tooltip_text("JSONPath expressions separated by commas, e.g. $.id, $..created_at")
+            .build();
+        let ignored_list = ListBox::new();
+        ignored_list.set_selection_mode(gtk::SelectionMode::None);
+        ignored_list.add_css_class("boxed-list");
+        ignored_list.set_margin_start(config::SPACING_MEDIUM);
+        ignored_list.set_margin_end(config::SPACING_MEDIUM);
+        ignored_list.set_margin_bottom(config::SPACING_MEDIUM);
+        ignored_list.append(&ignored_row);
+
+        let summary_label = Label::new(None);
+        summary_label.set_xalign(0.0);
+        summary_label.set_wrap(true);
+        summary_label.set_margin_start(config::SPACING_MEDIUM);
+        summary_label.set_margin_end(config::SPACING_MEDIUM);
+
+        let differences_list = ListBox::new();
+        differences_list.set_selection_mode(gtk::SelectionMode::None);
+        differences_list.set_margin_start(config::SPACING_MEDIUM);
+        differences_list.set_margin_end(config::SPACING_MEDIUM);
+        differences_list.set_margin_bottom(config::SPACING_MEDIUM);
+
+        let scrolled = ScrolledWindow::builder()
+            .hscrollbar_policy(gtk::PolicyType::Never)
+            .child(&differences_list)
+            .vexpand(true)
+            .build();
+
+        container.append(&toolbar);
+        container.append(&ignored_list);
+        container.append(&summary_label);
+        container.append(&scrolled);
+
+        let view = SnapshotView {
+            container,
+            ignored_row,
+            info_label,
+            accept_button,
+            summary_label,
+            differences_list,
+        };
+        view.set_snapshot(None, false);
+        view
+    }
+
+    /// `saved` tells whether a saved request is loaded, which a snapshot needs.
+    pub fn set_snapshot(&self, snapshot: Option<&Snapshot>, saved: bool) {
+        let text = match snapshot {
+            Some(snapshot) => match DateTime::parse_from_rfc3339(&snapshot.recorded) {
+                Ok(recorded) => format!(
+                    "Recorded {}",
+                    recorded.with_timezone(&Local).format("%Y-%m-%d %H:%M")
+                ),
+                Err(_) => "Recorded".to_string(),
+            },
+            None if saved => "No snapshot recorded".to_string(),
+            None => "Save the request to record a snapshot".to_string(),
+        };
+        self.info_label.set_text(&text);
+        self.ignored_row
+            .set_text(&snapshot.map(|s| s.ignored.join(", ")).unwrap_or_default());
+        self.ignored_row.set_sensitive(snapshot.is_some());
+        self.clear_result();
+    }
+
+    pub fn ignored(&self) -> Vec<String> {
+        self.ignored_row
+            .text()
+            .split(',')
+            .map(str::trim)
+            .filter(|path| !path.is_empty())
+            .map(String::from)
+            .collect()
+    }
+
+    pub fn clear_result(&self) {
+        self.accept_button.set_visible(false);
+        self.summary_label.set_text("");
+        self.summary_label.remove_css_class(config::CLASS_SUCCESS);
+        self.summary_label.remove_css_class(config::CLASS_ERROR);
+        while let Some(row) = self.differences_list.first_child() {
+            self.differences_list.remove(&row);
+        }
+    }
+
+    /// What `Snapshot::differences` found in the shown response.
+    pub fn show_result(&self, differences: &[String]) {
+        self.clear_result();
+
+        if differences.is_empty() {
+            self.summary_label.set_text("Response matches the snapshot");
+            self.summary_label.add_css_class(config::CLASS_SUCCESS);
+            return;
+        }
+        self.summary_label.set_text(&format!(
+            "{} differences from the snapshot",
+            differences.len()
+        ));
+        self.summary_label.add_css_class(config::CLASS_ERROR);
+        self.accept_button.set_visible(true);
+
+        for difference in differences {
+            let label = Label::builder()
+                .label(difference.as_str())
+                .css_classes(vec!["monospace".to_string()])
+                .xalign(0.0)
+                .wrap(true)
+                .selectable(true)
+                .margin_top(config::SPACING_EXTRA_SMALL)
+                .margin_bottom(config::SPACING_EXTRA_SMALL)
+                .margin_start(config::SPACING_EXTRA_SMALL)
+                .margin_end(config::SPACING_EXTRA_SMALL)
+                .build();
+            self.differences_list.append(&label);
+        }
+    }
+}
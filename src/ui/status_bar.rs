@@ -8,6 +8,10 @@ pub struct StatusBarWidgets {
     pub status_label: Label,
     pub time_label: Label,
     pub size_label: Label,
+    /// Variables the last response set, hidden when there are none
+    pub captured_label: Label,
+    /// Whether the last response matched the request's snapshot, hidden without one
+    pub snapshot_label: Label,
     pub spinner: Spinner,
 }
 pub fn build() -> StatusBarWidgets {
@@ -47,11 +51,28 @@ pub fn build() -> StatusBarWidgets {
 
     add_pair(&container, "Size: ", &size_label);
 
+    // "Captured: token, id"
+    let captured_label = Label::new(None);
+    captured_label.set_visible(false);
+    captured_label.set_hexpand(true);
+    captured_label.set_xalign(1.0);
+    captured_label.set_ellipsize(gtk::pango::EllipsizeMode::End);
+    container.append(&captured_label);
+
+    // "Matches snapshot"
+    let snapshot_label = Label::new(None);
+    snapshot_label.set_visible(false);
+    snapshot_label.set_hexpand(true);
+    snapshot_label.set_xalign(1.0);
+    container.append(&snapshot_label);
+
     StatusBarWidgets {
         container,
         status_label,
         time_label,
         size_label,
+        captured_label,
+        snapshot_label,
         spinner,
     }
 }
@@ -0,0 +1,105 @@
+use gtk::{Box, Image, Label, ListBox, Orientation, ScrolledWindow, prelude::*};
+
+use crate::{config, models::AssertionResult, ui::helpers::add_box_margins};
+
+/// The response "Tests" tab: how the last response fared against the request's assertions.
+#[derive(Clone)]
+pub struct TestResultsView {
+    pub container: Box,
+    summary_label: Label,
+    results_list: ListBox,
+}
+
+impl TestResultsView {
+    pub fn new() -> Self {
+        let container = Box::new(Orientation::Vertical, config::SPACING_NONE);
+
+        let summary_label = Label::new(None);
+        summary_label.set_xalign(0.0);
+        summary_label.set_wrap(true);
+        summary_label.set_margin_top(config::SPACING_MEDIUM);
+        summary_label.set_margin_start(config::SPACING_MEDIUM);
+        summary_label.set_margin_end(config::SPACING_MEDIUM);
+
+        let results_list = ListBox::new();
+        results_list.set_selection_mode(gtk::SelectionMode::None);
+        results_list.set_margin_start(config::SPACING_MEDIUM);
+        results_list.set_margin_end(config::SPACING_MEDIUM);
+        results_list.set_margin_bottom(config::SPACING_MEDIUM);
+
+        let scrolled = ScrolledWindow::builder()
+            .hscrollbar_policy(gtk::PolicyType::Never)
+            .child(&results_list)
+            .vexpand(true)
+            .build();
+
+        container.append(&summary_label);
+        container.append(&scrolled);
+
+        let view = TestResultsView {
+            container,
+            summary_label,
+            results_list,
+        };
+        view.clear();
+        view
+    }
+
+    pub fn clear(&self) {
+        self.summary_label.set_text("No assertions were checked");
+        self.summary_label.remove_css_class(config::CLASS_SUCCESS);
+        self.summary_label.remove_css_class(config::CLASS_ERROR);
+        while let Some(row) = self.results_list.first_child() {
+            self.results_list.remove(&row);
+        }
+    }
+
+    pub fn show(&self, results: &[AssertionResult]) {
+        self.clear();
+        if results.is_empty() {
+            return;
+        }
+
+        let passed = results.iter().filter(|r| r.passed).count();
+        self.summary_label
+            .set_text(&format!("{} of {} passed", passed, results.len()));
+        self.summary_label
+            .add_css_class(if passed == results.len() {
+                config::CLASS_SUCCESS
+            } else {
+                config::CLASS_ERROR
+            });
+
+        for result in results {
+            let row = Box::new(Orientation::Horizontal, config::SPACING_MEDIUM);
+            add_box_margins(&row, config::SPACING_EXTRA_SMALL);
+
+            let icon = Image::from_icon_name(if result.passed {
+                "emblem-ok-symbolic"
+            } else {
+                "dialog-error-symbolic"
+            });
+            icon.add_css_class(if result.passed {
+                config::CLASS_SUCCESS
+            } else {
+                config::CLASS_ERROR
+            });
+
+            let description = Label::new(Some(result.description.as_str()));
+            description.set_xalign(0.0);
+            description.set_wrap(true);
+            description.set_hexpand(true);
+
+            let message = Label::new(Some(result.message.as_str()));
+            message.add_css_class("dim-label");
+            message.set_xalign(1.0);
+            message.set_wrap(true);
+            message.set_selectable(true);
+
+            row.append(&icon);
+            row.append(&description);
+            row.append(&message);
+            self.results_list.append(&row);
+        }
+    }
+}
@@ -0,0 +1,211 @@
+use gtk::{glib, prelude::*, subclass::prelude::*};
+
+use crate::variables;
+
+/// Proposals for the built-in `{{$name}}` placeholders whose names start with
+/// what is typed after `{{` or `{{$`.
+fn matching(typed: &str) -> Vec<VariableProposal> {
+    variables::DYNAMIC_VARIABLES
+        .iter()
+        .filter(|name| name.trim_start_matches('$').starts_with(typed))
+        .map(|name| VariableProposal::new(name))
+        .collect()
+}
+
+mod imp {
+    use gtk::{gdk, gio, glib, prelude::*, subclass::prelude::*};
+    use sourceview5::{
+        CompletionCell, CompletionColumn, CompletionContext, CompletionProposal,
+        subclass::prelude::*,
+    };
+    use std::{cell::RefCell, future::Future, pin::Pin};
+
+    #[derive(Default)]
+    pub struct VariableProposal {
+        pub name: RefCell<String>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for VariableProposal {
+        const NAME: &'static str = "DispatchVariableProposal";
+        type Type = super::VariableProposal;
+        type Interfaces = (CompletionProposal,);
+    }
+
+    impl ObjectImpl for VariableProposal {}
+
+    impl CompletionProposalImpl for VariableProposal {}
+
+    // Every method is implemented, since the interface leaves some of them
+    // without a default to chain up to
+    #[derive(Default)]
+    pub struct VariableProvider;
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for VariableProvider {
+        const NAME: &'static str = "DispatchVariableProvider";
+        type Type = super::VariableProvider;
+        type Interfaces = (sourceview5::CompletionProvider,);
+    }
+
+    impl ObjectImpl for VariableProvider {}
+
+    impl CompletionProviderImpl for VariableProvider {
+        fn activate(&self, context: &CompletionContext, proposal: &CompletionProposal) {
+            let (Some(buffer), Some((mut start, mut end))) = (context.buffer(), context.bounds())
+            else {
+                return;
+            };
+            let Some(proposal) = proposal.downcast_ref::<super::VariableProposal>() else {
+                return;
+            };
+            // Words stop at `$`, so one typed before the name is replaced too
+            let mut before = start;
+            if before.backward_char() && before.char() == '$' {
+                start = before;
+            }
+            buffer.begin_user_action();
+            buffer.delete(&mut start, &mut end);
+            buffer.insert(&mut start, &proposal.name());
+            buffer.end_user_action();
+        }
+
+        fn display(
+            &self,
+            _context: &CompletionContext,
+            proposal: &CompletionProposal,
+            cell: &CompletionCell,
+        ) {
+            let name = proposal
+                .downcast_ref::<super::VariableProposal>()
+                .map(|proposal| proposal.name());
+            match cell.column() {
+                CompletionColumn::TypedText => cell.set_text(name.as_deref()),
+                _ => cell.set_text(None),
+            }
+        }
+
+        fn title(&self) -> Option<glib::GString> {
+            Some("Dynamic Variables".into())
+        }
+
+        fn priority(&self, _context: &CompletionContext) -> i32 {
+            0
+        }
+
+        fn is_trigger(&self, _iter: &gtk::TextIter, _c: char) -> bool {
+            false
+        }
+
+        fn key_activates(
+            &self,
+            _context: &CompletionContext,
+            _proposal: &CompletionProposal,
+            _keyval: gdk::Key,
+            _state: gdk::ModifierType,
+        ) -> bool {
+            false
+        }
+
+        fn refilter(&self, context: &CompletionContext, model: &gio::ListModel) {
+            if let Some(store) = model.downcast_ref::<gio::ListStore>() {
+                store.splice(0, store.n_items(), &super::matching(&context.word()));
+            }
+        }
+
+        fn list_alternates(
+            &self,
+            _context: &CompletionContext,
+            _proposal: &CompletionProposal,
+        ) -> Vec<CompletionProposal> {
+            Vec::new()
+        }
+
+        fn populate(&self, context: &CompletionContext) -> Result<gio::ListModel, glib::Error> {
+            let store = gio::ListStore::new::<super::VariableProposal>();
+            store.extend_from_slice(&super::matching(&context.word()));
+            Ok(store.upcast())
+        }
+
+        fn populate_future(
+            &self,
+            context: &CompletionContext,
+        ) -> Pin<Box<dyn Future<Output = Result<gio::ListModel, glib::Error>>>> {
+            Box::pin(std::future::ready(self.populate(context)))
+        }
+    }
+}
+
+glib::wrapper! {
+    /// A built-in placeholder offered by [`VariableProvider`].
+    pub struct VariableProposal(ObjectSubclass<imp::VariableProposal>)
+        @implements sourceview5::CompletionProposal;
+}
+
+impl VariableProposal {
+    fn new(name: &str) -> Self {
+        let proposal: Self = glib::Object::new();
+        proposal.imp().name.replace(name.to_string());
+        proposal
+    }
+
+    /// The full name, with its `$`.
+    pub fn name(&self) -> String {
+        self.imp().name.borrow().clone()
+    }
+}
+
+glib::wrapper! {
+    /// Completes the names of built-in `{{$name}}` placeholders in a source
+    /// view, inserting them with their `$`.
+    pub struct VariableProvider(ObjectSubclass<imp::VariableProvider>)
+        @implements sourceview5::CompletionProvider;
+}
+
+impl Default for VariableProvider {
+    fn default() -> Self {
+        glib::Object::new()
+    }
+}
+
+/// Offers the built-in `{{$name}}` placeholders in `entry` while one is being
+/// typed at the end of its text.
+#[allow(deprecated)]
+pub fn attach_to_entry(entry: &gtk::Entry) {
+    let names = gtk::ListStore::new(&[glib::Type::STRING]);
+    for name in variables::DYNAMIC_VARIABLES {
+        names.insert_with_values(None, &[(0, &name)]);
+    }
+
+    let completion = gtk::EntryCompletion::new();
+    completion.set_model(Some(&names));
+    completion.set_text_column(0);
+    // `key` is the whole text, folded to lower case
+    completion.set_match_func(|completion, key, iter| {
+        let Some((_, typed)) = key.rsplit_once("{{") else {
+            return false;
+        };
+        let typed = typed.trim_start().trim_start_matches('$');
+        let name = completion
+            .model()
+            .map(|model| model.get::<String>(iter, 0))
+            .unwrap_or_default();
+        !typed.contains('}')
+            && name
+                .trim_start_matches('$')
+                .to_lowercase()
+                .starts_with(typed)
+    });
+    completion.connect_match_selected(|completion, model, iter| {
+        let name = model.get::<String>(iter, 0);
+        if let Some(entry) = completion.entry() {
+            let text = entry.text();
+            if let Some(start) = text.rfind("{{") {
+                entry.set_text(&format!("{}{{{{{}}}}}", &text[..start], name));
+                entry.set_position(-1);
+            }
+        }
+        glib::Propagation::Stop
+    });
+    entry.set_completion(Some(&completion));
+}
@@ -1,21 +1,69 @@
-use crate::ui::key_value_editor::KeyValueEditor;
+use crate::models::{Method, Request};
+use crate::ui::{
+    assertion_editor::AssertionEditor, contract_view::ContractView,
+    extractor_editor::ExtractorEditor, history_model::HistoryModel, history_search::HistorySearch,
+    key_value_editor::KeyValueEditor, options_editor::OptionsEditor, runner_view::RunnerView,
+    script_editor::ScriptEditor, snapshot_view::SnapshotView, test_results_view::TestResultsView,
+};
+use gtk::prelude::*;
 use gtk::{DropDown, Entry, Label, ListBox, Spinner};
 use sourceview5::Buffer;
 
 #[derive(Clone)]
 pub struct WindowWidgets {
+    pub window: adw::ApplicationWindow,
+    pub toast_overlay: adw::ToastOverlay,
     pub url_entry: Entry,
     pub method_dropdown: DropDown,
     pub request_body_buffer: Buffer,
     pub response_buffer: Buffer,
     pub response_headers_buffer: Buffer,
+    pub test_results_view: TestResultsView,
+    pub contract_view: ContractView,
+    pub snapshot_view: SnapshotView,
+    pub console_buffer: Buffer,
     pub status_label: Label,
     pub time_label: Label,
     pub size_label: Label,
+    pub captured_label: Label,
+    pub snapshot_label: Label,
     pub spinner: Spinner,
     pub headers_editor: KeyValueEditor,
-    pub history_list: ListBox,
+    pub options_editor: OptionsEditor,
+    pub assertion_editor: AssertionEditor,
+    pub extractor_editor: ExtractorEditor,
+    pub script_editor: ScriptEditor,
+    pub runner_view: RunnerView,
+    pub environment_dropdown: DropDown,
+    pub history_list: gtk::ListView,
+    pub history_model: HistoryModel,
+    pub history_search: HistorySearch,
+    pub collections_list: ListBox,
     pub send_button: gtk::Button,
+    pub generate_code_btn: gtk::Button,
+    pub save_request_btn: gtk::Button,
     pub new_request_btn: gtk::Button,
     pub clear_history_btn: gtk::Button,
 }
+
+impl WindowWidgets {
+    /// Snapshot of the request as currently entered in the editor.
+    pub fn current_request(&self) -> Request {
+        let (start, end) = self.request_body_buffer.bounds();
+
+        Request {
+            method: Method::from_index(self.method_dropdown.selected()),
+            url: self.url_entry.text().to_string(),
+            headers: self.headers_editor.get_data(),
+            body: self
+                .request_body_buffer
+                .text(&start, &end, true)
+                .to_string(),
+            options: self.options_editor.get_data(),
+            contract: self.contract_view.get_link(),
+            assertions: self.assertion_editor.get_data(),
+            extractors: self.extractor_editor.get_data(),
+            scripts: self.script_editor.get_data(),
+        }
+    }
+}
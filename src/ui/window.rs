@@ -1,10 +1,14 @@
-use super::sidebar;
 use crate::database;
-use crate::state::Action;
-use crate::ui::{layout, reducer};
+use crate::formats::{curl, report::ReportFormat};
+use crate::state::{Action, AppState};
+use crate::ui::{
+    collections_view::{self, RowTarget},
+    layout, reducer,
+};
 use adw::{prelude::*, Application};
 use glib;
-use std::rc::Rc;
+use gtk::gio;
+use std::{cell::RefCell, rc::Rc};
 
 #[allow(deprecated)]
 pub fn build(app: &Application) {
@@ -12,22 +16,42 @@ pub fn build(app: &Application) {
     let (window, widgets) = layout::build_ui(app);
 
     // Init DB
-    let db = Rc::new(database::Database::new().expect("Failed to init DB"));
+    let db = match database::Database::new() {
+        Ok(db) => Rc::new(db),
+        Err(e) => {
+            // Nothing works without it, so explain why and close
+            let dialog = adw::AlertDialog::new(Some("Cannot Open Database"), Some(&e.to_string()));
+            dialog.add_response("close", "Close");
+            dialog.connect_response(
+                None,
+                glib::clone!(@strong window => move |_, _| window.close()),
+            );
+            window.present();
+            dialog.present(Some(&window));
+            return;
+        }
+    };
 
     // Load Data
-    if let Ok(history) = db.get_history() {
-        for item in history.iter().rev() {
-            sidebar::add_history_row(&widgets.history_list, &item.method, &item.url, item.id);
-        }
-    }
+    // Entries deleted last session can no longer be restored
+    let _ = db.purge_history(None);
+    // Vacuuming blocks writes, so it happens now rather than next to a send
+    let _ = db.compact();
+    widgets.history_model.set_database(db.clone());
+    reducer::reload_history(&widgets);
+
+    let state = Rc::new(RefCell::new(AppState::default()));
+    reducer::reload_collections(&widgets, &db);
+    reducer::load_active_environment(&db, &state);
+    reducer::reload_environments(&widgets, &db, &state);
 
     // MVU Loop
     let (sender, receiver) = glib::MainContext::channel(glib::Priority::DEFAULT);
 
     receiver.attach(
         None,
-        glib::clone!(@strong widgets, @strong db, @strong sender => move |action| {
-            reducer::handle_action(action, &widgets, &db, &sender);
+        glib::clone!(@strong widgets, @strong db, @strong state, @strong sender => move |action| {
+            reducer::handle_action(action, &widgets, &db, &state, &sender);
             glib::ControlFlow::Continue
         }),
     );
@@ -38,6 +62,46 @@ pub fn build(app: &Application) {
             sender.send(Action::SendRequest).unwrap();
         }));
 
+    let history_search = widgets.history_search.clone();
+    widgets
+        .url_entry
+        .connect_changed(glib::clone!(@strong sender => move |_| {
+            if history_search.current_host_only() {
+                let filter = history_search.filter();
+                let grouping = history_search.grouping();
+                sender.send(Action::SearchHistory(filter, grouping)).unwrap();
+            }
+        }));
+
+    // A curl command pasted into the URL bar, or typed and confirmed with
+    // Enter, is imported; the import replaces the URL it left behind
+    widgets
+        .url_entry
+        .connect_activate(glib::clone!(@strong sender => move |entry| {
+            let text = entry.text();
+            if curl::looks_like_curl(&text) {
+                sender.send(Action::ImportCurl(text.to_string())).unwrap();
+            }
+        }));
+
+    if let Some(text) = widgets
+        .url_entry
+        .delegate()
+        .and_then(|delegate| delegate.downcast::<gtk::Text>().ok())
+    {
+        text.connect_paste_clipboard(glib::clone!(@strong sender => move |text| {
+            text.clipboard().read_text_async(
+                None::<&gio::Cancellable>,
+                glib::clone!(@strong sender => move |result| {
+                    let pasted = result.ok().flatten();
+                    if let Some(pasted) = pasted.filter(|text| curl::looks_like_curl(text)) {
+                        sender.send(Action::ImportCurl(pasted.to_string())).unwrap();
+                    }
+                }),
+            );
+        }));
+    }
+
     widgets
         .new_request_btn
         .connect_clicked(glib::clone!(@strong sender => move |_| {
@@ -51,13 +115,351 @@ pub fn build(app: &Application) {
         }));
 
     widgets
-        .history_list
+        .generate_code_btn
+        .connect_clicked(glib::clone!(@strong sender => move |_| {
+            sender.send(Action::GenerateCode).unwrap();
+        }));
+
+    widgets
+        .save_request_btn
+        .connect_clicked(glib::clone!(@strong sender => move |_| {
+            sender.send(Action::SaveRequest).unwrap();
+        }));
+
+    // Actions behind the history row context menu
+    let history_actions = gio::SimpleActionGroup::new();
+
+    let generate_code = gio::SimpleAction::new("generate-code", Some(&i64::static_variant_type()));
+    generate_code.connect_activate(glib::clone!(@strong sender => move |_, parameter| {
+        if let Some(id) = parameter.and_then(|p| p.get::<i64>()) {
+            sender.send(Action::GenerateCodeForHistory(id)).unwrap();
+        }
+    }));
+    history_actions.add_action(&generate_code);
+
+    let export_entry_har =
+        gio::SimpleAction::new("export-entry-har", Some(&i64::static_variant_type()));
+    export_entry_har.connect_activate(glib::clone!(@strong sender => move |_, parameter| {
+        if let Some(id) = parameter.and_then(|p| p.get::<i64>()) {
+            sender.send(Action::ExportHar(Some(id))).unwrap();
+        }
+    }));
+    history_actions.add_action(&export_entry_har);
+
+    // Each takes the id of the entry the menu was opened on
+    type EntryAction = fn(i64) -> Action;
+    let entry_actions: [(&str, EntryAction); 7] = [
+        ("toggle-pin", Action::TogglePinHistoryItem),
+        ("rename", Action::RenameHistoryItem),
+        ("annotate", Action::AnnotateHistoryItem),
+        ("copy-url", Action::CopyHistoryUrl),
+        ("duplicate", Action::DuplicateHistoryItem),
+        ("compare", Action::CompareWithResponse),
+        ("delete", Action::DeleteHistoryItem),
+    ];
+    for (name, action) in entry_actions {
+        let entry_action = gio::SimpleAction::new(name, Some(&i64::static_variant_type()));
+        entry_action.connect_activate(glib::clone!(@strong sender => move |_, parameter| {
+            if let Some(id) = parameter.and_then(|p| p.get::<i64>()) {
+                sender.send(action(id)).unwrap();
+            }
+        }));
+        history_actions.add_action(&entry_action);
+    }
+
+    let delete_selected = gio::SimpleAction::new("delete-selected", None);
+    delete_selected.connect_activate(glib::clone!(@strong sender => move |_, _| {
+        sender.send(Action::DeleteSelectedHistory).unwrap();
+    }));
+    history_actions.add_action(&delete_selected);
+
+    let compare_selected = gio::SimpleAction::new("compare-selected", None);
+    compare_selected.connect_activate(glib::clone!(@strong sender => move |_, _| {
+        sender.send(Action::CompareSelectedHistory).unwrap();
+    }));
+    history_actions.add_action(&compare_selected);
+
+    let export_har = gio::SimpleAction::new("export-har", None);
+    export_har.connect_activate(glib::clone!(@strong sender => move |_, _| {
+        sender.send(Action::ExportHar(None)).unwrap();
+    }));
+    history_actions.add_action(&export_har);
+
+    let import_har = gio::SimpleAction::new("import-har", None);
+    import_har.connect_activate(glib::clone!(@strong sender => move |_, _| {
+        sender.send(Action::ImportHar).unwrap();
+    }));
+    history_actions.add_action(&import_har);
+
+    let preferences = gio::SimpleAction::new("preferences", None);
+    preferences.connect_activate(glib::clone!(@strong sender => move |_, _| {
+        sender.send(Action::EditHistoryPreferences).unwrap();
+    }));
+    history_actions.add_action(&preferences);
+
+    window.insert_action_group("history", Some(&history_actions));
+
+    // Actions behind the collections menu and collection row context menu
+    let collection_actions = gio::SimpleActionGroup::new();
+
+    let import_postman = gio::SimpleAction::new("import-postman", None);
+    import_postman.connect_activate(glib::clone!(@strong sender => move |_, _| {
+        sender.send(Action::ImportPostmanCollection).unwrap();
+    }));
+    collection_actions.add_action(&import_postman);
+
+    let import_openapi = gio::SimpleAction::new("import-openapi", None);
+    import_openapi.connect_activate(glib::clone!(@strong sender => move |_, _| {
+        sender.send(Action::ImportOpenApi).unwrap();
+    }));
+    collection_actions.add_action(&import_openapi);
+
+    let open_http = gio::SimpleAction::new("open-http", None);
+    open_http.connect_activate(glib::clone!(@strong sender => move |_, _| {
+        sender.send(Action::OpenHttpFile).unwrap();
+    }));
+    collection_actions.add_action(&open_http);
+
+    let export_postman =
+        gio::SimpleAction::new("export-postman", Some(&i64::static_variant_type()));
+    export_postman.connect_activate(glib::clone!(@strong sender => move |_, parameter| {
+        if let Some(id) = parameter.and_then(|p| p.get::<i64>()) {
+            sender.send(Action::ExportPostmanCollection(id)).unwrap();
+        }
+    }));
+    collection_actions.add_action(&export_postman);
+
+    let export_http = gio::SimpleAction::new("export-http", Some(&i64::static_variant_type()));
+    export_http.connect_activate(glib::clone!(@strong sender => move |_, parameter| {
+        if let Some(id) = parameter.and_then(|p| p.get::<i64>()) {
+            sender.send(Action::ExportHttpCollection(id)).unwrap();
+        }
+    }));
+    collection_actions.add_action(&export_http);
+
+    let run_collection = gio::SimpleAction::new("run", Some(&i64::static_variant_type()));
+    run_collection.connect_activate(glib::clone!(@strong sender => move |_, parameter| {
+        if let Some(id) = parameter.and_then(|p| p.get::<i64>()) {
+            sender.send(Action::RunCollection(id)).unwrap();
+        }
+    }));
+    collection_actions.add_action(&run_collection);
+
+    let run_item = gio::SimpleAction::new("run-item", Some(&i64::static_variant_type()));
+    run_item.connect_activate(glib::clone!(@strong sender => move |_, parameter| {
+        if let Some(id) = parameter.and_then(|p| p.get::<i64>()) {
+            sender.send(Action::RunItem(id)).unwrap();
+        }
+    }));
+    collection_actions.add_action(&run_item);
+
+    let delete_collection = gio::SimpleAction::new("delete", Some(&i64::static_variant_type()));
+    delete_collection.connect_activate(glib::clone!(@strong sender => move |_, parameter| {
+        if let Some(id) = parameter.and_then(|p| p.get::<i64>()) {
+            sender.send(Action::DeleteCollection(id)).unwrap();
+        }
+    }));
+    collection_actions.add_action(&delete_collection);
+
+    window.insert_action_group("collections", Some(&collection_actions));
+
+    // Actions behind the environments menu
+    let environment_actions = gio::SimpleActionGroup::new();
+
+    let new_environment = gio::SimpleAction::new("new", None);
+    new_environment.connect_activate(glib::clone!(@strong sender => move |_, _| {
+        sender.send(Action::NewEnvironment).unwrap();
+    }));
+    environment_actions.add_action(&new_environment);
+
+    let edit_environment = gio::SimpleAction::new("edit", None);
+    edit_environment.connect_activate(glib::clone!(@strong sender => move |_, _| {
+        sender.send(Action::EditEnvironment).unwrap();
+    }));
+    environment_actions.add_action(&edit_environment);
+
+    let unlock_secrets = gio::SimpleAction::new("unlock", None);
+    unlock_secrets.connect_activate(glib::clone!(@strong sender => move |_, _| {
+        sender.send(Action::PromptUnlockSecrets).unwrap();
+    }));
+    environment_actions.add_action(&unlock_secrets);
+
+    let import_environment = gio::SimpleAction::new("import-postman", None);
+    import_environment.connect_activate(glib::clone!(@strong sender => move |_, _| {
+        sender.send(Action::ImportPostmanEnvironment).unwrap();
+    }));
+    environment_actions.add_action(&import_environment);
+
+    let export_environment = gio::SimpleAction::new("export-postman", None);
+    export_environment.connect_activate(glib::clone!(@strong sender => move |_, _| {
+        sender.send(Action::ExportPostmanEnvironment).unwrap();
+    }));
+    environment_actions.add_action(&export_environment);
+
+    let delete_environment = gio::SimpleAction::new("delete", None);
+    delete_environment.connect_activate(glib::clone!(@strong sender => move |_, _| {
+        sender.send(Action::DeleteEnvironment).unwrap();
+    }));
+    environment_actions.add_action(&delete_environment);
+
+    window.insert_action_group("environments", Some(&environment_actions));
+
+    // Actions behind the contract tab menu
+    let contract_actions = gio::SimpleActionGroup::new();
+
+    let link_openapi = gio::SimpleAction::new("link-openapi", None);
+    link_openapi.connect_activate(glib::clone!(@strong sender => move |_, _| {
+        sender.send(Action::LinkOpenApiContract).unwrap();
+    }));
+    contract_actions.add_action(&link_openapi);
+
+    let link_schema = gio::SimpleAction::new("link-schema", None);
+    link_schema.connect_activate(glib::clone!(@strong sender => move |_, _| {
+        sender.send(Action::LinkJsonSchemaContract).unwrap();
+    }));
+    contract_actions.add_action(&link_schema);
+
+    let unlink = gio::SimpleAction::new("unlink", None);
+    unlink.connect_activate(glib::clone!(@strong sender => move |_, _| {
+        sender.send(Action::UpdateContract(None)).unwrap();
+    }));
+    contract_actions.add_action(&unlink);
+
+    window.insert_action_group("contract", Some(&contract_actions));
+
+    // Actions behind the snapshot tab menu
+    let snapshot_actions = gio::SimpleActionGroup::new();
+
+    let record_snapshot = gio::SimpleAction::new("record", None);
+    record_snapshot.connect_activate(glib::clone!(@strong sender => move |_, _| {
+        sender.send(Action::RecordSnapshot).unwrap();
+    }));
+    snapshot_actions.add_action(&record_snapshot);
+
+    let compare_snapshot = gio::SimpleAction::new("compare", None);
+    compare_snapshot.connect_activate(glib::clone!(@strong sender => move |_, _| {
+        sender.send(Action::CompareWithSnapshot).unwrap();
+    }));
+    snapshot_actions.add_action(&compare_snapshot);
+
+    let delete_snapshot = gio::SimpleAction::new("delete", None);
+    delete_snapshot.connect_activate(glib::clone!(@strong sender => move |_, _| {
+        sender.send(Action::DeleteSnapshot).unwrap();
+    }));
+    snapshot_actions.add_action(&delete_snapshot);
+
+    window.insert_action_group("snapshot", Some(&snapshot_actions));
+
+    let snapshot_view = widgets.snapshot_view.clone();
+    widgets
+        .snapshot_view
+        .ignored_row
+        .connect_apply(glib::clone!(@strong sender => move |_| {
+            sender
+                .send(Action::SetSnapshotIgnored(snapshot_view.ignored()))
+                .unwrap();
+        }));
+
+    // Collection runner dialog
+    widgets
+        .runner_view
+        .start_button
+        .connect_clicked(glib::clone!(@strong sender => move |_| {
+            sender.send(Action::StartRun).unwrap();
+        }));
+
+    widgets
+        .runner_view
+        .stop_button
+        .connect_clicked(glib::clone!(@strong sender => move |_| {
+            sender.send(Action::StopRun).unwrap();
+        }));
+
+    widgets
+        .runner_view
+        .data_button
+        .connect_clicked(glib::clone!(@strong sender => move |_| {
+            sender.send(Action::ChooseRunData).unwrap();
+        }));
+
+    widgets.runner_view.data_clear_button.connect_clicked(
+        glib::clone!(@strong sender => move |_| {
+            sender.send(Action::ClearRunData).unwrap();
+        }),
+    );
+
+    widgets
+        .runner_view
+        .accept_button
+        .connect_clicked(glib::clone!(@strong sender => move |_| {
+            sender.send(Action::AcceptRunSnapshots).unwrap();
+        }));
+
+    let runner_actions = gio::SimpleActionGroup::new();
+
+    let export_junit = gio::SimpleAction::new("export-junit", None);
+    export_junit.connect_activate(glib::clone!(@strong sender => move |_, _| {
+        sender.send(Action::ExportRunReport(ReportFormat::JUnit)).unwrap();
+    }));
+    runner_actions.add_action(&export_junit);
+
+    let export_json = gio::SimpleAction::new("export-json", None);
+    export_json.connect_activate(glib::clone!(@strong sender => move |_, _| {
+        sender.send(Action::ExportRunReport(ReportFormat::Json)).unwrap();
+    }));
+    runner_actions.add_action(&export_json);
+
+    let export_failed_rows = gio::SimpleAction::new("export-failed-rows", None);
+    export_failed_rows.connect_activate(glib::clone!(@strong sender => move |_, _| {
+        sender.send(Action::ExportFailedRows).unwrap();
+    }));
+    runner_actions.add_action(&export_failed_rows);
+
+    widgets
+        .runner_view
+        .dialog
+        .insert_action_group("runner", Some(&runner_actions));
+
+    // Index 0 is "No Environment", the rest follow `AppState::environments`
+    widgets.environment_dropdown.connect_selected_notify(
+        glib::clone!(@strong sender, @strong state => move |dropdown| {
+            let id = match dropdown.selected() {
+                0 | gtk::INVALID_LIST_POSITION => None,
+                index => state
+                    .borrow()
+                    .environments
+                    .get(index as usize - 1)
+                    .map(|e| e.id),
+            };
+            sender.send(Action::SelectEnvironment(id)).unwrap();
+        }),
+    );
+
+    widgets
+        .collections_list
         .connect_row_activated(glib::clone!(@strong sender => move |_, row| {
-            let id_str = row.widget_name();
-            if let Ok(id) = id_str.parse::<i64>() {
-                sender.send(Action::LoadHistoryItem(id)).unwrap();
+            if let Some(RowTarget::Item(id)) = collections_view::row_target(row) {
+                sender.send(Action::LoadCollectionItem(id)).unwrap();
             }
         }));
 
+    widgets.history_search.connect_changed(
+        glib::clone!(@strong sender => move |filter, grouping| {
+            sender.send(Action::SearchHistory(filter, grouping)).unwrap();
+        }),
+    );
+
+    let history_model = widgets.history_model.clone();
+    widgets.history_list.connect_activate(
+        glib::clone!(@strong sender, @strong history_model => move |_, position| {
+            if let Some(id) = history_model.row_id(position) {
+                sender.send(Action::LoadHistoryItem(id)).unwrap();
+            }
+        }),
+    );
+
+    // Prune history the retention policy no longer keeps
+    sender.send(Action::RunMaintenance).unwrap();
+
     window.present();
 }
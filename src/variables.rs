@@ -0,0 +1,303 @@
+use crate::models::{Request, Variable};
+use crate::secrets;
+use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
+use chrono::{SecondsFormat, Utc};
+use rand::{Rng, seq::SliceRandom};
+use std::collections::HashMap;
+
+pub type Scope = HashMap<String, String>;
+
+/// Merges variable layers; later layers win, as an environment overrides a
+/// collection. Locked secrets are left out, so their placeholders stay unresolved.
+pub fn scope(layers: &[&[Variable]]) -> Scope {
+    let mut scope = Scope::new();
+    for layer in layers {
+        for variable in layer
+            .iter()
+            .filter(|v| v.enabled && !v.key.is_empty() && !secrets::is_locked(v))
+        {
+            scope.insert(variable.key.clone(), variable.value.clone());
+        }
+    }
+    scope
+}
+
+/// Built-in placeholders generated each time they are substituted, so every
+/// occurrence gets a new value. `$randomInt` takes an optional `min max` and
+/// `$base64` the text to encode.
+pub const DYNAMIC_VARIABLES: [&str; 11] = [
+    "$uuid",
+    "$guid",
+    "$timestamp",
+    "$isoTimestamp",
+    "$randomInt",
+    "$randomBoolean",
+    "$randomFirstName",
+    "$randomLastName",
+    "$randomName",
+    "$randomEmail",
+    "$base64",
+];
+
+const FIRST_NAMES: [&str; 16] = [
+    "Ada",
+    "Alan",
+    "Barbara",
+    "Claude",
+    "Dennis",
+    "Edsger",
+    "Frances",
+    "Grace",
+    "Guido",
+    "Joan",
+    "John",
+    "Katherine",
+    "Ken",
+    "Linus",
+    "Margaret",
+    "Radia",
+];
+
+const LAST_NAMES: [&str; 16] = [
+    "Allen",
+    "Backus",
+    "Dijkstra",
+    "Hamilton",
+    "Hopper",
+    "Johnson",
+    "Kernighan",
+    "Knuth",
+    "Lamport",
+    "Liskov",
+    "Lovelace",
+    "Perlman",
+    "Ritchie",
+    "Shannon",
+    "Thompson",
+    "Turing",
+];
+
+/// Replaces `{{name}}` placeholders, then built-in `{{$name}}` ones. Unknown
+/// names are left untouched so they stay visible in the sent request.
+pub fn substitute(text: &str, scope: &Scope) -> String {
+    // Variables go first so they can be arguments, as in `{{$base64 {{user}}:{{password}}}}`
+    let text = replace(text, false, |name| scope.get(name).cloned());
+    replace(&text, true, dynamic_value)
+}
+
+/// Replaces the placeholders `resolve` knows. `dynamic` picks whether names
+/// starting with `$` are the ones replaced or the ones passed over.
+fn replace(text: &str, dynamic: bool, resolve: impl Fn(&str) -> Option<String>) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find("{{") {
+        result.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+
+        // Keep scanning inside, for placeholders in a dynamic one's arguments
+        if after.trim_start().starts_with('$') != dynamic {
+            result.push_str("{{");
+            rest = after;
+            continue;
+        }
+
+        // A dynamic placeholder ends at the `}}` matching its `{{`, past any
+        // placeholders left unresolved in its arguments
+        let end = if dynamic {
+            closing(after)
+        } else {
+            after.find("}}")
+        };
+        match end {
+            Some(end) => {
+                let name = after[..end].trim();
+                let value = if name.contains("{{") {
+                    None
+                } else {
+                    resolve(name)
+                };
+                match value {
+                    Some(value) => result.push_str(&value),
+                    None => result.push_str(&rest[start..start + 2 + end + 2]),
+                }
+                rest = &after[end + 2..];
+            }
+            None => {
+                result.push_str(&rest[start..]);
+                rest = "";
+            }
+        }
+    }
+
+    result.push_str(rest);
+    result
+}
+
+/// Where the `}}` closing a placeholder starts in `after`, the text following
+/// its `{{`, counting the placeholders nested inside it.
+fn closing(after: &str) -> Option<usize> {
+    let mut depth = 0;
+    let mut index = 0;
+    while index < after.len() {
+        let rest = &after[index..];
+        if rest.starts_with("{{") {
+            depth += 1;
+            index += 2;
+        } else if rest.starts_with("}}") {
+            if depth == 0 {
+                return Some(index);
+            }
+            depth -= 1;
+            index += 2;
+        } else {
+            index += rest.chars().next().map_or(1, char::len_utf8);
+        }
+    }
+    None
+}
+
+/// The value of a built-in placeholder such as `$randomInt 1 6`, or `None`
+/// when the name or its arguments are not understood.
+fn dynamic_value(placeholder: &str) -> Option<String> {
+    let (name, argument) = placeholder
+        .split_once(char::is_whitespace)
+        .map_or((placeholder, ""), |(name, argument)| {
+            (name, argument.trim())
+        });
+    let mut rng = rand::thread_rng();
+
+    let value = match name {
+        "$uuid" | "$guid" => uuid::Uuid::new_v4().to_string(),
+        "$timestamp" => Utc::now().timestamp().to_string(),
+        "$isoTimestamp" => Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
+        "$randomInt" => {
+            let bounds = argument
+                .split_whitespace()
+                .map(str::parse::<i64>)
+                .collect::<Result<Vec<_>, _>>()
+                .ok()?;
+            let (min, max) = match bounds[..] {
+                [] => (0, 1000),
+                // A negative bound counts down from zero
+                [max] => (0.min(max), 0.max(max)),
+                [min, max] => (min.min(max), min.max(max)),
+                _ => return None,
+            };
+            rng.gen_range(min..=max).to_string()
+        }
+        "$randomBoolean" => rng.gen_bool(0.5).to_string(),
+        "$randomFirstName" => pick(&FIRST_NAMES, &mut rng).to_string(),
+        "$randomLastName" => pick(&LAST_NAMES, &mut rng).to_string(),
+        "$randomName" => format!(
+            "{} {}",
+            pick(&FIRST_NAMES, &mut rng),
+            pick(&LAST_NAMES, &mut rng)
+        ),
+        "$randomEmail" => format!(
+            "{}.{}{}@example.com",
+            pick(&FIRST_NAMES, &mut rng).to_lowercase(),
+            pick(&LAST_NAMES, &mut rng).to_lowercase(),
+            rng.gen_range(1..1000)
+        ),
+        "$base64" => BASE64.encode(argument),
+        _ => return None,
+    };
+    Some(value)
+}
+
+fn pick<'a>(names: &[&'a str], rng: &mut impl Rng) -> &'a str {
+    names.choose(rng).copied().unwrap_or_default()
+}
+
+pub fn resolve_request(request: &Request, scope: &Scope) -> Request {
+    Request {
+        url: substitute(&request.url, scope),
+        headers: request
+            .headers
+            .iter()
+            .map(|(k, v)| (substitute(k, scope), substitute(v, scope)))
+            .collect(),
+        body: substitute(&request.body, scope),
+        ..request.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scope_of(pairs: &[(&str, &str)]) -> Scope {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    fn random_int(placeholder: &str) -> i64 {
+        dynamic_value(placeholder).unwrap().parse().unwrap()
+    }
+
+    #[test]
+    fn substitutes_variables_and_keeps_unknown_ones() {
+        let scope = scope_of(&[("host", "example.com"), ("id", "7")]);
+        assert_eq!(
+            substitute("https://{{host}}/users/{{ id }}?q={{missing}}", &scope),
+            "https://example.com/users/7?q={{missing}}"
+        );
+    }
+
+    #[test]
+    fn leaves_unclosed_and_unknown_dynamic_placeholders() {
+        let scope = Scope::new();
+        assert_eq!(substitute("{{$nope}} {{host", &scope), "{{$nope}} {{host");
+    }
+
+    #[test]
+    fn resolves_variables_inside_dynamic_arguments() {
+        let scope = scope_of(&[("user", "ada"), ("password", "secret")]);
+        assert_eq!(
+            substitute("Basic {{$base64 {{user}}:{{password}}}}", &scope),
+            format!("Basic {}", BASE64.encode("ada:secret"))
+        );
+    }
+
+    #[test]
+    fn leaves_dynamic_placeholders_with_unresolved_arguments() {
+        let scope = scope_of(&[("user", "ann")]);
+        assert_eq!(
+            substitute("{{$base64 {{missing}}}} {{$base64 {{user}}}}", &scope),
+            "{{$base64 {{missing}}}} YW5u"
+        );
+        assert_eq!(
+            substitute("{{$base64 a{{missing}}b}}!", &scope),
+            "{{$base64 a{{missing}}b}}!"
+        );
+    }
+
+    #[test]
+    fn random_int_stays_within_bounds() {
+        for _ in 0..200 {
+            assert!((0..=1000).contains(&random_int("$randomInt")));
+            assert!((0..=5).contains(&random_int("$randomInt 5")));
+            assert!((-5..=0).contains(&random_int("$randomInt -5")));
+            assert!((1..=6).contains(&random_int("$randomInt 1 6")));
+            assert!((1..=6).contains(&random_int("$randomInt 6 1")));
+        }
+    }
+
+    #[test]
+    fn rejects_arguments_it_does_not_understand() {
+        assert_eq!(dynamic_value("$randomInt one"), None);
+        assert_eq!(dynamic_value("$randomInt 1 2 3"), None);
+        assert_eq!(dynamic_value("$unknown"), None);
+    }
+
+    #[test]
+    fn generates_each_occurrence_separately() {
+        let text = substitute("{{$uuid}} {{$uuid}}", &Scope::new());
+        let (first, second) = text.split_once(' ').unwrap();
+        assert_eq!(first.len(), 36);
+        assert_ne!(first, second);
+    }
+}